@@ -0,0 +1,198 @@
+//! t2s_stage_decoder 解码循环每步耗时，对比每步拷贝输出、拼接掩码的原实现与复用输出和 DecodeBuffers 的现实现
+//!
+//! 需要 assets 下导出的 t2s 模型，张量名按 model_manifest.json 解析，两种实现都跑固定步数，不因结束符提前停止
+//!
+//! cargo bench -p sovits --bench t2s_decode
+use ndarray::{concatenate, Array1, Array2, Array3, Array4, Axis, Ix2, Ix3, Ix4};
+use ort::{inputs, value::DynValue};
+use sovits::decode_utils::DecodeBuffers;
+use sovits::model_manifest::{ModelManifest, ModelSession};
+use sovits::session_config::SessionConfig;
+use std::path::Path;
+use std::time::Instant;
+
+const MODEL_DIR: &str = "../assets";
+const PHONES: usize = 120;
+const PROMPT: usize = 200;
+const STEPS: usize = 300;
+
+/// 第一阶段的输出
+struct FirstStage {
+    y: Array2<i64>,
+    k: Array4<f32>,
+    v: Array4<f32>,
+    y_emb: Array3<f32>,
+}
+
+fn first_stage(session: &ModelSession) -> anyhow::Result<FirstStage> {
+    let all_phoneme_ids: Array2<i64> = Array2::from_elem((1, PHONES), 10);
+    let bert: Array3<f32> = Array3::zeros((1, 1024, PHONES));
+    let prompt: Array2<i64> = Array2::zeros((1, PROMPT));
+    let top_k = Array1::from(vec![20i64]);
+    let temperature = Array1::from(vec![0.8f32]);
+    let out = session.run(inputs![
+        session.input("all_phoneme_ids") => all_phoneme_ids.view(),
+        session.input("bert") => bert.view(),
+        session.input("prompt") => prompt.view(),
+        session.input("top_k") => top_k.view(),
+        session.input("temperature") => temperature.view(),
+    ]?)?;
+    Ok(FirstStage {
+        y: out[session.output("y")]
+            .try_extract_tensor::<i64>()?
+            .into_dimensionality::<Ix2>()?
+            .to_owned(),
+        k: out[session.output("k")]
+            .try_extract_tensor::<f32>()?
+            .into_dimensionality::<Ix4>()?
+            .to_owned(),
+        v: out[session.output("v")]
+            .try_extract_tensor::<f32>()?
+            .into_dimensionality::<Ix4>()?
+            .to_owned(),
+        y_emb: out[session.output("y_emb")]
+            .try_extract_tensor::<f32>()?
+            .into_dimensionality::<Ix3>()?
+            .to_owned(),
+    })
+}
+
+/// 原实现：每步拼接 y 和掩码，输出拷贝成 host 数组再作为下一步输入
+fn old_loop(session: &ModelSession, first: &FirstStage) -> anyhow::Result<usize> {
+    let top_k = Array1::from(vec![20i64]);
+    let temperature = Array1::from(vec![0.8f32]);
+    let x_example: Array2<f32> = Array2::zeros((1, PHONES));
+    let mut y = first.y.clone();
+    let mut k = first.k.clone();
+    let mut v = first.v.clone();
+    let mut y_emb = first.y_emb.clone();
+    let mut y_example: Array2<f32> = Array2::zeros((1, y_emb.shape()[1]));
+    let y_example_0: Array2<f32> = Array2::zeros((1, 1));
+
+    for _ in 1..=STEPS {
+        y_example = concatenate(Axis(1), &[y_example.view(), y_example_0.view()]).unwrap();
+        let xy_attn_mask: Array4<f32> = concatenate(Axis(1), &[x_example.view(), y_example.view()])
+            .unwrap()
+            .insert_axis(Axis(0))
+            .insert_axis(Axis(0));
+        let out = session.run(inputs![
+            session.input("y") => y.view(),
+            session.input("k") => k.view(),
+            session.input("v") => v.view(),
+            session.input("y_emb") => y_emb.view(),
+            session.input("xy_attn_mask") => xy_attn_mask.view(),
+            session.input("top_k") => top_k.view(),
+            session.input("temperature") => temperature.view(),
+        ]?)?;
+        k = out[session.output("o_k")]
+            .try_extract_tensor::<f32>()?
+            .into_dimensionality::<Ix4>()?
+            .into_owned();
+        v = out[session.output("o_v")]
+            .try_extract_tensor::<f32>()?
+            .into_dimensionality::<Ix4>()?
+            .into_owned();
+        y_emb = out[session.output("o_y_emb")]
+            .try_extract_tensor::<f32>()?
+            .into_dimensionality::<Ix3>()?
+            .into_owned();
+        let samples = out[session.output("samples")]
+            .try_extract_tensor::<i64>()?
+            .into_dimensionality::<Ix2>()?
+            .into_owned();
+        y = concatenate(Axis(1), &[y.view(), samples.view()]).unwrap();
+    }
+    Ok(y.len())
+}
+
+/// 现实现：k、v、y_emb 输出直接作为下一步输入，y 和掩码来自预分配的缓冲区
+fn new_loop(session: &ModelSession, first: &FirstStage) -> anyhow::Result<usize> {
+    let top_k = Array1::from(vec![20i64]);
+    let temperature = Array1::from(vec![0.8f32]);
+    let y_emb_len = first.y_emb.shape()[1];
+    let mut buffers = DecodeBuffers::new(first.y.as_slice().unwrap(), PHONES, y_emb_len, STEPS);
+    let mut k: DynValue = first.k.clone().try_into()?;
+    let mut v: DynValue = first.v.clone().try_into()?;
+    let mut y_emb: DynValue = first.y_emb.clone().try_into()?;
+
+    for step in 1..=STEPS {
+        let mut out = session.run(inputs![
+            session.input("y") => buffers.y(),
+            session.input("k") => k,
+            session.input("v") => v,
+            session.input("y_emb") => y_emb,
+            session.input("xy_attn_mask") => buffers.attn_mask(step),
+            session.input("top_k") => top_k.view(),
+            session.input("temperature") => temperature.view(),
+        ]?)?;
+        k = out
+            .remove(session.output("o_k"))
+            .expect("Failed to get o_k");
+        v = out
+            .remove(session.output("o_v"))
+            .expect("Failed to get o_v");
+        y_emb = out
+            .remove(session.output("o_y_emb"))
+            .expect("Failed to get o_y_emb");
+        let sample = out[session.output("samples")]
+            .try_extract_raw_tensor::<i64>()?
+            .1[0];
+        buffers.push(sample);
+    }
+    Ok(buffers.len())
+}
+
+fn bench(
+    name: &str,
+    session: &ModelSession,
+    first: &FirstStage,
+    f: fn(&ModelSession, &FirstStage) -> anyhow::Result<usize>,
+) {
+    let start = Instant::now();
+    let len = f(session, first).expect("Failed to run t2s_stage_decoder");
+    let elapsed = start.elapsed();
+    println!(
+        "{}: {} steps, y len {}, total {}ms, {:.3}ms/step",
+        name,
+        STEPS,
+        len,
+        elapsed.as_millis(),
+        elapsed.as_secs_f64() * 1000.0 / STEPS as f64
+    );
+}
+
+fn main() {
+    let manifest = ModelManifest::load(format!("{}/model_manifest.json", MODEL_DIR))
+        .expect("Failed to load model manifest");
+    let path = |name: &str| {
+        manifest
+            .model_file(name)
+            .map(|model| format!("{}/{}", MODEL_DIR, model.file))
+            .expect("Failed to get model file")
+    };
+    let first_stage_path = path("t2s_first_stage_decoder");
+    let stage_path = path("t2s_stage_decoder");
+    if !Path::new(&first_stage_path).exists() || !Path::new(&stage_path).exists() {
+        println!("{} or {} not found, skipped", first_stage_path, stage_path);
+        return;
+    }
+
+    // 与引擎一样经 manifest 校验并绑定张量名
+    let config = SessionConfig::default();
+    let load = |name: &str, path: &str| {
+        let session = config
+            .load(name, path)
+            .unwrap_or_else(|e| panic!("Failed to load {}: {}", name, e));
+        manifest
+            .model_session(name, session)
+            .unwrap_or_else(|e| panic!("{:#}", e))
+    };
+    let first_stage_session = load("t2s_first_stage_decoder", &first_stage_path);
+    let session = load("t2s_stage_decoder", &stage_path);
+    let first = first_stage(&first_stage_session).expect("Failed to run t2s_first_stage_decoder");
+
+    // 预热一次，避免首次运行的初始化计入
+    new_loop(&session, &first).expect("Failed to run t2s_stage_decoder");
+    bench("old", &session, &first, old_loop);
+    bench("new", &session, &first, new_loop);
+}
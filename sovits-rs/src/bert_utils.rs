@@ -1,58 +1,130 @@
 use super::audio_utils::AudioUtils;
-use super::text_utils::{CleanedText, TextUtils, CHINESE_LANG};
-use ndarray::{s, Array1, Array2, Array3, Array4, Axis};
-use ort::{inputs, session::Session};
+use super::decode_utils::{BatchConfig, DecodeBuffers, DecodeScheduler, EOS, MAX_DECODE_STEPS};
+use super::model_manifest::{ModelManifest, ModelSession};
+use super::session_config::{ModelSessionsConfig, SessionConfig};
+use super::text::chinese::WordTrace;
+use super::text::en_bert::{EnglishBert, FEATURE_DIM};
+use super::text::en_lexicon::EnglishLexiconConfig;
+use super::text::g2pw::G2pw;
+use super::text::zh_lexicon::ChineseLexiconConfig;
+use super::text_utils::{
+    CleanedText, LanguageOption, Segmenter, TextUtils, CHINESE_LANG, ENGLISH_LANG,
+};
+use super::voice_bundle::{source_checksum, VoiceBundle};
+use anyhow::{bail, Context};
+use log::{info, warn};
+use ndarray::{s, Array1, Array2, Array3, Axis};
+use ort::{inputs, session::SessionOutputs};
 use serde::Deserialize;
 use std::cmp::Ordering;
 use std::f32::consts::PI;
 use std::fs::File;
 use std::io::Read;
+use std::path::Path;
+use std::sync::Arc;
 use std::time::Instant;
 use tokenizers::Tokenizer;
 
+const TOP_K: i64 = 20;
+const TEMPERATURE: f32 = 0.8;
+/// g2pW 多音字模型及其多音字表，模型文件不存在时不启用
+const G2PW_MODEL_PATH: &str = "../assets/g2pw/g2pw.onnx";
+const G2PW_VOCAB_PATH: &str = "../assets/g2pw/polyphonic_chars.txt";
+/// 英文 bert 及其 tokenizer，模型文件不存在时英文特征为全零
+const EN_BERT_MODEL_PATH: &str = "../assets/en_bert/model.onnx";
+const EN_BERT_TOKENIZER_PATH: &str = "../assets/en_bert/tokenizer.json";
+/// 中文用户词典及 jieba 自定义词典，文件不存在时跳过
+
 #[derive(Deserialize)]
 struct RefWavConfig {
     ref_wav_path: Option<String>,
     ref_words: Option<String>,
+    /// 预处理好的语音包，与参考音频一致时直接加载
+    voice_bundle: Option<String>,
 }
 struct ModelSessions {
-    pub bert_model: Session,
-    pub ssl_model: Session,
-    pub vq_model_latent: Session,
-    pub t2s_first_stage_decoder: Session,
-    pub t2s_stage_decoder: Session,
-    pub vq_model: Session,
+    pub bert_model: ModelSession,
+    pub ssl_model: ModelSession,
+    pub vq_model_latent: ModelSession,
+    pub t2s_first_stage_decoder: ModelSession,
+    pub t2s_stage_decoder: Arc<ModelSession>,
+    pub vq_model: ModelSession,
+    /// 可选的英文 bert，没有时英文特征为全零
+    pub en_bert: Option<EnglishBert>,
 }
 
 impl ModelSessions {
-    fn load_model(model_path: &str) -> ort::Result<Session> {
-        Session::builder()?.commit_from_file(model_path)
+    /// 加载模型并按 manifest 校验文件和输入输出
+    fn load_model(
+        name: &str,
+        model_dir: &str,
+        manifest: &ModelManifest,
+        config: &SessionConfig,
+    ) -> anyhow::Result<ModelSession> {
+        let model_path = format!("{}/{}", model_dir, manifest.model_file(name)?.file);
+        manifest.verify_checksum(name, &model_path)?;
+        let session = config
+            .load(name, &model_path)
+            .with_context(|| format!("Failed to load {} from {}", name, model_path))?;
+        manifest.model_session(name, session)
     }
 
-    pub fn from_file(
-        bert_model_path: &str,
-        ssl_model_path: &str,
-        vq_model_latent_path: &str,
-        t2s_first_stage_decoder_path: &str,
-        t2s_stage_decoder_path: &str,
-        vq_model_path: &str,
-    ) -> Self {
-        Self {
-            bert_model: Self::load_model(bert_model_path).expect("Failed to load bert_model"),
-            ssl_model: Self::load_model(ssl_model_path).expect("Failed to load ssl_model"),
-            vq_model_latent: Self::load_model(vq_model_latent_path)
-                .expect("Failed to load vq_model_latent"),
-            t2s_first_stage_decoder: Self::load_model(t2s_first_stage_decoder_path)
-                .expect("Failed to load t2s_first_stage_decoder"),
-            t2s_stage_decoder: Self::load_model(t2s_stage_decoder_path)
-                .expect("Failed to load t2s_stage_decoder"),
-            vq_model: Self::load_model(vq_model_path).expect("Failed to load vq_model"),
+    pub fn from_manifest(
+        model_dir: &str,
+        manifest: &ModelManifest,
+        config: &ModelSessionsConfig,
+    ) -> anyhow::Result<Self> {
+        let load = |name: &str, session_config: SessionConfig| {
+            Self::load_model(name, model_dir, manifest, &session_config)
+        };
+        Ok(Self {
+            bert_model: load("bert_model", config.bert_model())?,
+            ssl_model: load("ssl_model", config.ssl_model())?,
+            vq_model_latent: load("vq_model_latent", config.vq_model_latent())?,
+            t2s_first_stage_decoder: load(
+                "t2s_first_stage_decoder",
+                config.t2s_first_stage_decoder(),
+            )?,
+            t2s_stage_decoder: Arc::new(load("t2s_stage_decoder", config.t2s_stage_decoder())?),
+            vq_model: load("vq_model", config.vq_model())?,
+            en_bert: Self::load_en_bert(config)?,
+        })
+    }
+
+    /// 英文 bert 不在 manifest 中，模型文件存在时才加载
+    fn load_en_bert(config: &ModelSessionsConfig) -> anyhow::Result<Option<EnglishBert>> {
+        if !Path::new(EN_BERT_MODEL_PATH).exists() {
+            return Ok(None);
+        }
+        let en_bert = EnglishBert::new(
+            EN_BERT_MODEL_PATH,
+            EN_BERT_TOKENIZER_PATH,
+            &config.en_bert(),
+        )?;
+        info!("english bert loaded from {}", EN_BERT_MODEL_PATH);
+        Ok(Some(en_bert))
+    }
+
+    fn voice_models(&self) -> VoiceModels<'_> {
+        VoiceModels {
+            bert_model: &self.bert_model,
+            ssl_model: &self.ssl_model,
+            vq_model_latent: &self.vq_model_latent,
+            en_bert: self.en_bert.as_ref(),
         }
     }
 
     // 添加其他方法来操作这些 Session 对象
 }
 
+/// 预处理参考音频用到的模型，生成语音包时不需要加载 t2s 和 vq_model
+struct VoiceModels<'a> {
+    bert_model: &'a ModelSession,
+    ssl_model: &'a ModelSession,
+    vq_model_latent: &'a ModelSession,
+    en_bert: Option<&'a EnglishBert>,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct BertFeatures {
     pub features: Array2<f32>,
@@ -60,15 +132,42 @@ pub struct BertFeatures {
     pub norm_text_str: String,
 }
 
+/// 创建 ChBertUtils 的配置
+#[derive(Debug, Clone)]
+pub struct EngineConfig {
+    pub sessions: ModelSessionsConfig,
+    /// max_batch_size 大于 1 时，t2s 解码交给合批调度线程
+    pub batch: BatchConfig,
+    /// 模型 Session 的组数，供多个分段并行合成
+    pub session_pool_size: usize,
+    /// 语种分段方式
+    pub segmenter: Segmenter,
+    /// 中文用户词典和 jieba 自定义词典
+    pub chinese_lexicon: ChineseLexiconConfig,
+    /// 英文 CMUdict、用户词典及查词顺序
+    pub english_lexicon: EnglishLexiconConfig,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            sessions: ModelSessionsConfig::default(),
+            batch: BatchConfig::default(),
+            session_pool_size: 1,
+            segmenter: Segmenter::default(),
+            chinese_lexicon: ChineseLexiconConfig::default(),
+            english_lexicon: EnglishLexiconConfig::default(),
+        }
+    }
+}
+
 pub struct ChBertUtils {
-    tokenizer: Tokenizer,
-    ref_words: String,
+    tokenizer: Arc<Tokenizer>,
     text_util: TextUtils,
-    model_sessions: ModelSessions,
-    wav16k_arr: Array2<f32>,
-    wav32k_arr: Array2<f32>,
-    features: Array2<f32>,
-    phones_list_unpack: Vec<usize>,
+    session_pool: Vec<ModelSessions>,
+    voice: VoiceBundle,
+    manifest: ModelManifest,
+    decode_scheduler: Option<DecodeScheduler>,
 }
 pub fn hanning(m: i64) -> Array1<f32> {
     match m.cmp(&1) {
@@ -94,20 +193,24 @@ impl Default for ChBertUtils {
 
 impl ChBertUtils {
     pub fn new() -> Self {
-        let tokenizer = Tokenizer::from_file("../assets/tokenizer.json").unwrap();
+        Self::with_session_config(&ModelSessionsConfig::default())
+    }
 
-        let sampling_rate: i32 = 32000;
-        let zero_sampling_len = (sampling_rate as f32 * 0.3) as usize;
-        let zero_wav: Array1<f32> = Array1::zeros((zero_sampling_len,));
+    /// 按配置创建各模型的 Session
+    pub fn with_session_config(session_config: &ModelSessionsConfig) -> Self {
+        Self::with_config(&EngineConfig {
+            sessions: session_config.clone(),
+            ..EngineConfig::default()
+        })
+    }
 
-        let text_util = TextUtils::new(
-            "../assets/eng_dict.json",
-            "../assets/rep_map.json",
-            "../assets/model.npz",
-            "../assets/PHRASES_DICT.json",
-            "../assets/PINYIN_DICT.json",
-        )
-        .expect("Failed to create text_util");
+    pub fn with_config(engine_config: &EngineConfig) -> Self {
+        let session_config = &engine_config.sessions;
+        let tokenizer = Arc::new(Tokenizer::from_file("../assets/tokenizer.json").unwrap());
+
+        let manifest = ModelManifest::load("../assets/model_manifest.json")
+            .unwrap_or_else(|e| panic!("{:#}", e));
+        let text_util = Self::load_text_util(&manifest, engine_config, &tokenizer);
 
         let mut file = File::open("../assets/ref_wav.json").expect("Failed to open ref_wav.json");
         let mut contents = String::new();
@@ -124,8 +227,144 @@ impl ChBertUtils {
         );
         let ref_words = config.ref_words.unwrap_or("今天天气不错，我准备去打篮球。I am going to play basketball today. 我的房间号是 404，希望一切顺利。".to_string());
 
-        let wav16k: Vec<i16> = AudioUtils::decode_path_to_data(&ref_wav_path, 16000).unwrap();
-        let wav32k: Vec<i16> = AudioUtils::decode_path_to_data(&ref_wav_path, 32000).unwrap();
+        let session_pool: Vec<ModelSessions> = (0..engine_config.session_pool_size.max(1))
+            .map(|_| {
+                ModelSessions::from_manifest("../assets", &manifest, session_config)
+                    .unwrap_or_else(|e| panic!("{:#}", e))
+            })
+            .collect();
+        let voice = Self::load_voice(
+            &manifest,
+            config.voice_bundle.as_deref(),
+            &ref_wav_path,
+            &ref_words,
+            &tokenizer,
+            &text_util,
+            &session_pool[0].voice_models(),
+        );
+        let decode_scheduler = (engine_config.batch.max_batch_size > 1).then(|| {
+            DecodeScheduler::new(
+                session_pool[0].t2s_stage_decoder.clone(),
+                &engine_config.batch,
+                TOP_K,
+                TEMPERATURE,
+            )
+        });
+        Self {
+            tokenizer,
+            text_util,
+            session_pool,
+            voice,
+            decode_scheduler,
+            manifest,
+        }
+    }
+
+    /// 按 manifest 的音素表创建文本前端，加载用户词典和可选的多音字模型
+    fn load_text_util(
+        manifest: &ModelManifest,
+        engine_config: &EngineConfig,
+        tokenizer: &Arc<Tokenizer>,
+    ) -> TextUtils {
+        let symbols = manifest
+            .symbol_table("../assets")
+            .unwrap_or_else(|e| panic!("{:#}", e));
+        let mut text_util = TextUtils::new(
+            "../assets/eng_dict.json",
+            "../assets/rep_map.json",
+            "../assets/model.npz",
+            "../assets/PHRASES_DICT.json",
+            "../assets/PINYIN_DICT.json",
+            "../assets/ja_lexicon.json",
+            "../assets/yue_dict.json",
+            "../assets/zh_dict.json",
+            symbols,
+            engine_config.segmenter,
+        )
+        .expect("Failed to create text_util");
+        // 用户词典可选：缺少的文件跳过，格式错误时告警并使用空词典，修好后可重新加载
+        if let Err(e) = text_util
+            .lang_chinese
+            .load_user_dicts(&engine_config.chinese_lexicon)
+        {
+            warn!("failed to load user lexicon, using empty lexicon: {}", e);
+        }
+        text_util
+            .lang_english
+            .load_lexicons(&engine_config.english_lexicon)
+            .unwrap_or_else(|e| panic!("Failed to load english lexicons: {}", e));
+        // 多音字模型可选，没有模型文件时只查词典
+        if Path::new(G2PW_MODEL_PATH).exists() {
+            let g2pw = G2pw::new(
+                G2PW_MODEL_PATH,
+                G2PW_VOCAB_PATH,
+                &engine_config.sessions.g2pw(),
+                tokenizer.clone(),
+            )
+            .unwrap_or_else(|e| panic!("{:#}", e));
+            text_util.lang_chinese.set_g2pw(g2pw);
+            info!("g2pw loaded from {}", G2PW_MODEL_PATH);
+        }
+        text_util
+    }
+
+    /// 语音包存在且与当前模型、参考音频、参考文本一致时直接加载，否则重新预处理
+    fn load_voice(
+        manifest: &ModelManifest,
+        bundle: Option<&str>,
+        ref_wav_path: &str,
+        ref_words: &str,
+        tokenizer: &Tokenizer,
+        text_util: &TextUtils,
+        models: &VoiceModels,
+    ) -> VoiceBundle {
+        if let Some(bundle) = bundle {
+            let bundle_path = format!("../assets/{}", bundle);
+            match VoiceBundle::load(&bundle_path) {
+                Ok(voice) => match voice.check_manifest(manifest) {
+                    Err(e) => warn!(
+                        "voice bundle {} does not match the model: {}, rebuild from source",
+                        bundle_path, e
+                    ),
+                    Ok(()) => match source_checksum(ref_wav_path, ref_words) {
+                        Ok(checksum) if checksum != voice.source_checksum => {
+                            warn!("voice bundle {} is stale, rebuild from source", bundle_path)
+                        }
+                        // 只部署了语音包时没有参考音频可校验
+                        _ => {
+                            info!("load voice bundle {}", bundle_path);
+                            return voice;
+                        }
+                    },
+                },
+                Err(e) => warn!("load voice bundle {} failed: {}", bundle_path, e),
+            }
+        }
+        Self::make_voice(
+            manifest,
+            ref_wav_path,
+            ref_words,
+            tokenizer,
+            text_util,
+            models,
+        )
+    }
+
+    /// 对参考音频和参考文本做预处理，结果可保存为语音包
+    fn make_voice(
+        manifest: &ModelManifest,
+        ref_wav_path: &str,
+        ref_words: &str,
+        tokenizer: &Tokenizer,
+        text_util: &TextUtils,
+        models: &VoiceModels,
+    ) -> VoiceBundle {
+        let sample_rate = manifest.sample_rate;
+        let zero_sampling_len = (sample_rate as f32 * 0.3) as usize;
+        let zero_wav: Array1<f32> = Array1::zeros((zero_sampling_len,));
+
+        let wav16k: Vec<i16> = AudioUtils::decode_path_to_data(ref_wav_path, 16000).unwrap();
+        let wav32k: Vec<i16> = AudioUtils::decode_path_to_data(ref_wav_path, sample_rate).unwrap();
         let wav16k: Vec<f32> = wav16k.iter().map(|&x| x as f32 / 32768.0).collect();
         let wav32k: Vec<f32> = wav32k.iter().map(|&x| x as f32 / 32768.0).collect();
         let wav16k_arr =
@@ -133,60 +372,113 @@ impl ChBertUtils {
                 .expect("Failed to concatenate wav16k_arr")
                 .insert_axis(Axis(0));
         let wav32k_arr = Array1::from_vec(wav32k).insert_axis(Axis(0));
-        // use cuda
-        // ort::init()
-        //     .with_execution_providers([ort::execution_providers::CUDAExecutionProvider::default()
-        //         .build()
-        //         .error_on_failure()])
-        //     .commit()
-        //     .unwrap();
-        let model_sessions = ModelSessions::from_file(
-            "../assets/bert_model.onnx",
-            "../assets/ssl_model.onnx",
-            "../assets/vq_model_latent.onnx",
-            "../assets/t2s_first_stage_decoder.onnx",
-            "../assets/t2s_stage_decoder.onnx",
-            "../assets/vq_model.onnx",
+
+        let cleaned = text_util.get_cleaned_text_final(ref_words);
+        // 参考文本的音素会写入语音包，不能含有 UNK
+        assert!(
+            cleaned.unknown_phones.is_empty(),
+            "reference words contain phones {:?} unknown to the model symbol set",
+            cleaned.unknown_phones
         );
-        let CleanedText {
-            mut phones_list,
-            word2ph_list,
-            lang_list,
-            norm_text_list,
-        } = text_util.get_cleaned_text_final(&ref_words);
         let BertFeatures {
             features,
             phones_list_unpack,
             ..
-        } = ChBertUtils::get_bert_features(
-            &tokenizer,
-            &model_sessions.bert_model,
-            &mut phones_list,
-            &word2ph_list,
-            &norm_text_list,
-            &lang_list,
-        );
-        Self {
-            tokenizer,
-            ref_words,
-            text_util,
-            model_sessions,
-            wav16k_arr,
-            wav32k_arr,
-            features,
-            phones_list_unpack,
+        } = ChBertUtils::get_bert_features(tokenizer, models.bert_model, models.en_bert, cleaned);
+
+        //float32[batch_sie:1, W:113104]
+        let input_wav16k = inputs![wav16k_arr.view()].expect("Failed to create input_wav16k input");
+        let ssl_content = models
+            .ssl_model
+            .run(input_wav16k)
+            .expect("Failed to run ssl_model");
+        let ssl_content = ssl_content[models.ssl_model.output("output")]
+            .try_extract_tensor::<f32>()
+            .expect("Failed to extract ssl_content tensor");
+
+        // float32[batch_size:1, 768, H:383]
+        let ssl_content: Array3<f32> = ssl_content.view().slice(s![.., .., ..]).to_owned();
+
+        let input_ssl_content =
+            inputs![ssl_content].expect("Failed to create input_ssl_content input");
+        let codes = models
+            .vq_model_latent
+            .run(input_ssl_content)
+            .expect("Failed to run vq_model_latent");
+        let codes = codes[models.vq_model_latent.output("output")]
+            .try_extract_tensor::<i64>()
+            .expect("Failed to extract codes tensor");
+        //[1, 191]
+        let prompt: Array2<i64> = codes.view().slice(s![0, .., ..]).to_owned();
+
+        VoiceBundle {
+            model_version: manifest.version.clone(),
+            symbols: manifest.symbols.clone(),
+            sample_rate,
+            ref_words: ref_words.to_string(),
+            source_checksum: source_checksum(ref_wav_path, ref_words)
+                .expect("Failed to read reference wav"),
+            wav16k: wav16k_arr,
+            wav32k: wav32k_arr,
+            phones: phones_list_unpack,
+            bert_features: features,
+            prompt,
         }
     }
 
+    /// 为参考音频生成语音包，只加载文本前端和预处理参考音频用到的模型
+    pub fn build_voice_bundle(
+        session_config: &ModelSessionsConfig,
+        ref_wav_path: &str,
+        ref_words: &str,
+    ) -> anyhow::Result<VoiceBundle> {
+        let tokenizer =
+            Arc::new(Tokenizer::from_file("../assets/tokenizer.json").map_err(anyhow::Error::msg)?);
+        let manifest = ModelManifest::load("../assets/model_manifest.json")?;
+        let engine_config = EngineConfig {
+            sessions: session_config.clone(),
+            ..EngineConfig::default()
+        };
+        let text_util = Self::load_text_util(&manifest, &engine_config, &tokenizer);
+        let load = |name: &str, config: SessionConfig| {
+            ModelSessions::load_model(name, "../assets", &manifest, &config)
+        };
+        let bert_model = load("bert_model", session_config.bert_model())?;
+        let ssl_model = load("ssl_model", session_config.ssl_model())?;
+        let vq_model_latent = load("vq_model_latent", session_config.vq_model_latent())?;
+        let en_bert = ModelSessions::load_en_bert(session_config)?;
+        let models = VoiceModels {
+            bert_model: &bert_model,
+            ssl_model: &ssl_model,
+            vq_model_latent: &vq_model_latent,
+            en_bert: en_bert.as_ref(),
+        };
+        Ok(Self::make_voice(
+            &manifest,
+            ref_wav_path,
+            ref_words,
+            &tokenizer,
+            &text_util,
+            &models,
+        ))
+    }
+
     // 返回最终的混合中英文句子features
+    // 没有英文 bert 时英文特征为全零
     pub fn get_bert_features(
         tokenizer: &Tokenizer,
-        bert_model: &Session,
-        phones_list: &mut [Vec<usize>],
-        word2ph_list: &[Vec<usize>],
-        norm_text_list: &[String],
-        language_list: &[String],
+        bert_model: &ModelSession,
+        en_bert: Option<&EnglishBert>,
+        cleaned: CleanedText,
     ) -> BertFeatures {
+        let CleanedText {
+            mut phones_list,
+            word2ph_list,
+            lang_list: language_list,
+            norm_text_list,
+            en_words_list,
+            ..
+        } = cleaned;
         let mut features = Vec::new();
         let mut phones_list_unpack = Vec::new();
         let mut norm_text_str = String::new();
@@ -220,7 +512,7 @@ impl ChBertUtils {
                     .run(input_tensor_value)
                     .expect("Failed to run bert model");
 
-                let hidden_states = generator_source["hidden_states"]
+                let hidden_states = generator_source[bert_model.output("hidden_states")]
                     .try_extract_tensor()
                     .unwrap();
                 // [1, 32, 1024] -> [0,1:-1,:]
@@ -252,6 +544,17 @@ impl ChBertUtils {
                 )
                 .unwrap();
                 ndarray::ArrayBase::t(&phone_level_feature).to_owned()
+            } else if language_list[i] == ENGLISH_LANG {
+                en_bert
+                    .and_then(|en_bert| {
+                        en_bert
+                            .features(&en_words_list[i])
+                            .map_err(|e| {
+                                warn!("english bert failed on {}: {:#}", norm_text_list[i], e)
+                            })
+                            .ok()
+                    })
+                    .unwrap_or_else(|| Array2::zeros((FEATURE_DIM, phones_len)))
             } else {
                 Array2::zeros((1024, phones_len))
             };
@@ -269,46 +572,88 @@ impl ChBertUtils {
         }
     }
 
+    /// 逐条执行 t2s 解码循环，返回 pred_semantic
+    fn t2s_decode(
+        &self,
+        sessions: &ModelSessions,
+        mut first_stage_out: SessionOutputs,
+        x_len: usize,
+        top_k: &Array1<i64>,
+        temperature: &Array1<f32>,
+    ) -> anyhow::Result<Array3<i64>> {
+        let first_stage = &sessions.t2s_first_stage_decoder;
+        let stage = &sessions.t2s_stage_decoder;
+        // k、v、y_emb 直接把上一步的输出作为下一步的输入，不再拷贝到 host 数组
+        let mut k = first_stage_out
+            .remove(first_stage.output("k"))
+            .context("Failed to get k")?;
+        let mut v = first_stage_out
+            .remove(first_stage.output("v"))
+            .context("Failed to get v")?;
+        let mut y_emb = first_stage_out
+            .remove(first_stage.output("y_emb"))
+            .context("Failed to get y_emb")?;
+        let y_emb_len = y_emb.shape()?[1] as usize;
+        let (_, y) = first_stage_out[first_stage.output("y")].try_extract_raw_tensor::<i64>()?;
+        let mut buffers = DecodeBuffers::new(y, x_len, y_emb_len, MAX_DECODE_STEPS);
+        drop(first_stage_out);
+
+        let mut loop_idx = 0;
+        for idx in 1..MAX_DECODE_STEPS {
+            let t2s_stage_decoder_input = inputs![
+            stage.input("y") => buffers.y(),
+            stage.input("k") => k,
+            stage.input("v") => v,
+            stage.input("y_emb") => y_emb,
+            stage.input("xy_attn_mask") => buffers.attn_mask(idx),
+            stage.input("top_k") => top_k.view(),
+            stage.input("temperature") => temperature.view(),
+            ]?;
+
+            let mut t2s_stage_decoder_out = stage
+                .run(t2s_stage_decoder_input)
+                .context("Failed to run t2s_stage_decoder")?;
+
+            k = t2s_stage_decoder_out
+                .remove(stage.output("o_k"))
+                .context("Failed to get o_k")?;
+            v = t2s_stage_decoder_out
+                .remove(stage.output("o_v"))
+                .context("Failed to get o_v")?;
+            y_emb = t2s_stage_decoder_out
+                .remove(stage.output("o_y_emb"))
+                .context("Failed to get o_y_emb")?;
+            let logit = t2s_stage_decoder_out[stage.output("logits")]
+                .try_extract_raw_tensor::<i64>()?
+                .1[0];
+            let sample = t2s_stage_decoder_out[stage.output("samples")]
+                .try_extract_raw_tensor::<i64>()?
+                .1[0];
+
+            buffers.push(sample);
+
+            if sample == EOS || logit == EOS {
+                loop_idx = idx;
+                break;
+            }
+        }
+        Ok(buffers.pred_semantic(loop_idx))
+    }
+
     fn infer_wav(
         &self,
+        sessions: &ModelSessions,
         bert_features1: &Array2<f32>,
         bert_features2: &Array2<f32>,
         phones_list_unpack1: &[usize],
         phones_list_unpack2: &[usize],
-    ) -> Vec<i16> {
-        //float32[batch_sie:1, W:113104]
-        let input_wav16k =
-            inputs![self.wav16k_arr.clone()].expect("Failed to create input_wav16k input");
-        let ssl_content = self
-            .model_sessions
-            .ssl_model
-            .run(input_wav16k)
-            .expect("Failed to run ssl_model");
-        let ssl_content = ssl_content["output"]
-            .try_extract_tensor::<f32>()
-            .expect("Failed to extract ssl_content tensor");
-        let hop_length = 640;
-        let win_length = 2048;
-        let hann_window = hanning(win_length);
+    ) -> anyhow::Result<Vec<i16>> {
+        let hop_length = self.manifest.hop_length;
+        let hann_window = hanning(self.manifest.win_length as i64);
+        let prompt = &self.voice.prompt;
 
-        // float32[batch_size:1, 768, H:383]
-        let ssl_content: Array3<f32> = ssl_content.view().slice(s![.., .., ..]).to_owned();
-
-        let input_ssl_content =
-            inputs![ssl_content].expect("Failed to create input_ssl_content input");
-        let codes = self
-            .model_sessions
-            .vq_model_latent
-            .run(input_ssl_content)
-            .expect("Failed to run vq_model_latent");
-        let codes = codes["output"]
-            .try_extract_tensor::<i64>()
-            .expect("Failed to extract codes tensor");
-        //[1, 191]
-        let prompt: Array2<i64> = codes.view().slice(s![0, .., ..]).to_owned();
-
-        let top_k: Array1<i64> = ndarray::Array1::from(vec![20]);
-        let temperature: Array1<f32> = ndarray::Array1::from(vec![0.8]);
+        let top_k: Array1<i64> = ndarray::Array1::from(vec![TOP_K]);
+        let temperature: Array1<f32> = ndarray::Array1::from(vec![TEMPERATURE]);
         //  合并参考的声音
         let bert: Array3<f32> =
             ndarray::concatenate(Axis(1), &[bert_features1.view(), bert_features2.view()])
@@ -328,166 +673,72 @@ impl ChBertUtils {
             .insert_axis(Axis(0))
             .mapv(|x| x as i64);
 
-        let x_example: Array2<f32> =
-            Array2::zeros((all_phoneme_ids.shape()[0], all_phoneme_ids.shape()[1]));
-
+        let first_stage = &sessions.t2s_first_stage_decoder;
         let start_loop = Instant::now();
         let first_stage_decoder_input = inputs![
-            "all_phoneme_ids" => all_phoneme_ids.view(),
-            "bert" => bert.view(),
-            "prompt" => prompt.view(),
-            "top_k" => top_k.view(),
-            "temperature" => temperature.view(),
-        ]
-        .expect("Failed to create first_stage_decoder_input");
+            first_stage.input("all_phoneme_ids") => all_phoneme_ids.view(),
+            first_stage.input("bert") => bert.view(),
+            first_stage.input("prompt") => prompt.view(),
+            first_stage.input("top_k") => top_k.view(),
+            first_stage.input("temperature") => temperature.view(),
+        ]?;
         let start_loop1 = Instant::now();
-        let t2s_first_stage_out = self
-            .model_sessions
-            .t2s_first_stage_decoder
+        let t2s_first_stage_out = first_stage
             .run(first_stage_decoder_input)
-            .expect("Failed to run t2s_first_stage_decoder");
+            .context("Failed to run t2s_first_stage_decoder")?;
         println!(
             "t2s_first_stage time: {}ms",
             start_loop1.elapsed().as_millis()
         );
 
-        let mut y: Array2<i64> = t2s_first_stage_out["y"]
-            .try_extract_tensor::<i64>()
-            .unwrap()
-            .view()
-            .slice(s![.., ..])
-            .into_owned();
-        let mut k: Array4<f32> = t2s_first_stage_out["k"]
-            .try_extract_tensor::<f32>()
-            .unwrap()
-            .view()
-            .slice(s![.., .., .., ..])
-            .into_owned();
-        let mut v: Array4<f32> = t2s_first_stage_out["v"]
-            .try_extract_tensor::<f32>()
-            .unwrap()
-            .view()
-            .slice(s![.., .., .., ..])
-            .into_owned();
-        let mut y_emb: Array3<f32> = t2s_first_stage_out["y_emb"]
-            .try_extract_tensor::<f32>()
-            .unwrap()
-            .view()
-            .slice(s![.., .., ..])
-            .into_owned();
-
-        let mut y_example: Array2<f32> = Array2::zeros((1, y_emb.shape()[1]));
-        let y_example_0: Array2<f32> = Array2::zeros((1, 1));
-
-        let mut loop_idx = 0;
-        for idx in 1..1500 {
-            y_example = ndarray::concatenate(Axis(1), &[y_example.view(), y_example_0.view()])
-                .expect("Failed to concatenate y_example");
-            let xy_attn_mask: Array4<f32> =
-                ndarray::concatenate(Axis(1), &[x_example.view(), y_example.view()])
-                    .unwrap()
-                    .insert_axis(Axis(0))
-                    .insert_axis(Axis(0));
-
-            let t2s_stage_decoder_input = inputs![
-            "y" => y.view(),
-            "k" => k.view(),
-            "v" => v.view(),
-            "y_emb" => y_emb.view(),
-            "xy_attn_mask" => xy_attn_mask.view(),
-            "top_k" => top_k.view(),
-            "temperature" => temperature.view(),
-            ]
-            .expect("Failed to create t2s_stage_decoder_input");
-
-            let t2s_stage_decoder_out = self
-                .model_sessions
-                .t2s_stage_decoder
-                .run(t2s_stage_decoder_input)
-                .expect("Failed to run t2s_stage_decoder");
-
-            k = t2s_stage_decoder_out["o_k"]
-                .try_extract_tensor::<f32>()
-                .unwrap()
-                .view()
-                .slice(s![.., .., .., ..])
-                .into_owned();
-            v = t2s_stage_decoder_out["o_v"]
-                .try_extract_tensor::<f32>()
-                .unwrap()
-                .view()
-                .slice(s![.., .., .., ..])
-                .into_owned();
-            y_emb = t2s_stage_decoder_out["o_y_emb"]
-                .try_extract_tensor::<f32>()
-                .unwrap()
-                .view()
-                .slice(s![.., .., ..])
-                .into_owned();
-            let logits: Array1<i64> = t2s_stage_decoder_out["logits"]
-                .try_extract_tensor::<i64>()
-                .unwrap()
-                .view()
-                .slice(s![..])
-                .into_owned();
-            let samples: Array2<i64> = t2s_stage_decoder_out["samples"]
-                .try_extract_tensor::<i64>()
-                .unwrap()
-                .view()
-                .slice(s![.., ..])
-                .into_owned();
-
-            y = ndarray::concatenate(Axis(1), &[y.view(), samples.view()])
-                .expect("Failed to concatenate y");
-
-            if *samples.get((0, 0)).expect("Failed to get sample") == 1024
-                || *logits.get(0).expect("Failed to get logit") == 1024
-            {
-                loop_idx = idx;
-                break;
+        let pred_semantic = match &self.decode_scheduler {
+            Some(scheduler) => {
+                let (_, y) =
+                    t2s_first_stage_out[first_stage.output("y")].try_extract_raw_tensor::<i64>()?;
+                let extract = |name: &str| {
+                    t2s_first_stage_out[first_stage.output(name)].try_extract_tensor::<f32>()
+                };
+                scheduler.decode(y, extract("k")?, extract("v")?, extract("y_emb")?)?
             }
-        }
+            None => self.t2s_decode(
+                sessions,
+                t2s_first_stage_out,
+                all_phoneme_ids.shape()[1],
+                &top_k,
+                &temperature,
+            )?,
+        };
         println!(
             "{}ms , loop_idx:{}",
             start_loop.elapsed().as_millis(),
-            loop_idx
+            pred_semantic.shape()[2]
         );
 
-        *y.get_mut((0, y.shape()[1] - 1)).unwrap() = 0;
-
-        let pred_semantic: Array3<i64> = y
-            .slice(s![.., y.shape()[1] - loop_idx..])
-            .into_owned()
-            .insert_axis(Axis(0));
-
         let y_len = (pred_semantic.shape()[2] * 2) as i64;
         let y_lengths: Array1<i64> = ndarray::Array1::from(vec![y_len]);
         let text_lengths: Array1<i64> = ndarray::Array1::from(vec![text.shape()[0] as i64]);
-        let t = (self.wav32k_arr.shape()[1] - hop_length) / hop_length + 1;
+        let t = (self.voice.wav32k.shape()[1] - hop_length) / hop_length + 1;
         let refer_mask: Array3<i64> =
             Array3::ones((pred_semantic.shape()[0], pred_semantic.shape()[1], t));
 
+        let vq_model = &sessions.vq_model;
         let vq_model_input = inputs![
-            "pred_semantic"=>pred_semantic.view(),
-            "text"=>text.view(),
-            "org_audio"=>self.wav32k_arr.view(),
-            "hann_window"=>hann_window.view(),
-            "refer_mask"=>refer_mask.view(),
-            "y_lengths"=>y_lengths.view(),
-            "text_lengths"=>text_lengths.view(),
-        ]
-        .expect("Failed to create vq_model_input");
+            vq_model.input("pred_semantic")=>pred_semantic.view(),
+            vq_model.input("text")=>text.view(),
+            vq_model.input("org_audio")=>self.voice.wav32k.view(),
+            vq_model.input("hann_window")=>hann_window.view(),
+            vq_model.input("refer_mask")=>refer_mask.view(),
+            vq_model.input("y_lengths")=>y_lengths.view(),
+            vq_model.input("text_lengths")=>text_lengths.view(),
+        ]?;
         let start_vq_model = Instant::now();
-        let vq_model_out = self
-            .model_sessions
-            .vq_model
+        let vq_model_out = vq_model
             .run(vq_model_input)
-            .expect("Failed to run vq_model");
+            .context("Failed to run vq_model")?;
         let start_vq_model2 = Instant::now();
 
-        let audio: Array1<f32> = vq_model_out["audio"]
-            .try_extract_tensor::<f32>()
-            .unwrap()
+        let audio: Array1<f32> = vq_model_out[vq_model.output("audio")]
+            .try_extract_tensor::<f32>()?
             .view()
             .slice(s![0, 0, ..])
             .into_owned();
@@ -510,49 +761,101 @@ impl ChBertUtils {
         // 保存结果
         // AudioUtils::decode_data_to_path(&audio_norm, "./make_32k.wav", 32000, true).unwrap();
         println!("total time: {}ms", start_loop.elapsed().as_millis());
-        audio_norm
+        Ok(audio_norm)
     }
 
-    pub fn infer(&self, text: &str) -> Vec<i16> {
+    /// 按参考文本长度切分待合成的文本
+    pub fn cut_texts(&self, text: &str) -> Vec<String> {
         let texts = self
             .text_util
             .lang_seg
-            .cut_texts(text, self.ref_words.chars().count());
+            .cut_texts(text, self.voice.ref_words.chars().count());
 
         println!("texts:{}", texts.join("\n"));
-
         texts
-            .iter()
-            .flat_map(|t| {
-                let CleanedText {
-                    mut phones_list,
-                    word2ph_list,
-                    lang_list,
-                    norm_text_list,
-                } = self.text_util.get_cleaned_text_final(t);
-                let BertFeatures {
-                    features,
-                    phones_list_unpack,
-                    norm_text_str,
-                } = ChBertUtils::get_bert_features(
-                    &self.tokenizer,
-                    &self.model_sessions.bert_model,
-                    &mut phones_list,
-                    &word2ph_list,
-                    &norm_text_list,
-                    &lang_list,
-                );
-
-                println!("_phones_list_unpack:{:?}", phones_list_unpack);
-                println!("text:{} ->{}", text, norm_text_str);
-
-                self.infer_wav(
-                    &self.features,
-                    &features,
-                    &self.phones_list_unpack,
-                    &phones_list_unpack,
-                )
-            })
-            .collect()
+    }
+
+    /// 合成音频的采样率
+    pub fn sample_rate(&self) -> u32 {
+        self.manifest.sample_rate
+    }
+
+    pub fn session_pool_size(&self) -> usize {
+        self.session_pool.len()
+    }
+
+    /// 重新加载中文用户词典、jieba 自定义词典和英文词典，返回中英文用户词条数；出错时继续使用原词典
+    pub fn reload_user_lexicon(&self) -> Result<usize, String> {
+        let chinese = &self.text_util.lang_chinese;
+        let english = &self.text_util.lang_english;
+        // 两边都读取成功后再一起替换，不会只换掉一半
+        let (zh, en) = match (chinese.read_user_lexicon(), english.read_lexicons()) {
+            (Ok(zh), Ok(en)) => (zh, en),
+            (Err(e), Ok(_)) => return Err(format!("chinese lexicon: {}", e)),
+            (Ok(_), Err(e)) => return Err(format!("english lexicon: {}", e)),
+            (Err(zh), Err(en)) => {
+                return Err(format!("chinese lexicon: {}; english lexicon: {}", zh, en))
+            }
+        };
+        Ok(chinese.set_user_lexicon(zh) + english.set_lexicons(en))
+    }
+
+    /// 调试用：中文文本的分词和变调结果
+    pub fn explain_chinese(&self, text: &str) -> Vec<WordTrace> {
+        self.text_util.lang_chinese.explain(text)
+    }
+
+    /// 检查请求中的语言选项：auto、zh、en、ja、yue、ko、zh-en-mixed
+    pub fn check_language(language: &str) -> Result<(), String> {
+        LanguageOption::parse(language).map(|_| ())
+    }
+
+    /// 合成单个分段，worker 决定使用哪一组 Session，并行调用时各自传入不同的 worker
+    pub fn infer_segment(
+        &self,
+        text: &str,
+        language: &str,
+        worker: usize,
+    ) -> anyhow::Result<Vec<i16>> {
+        let sessions = &self.session_pool[worker % self.session_pool.len()];
+        let option = LanguageOption::parse(language).map_err(anyhow::Error::msg)?;
+        let cleaned = self.text_util.get_cleaned_text_lang(text, option);
+        // UNK 没有对应的发音，与参考文本一样拒绝，而不是合成出错误的读音
+        if !cleaned.unknown_phones.is_empty() {
+            bail!(
+                "text {} contains phones {:?} unknown to the model symbol set",
+                text,
+                cleaned.unknown_phones
+            );
+        }
+        let BertFeatures {
+            features,
+            phones_list_unpack,
+            norm_text_str,
+        } = ChBertUtils::get_bert_features(
+            &self.tokenizer,
+            &sessions.bert_model,
+            sessions.en_bert.as_ref(),
+            cleaned,
+        );
+
+        println!("_phones_list_unpack:{:?}", phones_list_unpack);
+        println!("text:{} ->{}", text, norm_text_str);
+
+        self.infer_wav(
+            sessions,
+            &self.voice.bert_features,
+            &features,
+            &self.voice.phones,
+            &phones_list_unpack,
+        )
+    }
+
+    pub fn infer(&self, text: &str) -> anyhow::Result<Vec<i16>> {
+        let mut wav = vec![];
+        for t in self.cut_texts(text) {
+            wav.extend(self.infer_segment(&t, "auto", 0)?);
+        }
+        Ok(wav)
     }
 }
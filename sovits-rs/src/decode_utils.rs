@@ -0,0 +1,707 @@
+use crate::model_manifest::ModelSession;
+use anyhow::{anyhow, ensure, Context};
+use log::{error, info};
+use ndarray::{
+    concatenate, s, Array, Array1, Array3, Array4, ArrayView, ArrayView3, ArrayView4, ArrayViewD,
+    Axis, Dimension, Ix3, Ix4, Slice,
+};
+use ort::{inputs, value::DynValue};
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// t2s 解码的结束符
+pub const EOS: i64 = 1024;
+/// 单段最多解码步数
+pub const MAX_DECODE_STEPS: usize = 1500;
+/// 合批时左侧补齐位置的掩码值
+const MASKED: f32 = f32::NEG_INFINITY;
+
+/// t2s 循环里每步都要喂给模型的 y 和 xy_attn_mask，按最大步数预先分配，避免每步拼接
+pub struct DecodeBuffers {
+    y: Vec<i64>,
+    attn_mask: Vec<f32>,
+    mask_base_len: usize,
+}
+
+impl DecodeBuffers {
+    /// prompt 为第一阶段输出的 y，x_len 为音素数，y_emb_len 为第一阶段 y_emb 的长度
+    pub fn new(prompt: &[i64], x_len: usize, y_emb_len: usize, max_steps: usize) -> Self {
+        let mut y = Vec::with_capacity(prompt.len() + max_steps);
+        y.extend_from_slice(prompt);
+        DecodeBuffers {
+            y,
+            // 掩码全为 0，只随步数变长
+            attn_mask: vec![0.0; x_len + y_emb_len + max_steps],
+            mask_base_len: x_len + y_emb_len,
+        }
+    }
+
+    /// 当前 y，形状 [1, len]
+    pub fn y(&self) -> ([usize; 2], &[i64]) {
+        ([1, self.y.len()], &self.y)
+    }
+
+    /// 第 step 步(从 1 开始)的 xy_attn_mask，形状 [1, 1, 1, x_len + y_emb_len + step]
+    pub fn attn_mask(&self, step: usize) -> ([usize; 4], &[f32]) {
+        let len = (self.mask_base_len + step).min(self.attn_mask.len());
+        ([1, 1, 1, len], &self.attn_mask[..len])
+    }
+
+    pub fn push(&mut self, sample: i64) {
+        self.y.push(sample);
+    }
+
+    pub fn len(&self) -> usize {
+        self.y.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.y.is_empty()
+    }
+
+    /// 取最后 n 个采样作为 pred_semantic，形状 [1, 1, n]，末尾的结束符置 0
+    pub fn pred_semantic(&mut self, n: usize) -> Array3<i64> {
+        if let Some(last) = self.y.last_mut() {
+            *last = 0;
+        }
+        let start = self.y.len() - n.min(self.y.len());
+        Array1::from(self.y[start..].to_vec())
+            .insert_axis(Axis(0))
+            .insert_axis(Axis(0))
+    }
+}
+
+/// t2s 合批解码参数，开启后要求 t2s_stage_decoder 导出时 batch 维是动态的
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct BatchConfig {
+    /// 一次 t2s_stage_decoder 调用最多合并的序列数，1 表示不合批
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: usize,
+    /// 空闲时收到第一条序列后，等待其它序列一起开始解码的时间
+    #[serde(default)]
+    pub batch_wait_ms: u64,
+    /// 序列加入正在解码的组时 y_emb 左侧最多补齐的步数，超过时另起一组。新采样的位置编码按 y_emb
+    /// 长度计算，补齐后该序列的位置整体后移，输出与不合批时不同；默认 0 只与位置一致的序列合批
+    #[serde(default)]
+    pub max_join_offset: usize,
+}
+
+fn default_max_batch_size() -> usize {
+    1
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        BatchConfig {
+            max_batch_size: default_max_batch_size(),
+            batch_wait_ms: 0,
+            max_join_offset: 0,
+        }
+    }
+}
+
+/// 解码结果，失败时为错误信息
+type DecodeReply = Sender<Result<Array3<i64>, String>>;
+
+/// 第一阶段的输出，k、v 为 [layers, 1, kv_len, hidden]，y_emb 为 [1, y_emb_len, hidden]
+struct DecodeRequest {
+    y: Vec<i64>,
+    k: Array4<f32>,
+    v: Array4<f32>,
+    y_emb: Array3<f32>,
+    reply: DecodeReply,
+}
+
+struct BatchRow {
+    buffers: DecodeBuffers,
+    /// k、v 左侧补齐的长度，掩码中对应位置不参与注意力
+    pad: usize,
+    steps: usize,
+    reply: DecodeReply,
+}
+
+/// 一起解码的一组序列。k、v 左补齐到最长的序列；y_emb 长度全组共用，较短的序列加入时左补齐
+struct BatchGroup {
+    rows: Vec<BatchRow>,
+    state: Option<(DynValue, DynValue, DynValue)>,
+    kv_len: usize,
+    y_emb_len: usize,
+}
+
+/// 合并后的 k、v、y_emb
+type BatchState = (Array4<f32>, Array4<f32>, Array3<f32>);
+
+/// 沿 axis 左侧补 0 到 len
+fn pad_left<D: Dimension>(arr: ArrayView<f32, D>, axis: Axis, len: usize) -> Array<f32, D> {
+    let t = arr.len_of(axis);
+    let mut shape = arr.raw_dim();
+    shape[axis.index()] = len;
+    let mut padded = Array::zeros(shape);
+    padded
+        .slice_axis_mut(axis, Slice::from(len - t..))
+        .assign(&arr);
+    padded
+}
+
+fn views<D: Dimension>(arrs: &[Array<f32, D>]) -> Vec<ArrayView<'_, f32, D>> {
+    arrs.iter().map(|a| a.view()).collect()
+}
+
+fn extract<D: Dimension>(value: &DynValue) -> anyhow::Result<ArrayView<'_, f32, D>> {
+    Ok(value
+        .try_extract_tensor::<f32>()?
+        .into_dimensionality::<D>()?)
+}
+
+impl BatchGroup {
+    fn new(y_emb_len: usize) -> Self {
+        BatchGroup {
+            rows: vec![],
+            state: None,
+            kv_len: 0,
+            y_emb_len,
+        }
+    }
+
+    /// 已有 incoming 条待加入时，能否再加入 y_emb 长度为 y_emb_len 的序列
+    fn accepts(&self, y_emb_len: usize, incoming: usize, config: &BatchConfig) -> bool {
+        self.rows.len() + incoming < config.max_batch_size.max(1)
+            && self.y_emb_len >= y_emb_len
+            && self.y_emb_len - y_emb_len <= config.max_join_offset
+    }
+
+    /// 保留 keep 中的行并加入新序列，k、v 重新左补齐到最长的序列
+    fn rebuild(&mut self, keep: &[usize], requests: Vec<DecodeRequest>) -> anyhow::Result<()> {
+        let state = self.state.take();
+        let current = state
+            .as_ref()
+            .map(|(k, v, y_emb)| -> anyhow::Result<_> {
+                Ok((extract(k)?, extract(v)?, extract(y_emb)?))
+            })
+            .transpose();
+        let current = match current {
+            Ok(current) => current,
+            Err(e) => {
+                for request in requests {
+                    let _ = request.reply.send(Err(e.to_string()));
+                }
+                return Err(e);
+            }
+        };
+        let merged = self.merge(current, keep, requests);
+        drop(state);
+        if let Some((k, v, y_emb)) = merged {
+            self.state = Some((k.try_into()?, v.try_into()?, y_emb.try_into()?));
+        }
+        Ok(())
+    }
+
+    /// rebuild 的数组部分：新序列的 y_emb 左补齐到组的长度，位置编码随之后移；没有序列时返回 None
+    fn merge(
+        &mut self,
+        current: Option<(ArrayView4<f32>, ArrayView4<f32>, ArrayView3<f32>)>,
+        keep: &[usize],
+        requests: Vec<DecodeRequest>,
+    ) -> Option<BatchState> {
+        let mut rows: Vec<BatchRow> = std::mem::take(&mut self.rows)
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| keep.contains(i))
+            .map(|(_, row)| row)
+            .collect();
+        let kv_len = rows
+            .iter()
+            .map(|row| self.kv_len - row.pad)
+            .chain(requests.iter().map(|r| r.k.shape()[2]))
+            .max()
+            .unwrap_or(0);
+
+        let mut ks = vec![];
+        let mut vs = vec![];
+        let mut y_embs = vec![];
+        if let Some((k, v, y_emb)) = current {
+            for (&i, row) in keep.iter().zip(rows.iter_mut()) {
+                let start = row.pad;
+                ks.push(pad_left(
+                    k.slice(s![.., i..i + 1, start.., ..]),
+                    Axis(2),
+                    kv_len,
+                ));
+                vs.push(pad_left(
+                    v.slice(s![.., i..i + 1, start.., ..]),
+                    Axis(2),
+                    kv_len,
+                ));
+                y_embs.push(y_emb.slice(s![i..i + 1, .., ..]).to_owned());
+                row.pad = kv_len - (self.kv_len - start);
+            }
+        }
+        for request in requests {
+            let len = request.k.shape()[2];
+            let y_emb_len = request.y_emb.shape()[1];
+            ks.push(pad_left(request.k.view(), Axis(2), kv_len));
+            vs.push(pad_left(request.v.view(), Axis(2), kv_len));
+            y_embs.push(pad_left(request.y_emb.view(), Axis(1), self.y_emb_len));
+            rows.push(BatchRow {
+                buffers: DecodeBuffers::new(
+                    &request.y,
+                    len - y_emb_len,
+                    y_emb_len,
+                    MAX_DECODE_STEPS,
+                ),
+                pad: kv_len - len,
+                steps: 0,
+                reply: request.reply,
+            });
+        }
+
+        self.rows = rows;
+        self.kv_len = kv_len;
+        if self.rows.is_empty() {
+            return None;
+        }
+        Some((
+            concatenate(Axis(1), &views(&ks)).expect("Failed to concatenate k"),
+            concatenate(Axis(1), &views(&vs)).expect("Failed to concatenate v"),
+            concatenate(Axis(0), &views(&y_embs)).expect("Failed to concatenate y_emb"),
+        ))
+    }
+
+    /// 这一步的 y 和 xy_attn_mask：y 左侧补 0 到最长，掩码左侧补齐的位置不参与注意力
+    fn inputs(&self) -> (usize, Vec<i64>, Vec<f32>) {
+        let y_len = self
+            .rows
+            .iter()
+            .map(|row| row.buffers.len())
+            .max()
+            .unwrap_or(0);
+        let mut y = Vec::with_capacity(self.rows.len() * y_len);
+        let mut xy_attn_mask = Vec::with_capacity(self.rows.len() * (self.kv_len + 1));
+        for row in &self.rows {
+            let (_, row_y) = row.buffers.y();
+            y.resize(y.len() + y_len - row_y.len(), 0);
+            y.extend_from_slice(row_y);
+            let (_, row_mask) = row.buffers.attn_mask(row.steps + 1);
+            xy_attn_mask.resize(xy_attn_mask.len() + row.pad, MASKED);
+            xy_attn_mask.extend_from_slice(row_mask);
+        }
+        (y_len, y, xy_attn_mask)
+    }
+
+    /// 记录每行的采样，结束的序列返回结果，返回仍在解码的行
+    fn advance(&mut self, samples: &[i64], logits: &[i64]) -> Vec<usize> {
+        let mut keep = Vec::with_capacity(self.rows.len());
+        for (i, row) in self.rows.iter_mut().enumerate() {
+            let sample = samples[i];
+            row.buffers.push(sample);
+            row.steps += 1;
+            let pred_semantic = if sample == EOS || logits.get(i) == Some(&EOS) {
+                row.buffers.pred_semantic(row.steps)
+            } else if row.steps + 1 >= MAX_DECODE_STEPS {
+                // 与单条解码一致，未遇到结束符时结果为空
+                row.buffers.pred_semantic(0)
+            } else {
+                keep.push(i);
+                continue;
+            };
+            let _ = row.reply.send(Ok(pred_semantic));
+        }
+        self.kv_len += 1;
+        self.y_emb_len += 1;
+        keep
+    }
+
+    /// 丢弃这一组，等待的请求收到错误
+    fn fail(&mut self, message: &str) {
+        for row in self.rows.drain(..) {
+            let _ = row.reply.send(Err(message.to_string()));
+        }
+        self.state = None;
+    }
+
+    /// 所有序列前进一步，结束的序列返回结果并移出
+    fn step(
+        &mut self,
+        session: &ModelSession,
+        top_k: &Array1<i64>,
+        temperature: &Array1<f32>,
+    ) -> anyhow::Result<()> {
+        let (k, v, y_emb) = self.state.take().context("Failed to get batch state")?;
+        let batch = self.rows.len();
+        let (y_len, y, xy_attn_mask) = self.inputs();
+        let mask_len = self.kv_len + 1;
+
+        let input = inputs![
+            session.input("y") => ([batch, y_len], y),
+            session.input("k") => k,
+            session.input("v") => v,
+            session.input("y_emb") => y_emb,
+            session.input("xy_attn_mask") => ([batch, 1, 1, mask_len], xy_attn_mask),
+            session.input("top_k") => top_k.view(),
+            session.input("temperature") => temperature.view(),
+        ]?;
+        let mut out = session.run(input)?;
+        let k = out
+            .remove(session.output("o_k"))
+            .context("Failed to get o_k")?;
+        let v = out
+            .remove(session.output("o_v"))
+            .context("Failed to get o_v")?;
+        let y_emb = out
+            .remove(session.output("o_y_emb"))
+            .context("Failed to get o_y_emb")?;
+        let (_, logits) = out[session.output("logits")].try_extract_raw_tensor::<i64>()?;
+        let (_, samples) = out[session.output("samples")].try_extract_raw_tensor::<i64>()?;
+        // batch 维固定为 1 导出的模型在合批时只返回一行
+        ensure!(
+            samples.len() == batch && logits.len() == batch,
+            "t2s_stage_decoder returned {} samples and {} logits for a batch of {}, \
+             batching requires a dynamic batch dimension",
+            samples.len(),
+            logits.len(),
+            batch
+        );
+        let keep = self.advance(samples, logits);
+        drop(out);
+
+        self.state = Some((k, v, y_emb));
+        if keep.len() < batch {
+            self.rebuild(&keep, vec![])?;
+        }
+        Ok(())
+    }
+
+    /// step 中的 panic 转为错误，只让这一组失败，调度线程继续运行
+    fn step_guarded(
+        &mut self,
+        session: &ModelSession,
+        top_k: &Array1<i64>,
+        temperature: &Array1<f32>,
+    ) -> anyhow::Result<()> {
+        panic::catch_unwind(AssertUnwindSafe(|| self.step(session, top_k, temperature)))
+            .unwrap_or_else(|_| Err(anyhow!("t2s batch step panicked")))
+    }
+}
+
+/// 把同时进行的多条序列合并成一次 t2s_stage_decoder 调用，每条序列遇到结束符后单独返回
+pub struct DecodeScheduler {
+    sender: Sender<DecodeRequest>,
+}
+
+impl DecodeScheduler {
+    pub fn new(
+        session: Arc<ModelSession>,
+        config: &BatchConfig,
+        top_k: i64,
+        temperature: f32,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        info!(
+            "t2s decode scheduler started, max_batch_size: {}, max_join_offset: {}",
+            config.max_batch_size, config.max_join_offset
+        );
+        let config = config.clone();
+        thread::Builder::new()
+            .name("t2s-decode-scheduler".to_string())
+            .spawn(move || {
+                let pool = ThreadPoolBuilder::new()
+                    .thread_name(|i| format!("t2s-decode-{}", i))
+                    .build()
+                    .expect("Failed to build t2s decode thread pool");
+                let top_k = Array1::from(vec![top_k]);
+                let temperature = Array1::from(vec![temperature]);
+                Self::run(&session, &pool, &receiver, &config, &top_k, &temperature)
+            })
+            .expect("Failed to spawn t2s decode scheduler");
+        DecodeScheduler { sender }
+    }
+
+    /// 提交第一阶段的输出并等待解码结束，返回 pred_semantic
+    pub fn decode(
+        &self,
+        y: &[i64],
+        k: ArrayViewD<f32>,
+        v: ArrayViewD<f32>,
+        y_emb: ArrayViewD<f32>,
+    ) -> anyhow::Result<Array3<i64>> {
+        let (reply, receiver) = mpsc::channel();
+        let request = DecodeRequest {
+            y: y.to_vec(),
+            k: k.into_dimensionality::<Ix4>()?.to_owned(),
+            v: v.into_dimensionality::<Ix4>()?.to_owned(),
+            y_emb: y_emb.into_dimensionality::<Ix3>()?.to_owned(),
+            reply,
+        };
+        self.sender
+            .send(request)
+            .map_err(|_| anyhow!("t2s decode scheduler stopped"))?;
+        receiver
+            .recv()
+            .map_err(|_| anyhow!("t2s decode scheduler stopped"))?
+            .map_err(anyhow::Error::msg)
+    }
+
+    fn run(
+        session: &ModelSession,
+        pool: &ThreadPool,
+        receiver: &Receiver<DecodeRequest>,
+        config: &BatchConfig,
+        top_k: &Array1<i64>,
+        temperature: &Array1<f32>,
+    ) {
+        let mut groups: Vec<BatchGroup> = vec![];
+        loop {
+            let mut requests = vec![];
+            if groups.is_empty() {
+                match receiver.recv() {
+                    Ok(request) => requests.push(request),
+                    Err(_) => return,
+                }
+                let deadline = Instant::now() + Duration::from_millis(config.batch_wait_ms);
+                while requests.len() < config.max_batch_size {
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+                    match receiver.recv_timeout(timeout) {
+                        Ok(request) => requests.push(request),
+                        Err(_) => break,
+                    }
+                }
+            }
+            requests.extend(receiver.try_iter());
+            Self::admit(&mut groups, requests, config);
+
+            // 多个组在线程池中同时前进一步
+            let results: Vec<anyhow::Result<()>> = if groups.len() == 1 {
+                vec![groups[0].step_guarded(session, top_k, temperature)]
+            } else {
+                pool.install(|| {
+                    groups
+                        .par_iter_mut()
+                        .map(|group| group.step_guarded(session, top_k, temperature))
+                        .collect()
+                })
+            };
+            for (group, result) in groups.iter_mut().zip(results) {
+                if let Err(e) = result {
+                    error!("t2s batch step failed: {}", e);
+                    group.fail(&e.to_string());
+                }
+            }
+            groups.retain(|group| !group.rows.is_empty());
+        }
+    }
+
+    /// 新序列并入未满、y_emb 补齐最少的组，包括正在解码的组；没有可并入的组时另起一组
+    fn admit(groups: &mut Vec<BatchGroup>, mut requests: Vec<DecodeRequest>, config: &BatchConfig) {
+        // 长的先分组，较短的序列补齐后可以并入
+        requests.sort_by_key(|r| Reverse(r.y_emb.shape()[1]));
+        let mut incoming: Vec<Vec<DecodeRequest>> = groups.iter().map(|_| vec![]).collect();
+        for request in requests {
+            let y_emb_len = request.y_emb.shape()[1];
+            match Self::find_group(groups, &incoming, y_emb_len, config) {
+                Some(idx) => incoming[idx].push(request),
+                None => {
+                    groups.push(BatchGroup::new(y_emb_len));
+                    incoming.push(vec![request]);
+                }
+            }
+        }
+        for (group, requests) in groups.iter_mut().zip(incoming) {
+            if requests.is_empty() {
+                continue;
+            }
+            let keep: Vec<usize> = (0..group.rows.len()).collect();
+            if let Err(e) = group.rebuild(&keep, requests) {
+                error!("t2s batch rebuild failed: {}", e);
+                group.fail(&e.to_string());
+            }
+        }
+    }
+
+    fn find_group(
+        groups: &[BatchGroup],
+        incoming: &[Vec<DecodeRequest>],
+        y_emb_len: usize,
+        config: &BatchConfig,
+    ) -> Option<usize> {
+        groups
+            .iter()
+            .zip(incoming)
+            .enumerate()
+            .filter(|(_, (group, pending))| group.accepts(y_emb_len, pending.len(), config))
+            .min_by_key(|(_, (group, _))| group.y_emb_len - y_emb_len)
+            .map(|(idx, _)| idx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_buffers() {
+        let mut buffers = DecodeBuffers::new(&[1, 2, 3], 4, 3, 10);
+        assert_eq!(buffers.y(), ([1, 3], &[1i64, 2, 3][..]));
+        let (shape, mask) = buffers.attn_mask(1);
+        assert_eq!(shape, [1, 1, 1, 8]);
+        assert!(mask.iter().all(|&m| m == 0.0));
+
+        buffers.push(7);
+        buffers.push(EOS);
+        assert_eq!(buffers.y().0, [1, 5]);
+        assert_eq!(buffers.attn_mask(2).0, [1, 1, 1, 9]);
+
+        let pred_semantic = buffers.pred_semantic(2);
+        assert_eq!(pred_semantic.shape(), &[1, 1, 2]);
+        assert_eq!(pred_semantic.as_slice().unwrap(), &[7, 0]);
+    }
+
+    #[test]
+    fn test_pad_left() {
+        let arr = Array4::from_shape_fn((2, 1, 3, 2), |(l, _, t, h)| (l * 100 + t * 10 + h) as f32);
+        let padded = pad_left(arr.view(), Axis(2), 5);
+        assert_eq!(padded.shape(), &[2, 1, 5, 2]);
+        assert!(padded.slice(s![.., .., ..2, ..]).iter().all(|&x| x == 0.0));
+        assert_eq!(padded.slice(s![.., .., 2.., ..]), arr);
+
+        let config: BatchConfig = serde_json::from_str(r#"{"batch_wait_ms": 20}"#).unwrap();
+        assert_eq!(config.max_batch_size, 1);
+        assert_eq!(config.batch_wait_ms, 20);
+        assert_eq!(config.max_join_offset, 0);
+    }
+
+    type Reply = Receiver<Result<Array3<i64>, String>>;
+
+    /// 第一阶段输出为常数 value 的序列，prompt 全为 5
+    fn request(x_len: usize, y_emb_len: usize, value: f32) -> (DecodeRequest, Reply) {
+        let (reply, receiver) = mpsc::channel();
+        let kv_len = x_len + y_emb_len;
+        let request = DecodeRequest {
+            y: vec![5; y_emb_len],
+            k: Array4::from_elem((2, 1, kv_len, 3), value),
+            v: Array4::from_elem((2, 1, kv_len, 3), value),
+            y_emb: Array3::from_elem((1, y_emb_len, 3), value),
+            reply,
+        };
+        (request, receiver)
+    }
+
+    /// 模拟模型一步的输出，k、v、y_emb 末尾各加一个位置
+    fn grow(state: BatchState) -> BatchState {
+        let (k, v, y_emb) = state;
+        let (layers, batch, _, hidden) = k.dim();
+        let k_step = Array4::from_elem((layers, batch, 1, hidden), 9.0);
+        let y_emb_step = Array3::from_elem((batch, 1, hidden), 9.0);
+        (
+            concatenate(Axis(2), &[k.view(), k_step.view()]).unwrap(),
+            concatenate(Axis(2), &[v.view(), k_step.view()]).unwrap(),
+            concatenate(Axis(1), &[y_emb.view(), y_emb_step.view()]).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_merge_and_inputs() {
+        let mut group = BatchGroup::new(3);
+        let (r1, _rx1) = request(4, 3, 1.0);
+        let (r2, _rx2) = request(2, 3, 2.0);
+        let (k, _, y_emb) = group.merge(None, &[], vec![r1, r2]).unwrap();
+        assert_eq!(group.kv_len, 7);
+        assert_eq!(k.shape(), &[2, 2, 7, 3]);
+        assert_eq!(y_emb.shape(), &[2, 3, 3]);
+        // 较短的序列 k 左补齐，掩码对应位置不参与注意力
+        assert_eq!(group.rows[1].pad, 2);
+        assert_eq!(k[[0, 1, 1, 0]], 0.0);
+        assert_eq!(k[[0, 1, 2, 0]], 2.0);
+
+        let (y_len, y, mask) = group.inputs();
+        assert_eq!(y_len, 3);
+        assert_eq!(y, vec![5; 6]);
+        assert_eq!(mask.len(), 2 * 8);
+        assert!(mask[..8].iter().all(|&m| m == 0.0));
+        assert_eq!(&mask[8..10], &[MASKED, MASKED]);
+        assert!(mask[10..].iter().all(|&m| m == 0.0));
+    }
+
+    #[test]
+    fn test_mid_flight_admission() {
+        let config = BatchConfig {
+            max_batch_size: 4,
+            batch_wait_ms: 0,
+            max_join_offset: 2,
+        };
+        let mut group = BatchGroup::new(3);
+        let (r1, rx1) = request(4, 3, 1.0);
+        let mut state = group.merge(None, &[], vec![r1]).unwrap();
+        for _ in 0..2 {
+            assert_eq!(group.advance(&[7], &[0]), vec![0]);
+            state = grow(state);
+        }
+        assert_eq!((group.kv_len, group.y_emb_len), (9, 5));
+
+        // 解码中的组 y_emb 已变长，新序列补齐后仍可并入
+        let groups = [group];
+        assert_eq!(
+            DecodeScheduler::find_group(&groups, &[vec![]], 3, &config),
+            Some(0)
+        );
+        let strict = BatchConfig {
+            max_join_offset: 1,
+            ..config.clone()
+        };
+        assert_eq!(
+            DecodeScheduler::find_group(&groups, &[vec![]], 3, &strict),
+            None
+        );
+        assert_eq!(
+            DecodeScheduler::find_group(&groups, &[vec![]], 6, &config),
+            None
+        );
+        let full = BatchConfig {
+            max_batch_size: 1,
+            ..config
+        };
+        assert_eq!(
+            DecodeScheduler::find_group(&groups, &[vec![]], 3, &full),
+            None
+        );
+
+        let [mut group] = groups;
+        let (r2, rx2) = request(2, 3, 2.0);
+        let (k, v, y_emb) = &state;
+        let (k, _, y_emb) = group
+            .merge(Some((k.view(), v.view(), y_emb.view())), &[0], vec![r2])
+            .unwrap();
+        assert_eq!(group.kv_len, 9);
+        assert_eq!(k.shape(), &[2, 2, 9, 3]);
+        assert_eq!(k[[0, 0, 8, 0]], 9.0);
+        assert_eq!((group.rows[0].pad, group.rows[1].pad), (0, 4));
+        // 新序列的 y_emb 左补齐到组的长度
+        assert_eq!(y_emb.shape(), &[2, 5, 3]);
+        assert_eq!(y_emb[[1, 1, 0]], 0.0);
+        assert_eq!(y_emb[[1, 2, 0]], 2.0);
+
+        let (y_len, y, mask) = group.inputs();
+        assert_eq!(y_len, 5);
+        assert_eq!(&y[5..], &[0, 0, 5, 5, 5]);
+        assert_eq!(mask.len(), 2 * 10);
+        assert!(mask[..10].iter().all(|&m| m == 0.0));
+        assert!(mask[10..14].iter().all(|&m| m == MASKED));
+        assert!(mask[14..].iter().all(|&m| m == 0.0));
+
+        // 先结束的序列单独返回
+        assert_eq!(group.advance(&[EOS, 8], &[0, 0]), vec![1]);
+        let pred_semantic = rx1.recv().unwrap().unwrap();
+        assert_eq!(pred_semantic.as_slice().unwrap(), &[7, 7, 0]);
+        assert!(rx2.try_recv().is_err());
+
+        group.fail("session failed");
+        assert_eq!(rx2.recv().unwrap().unwrap_err(), "session failed");
+        assert!(group.rows.is_empty());
+    }
+}
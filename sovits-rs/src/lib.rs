@@ -1,4 +1,13 @@
 mod audio_utils;
 pub mod bert_utils;
+pub mod decode_utils;
+pub mod model_manifest;
+pub mod session_config;
 mod text;
 mod text_utils;
+pub mod voice_bundle;
+
+pub use text::chinese::WordTrace;
+pub use text::en_lexicon::{EnglishLexiconConfig, LexiconSource};
+pub use text::zh_lexicon::ChineseLexiconConfig;
+pub use text_utils::Segmenter;
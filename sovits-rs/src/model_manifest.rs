@@ -0,0 +1,539 @@
+use crate::text::symbols::SymbolTable;
+use anyhow::{bail, Context};
+use fnv::FnvHasher;
+use ort::session::Session;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::hash::Hasher;
+use std::io::Read;
+use std::ops::Deref;
+use std::path::Path;
+
+/// 支持的模型版本
+pub const SUPPORTED_VERSIONS: [&str; 2] = ["v1", "v2"];
+
+/// 支持的音素表版本，v2 在 v1 后追加了日文声调、韩文和粤语音素
+pub const SUPPORTED_SYMBOLS: [&str; 2] = ["v1", "v2"];
+
+/// 随 assets 发布的 v2 音素表，即 GPT-SoVITS text/symbols2.py 的 symbols
+pub const V2_SYMBOLS_FILE: &str = "symbols_v2.json";
+
+/// 推理代码对一个模型输入输出的要求，bert、ssl 等按位置传参的模型只要求输入个数
+#[derive(Debug)]
+struct Signature {
+    input_count: usize,
+    inputs: &'static [&'static str],
+    outputs: &'static [&'static str],
+}
+
+const BERT_MODEL: Signature = Signature {
+    input_count: 3,
+    inputs: &[],
+    outputs: &["hidden_states"],
+};
+const SSL_MODEL: Signature = Signature {
+    input_count: 1,
+    inputs: &[],
+    outputs: &["output"],
+};
+const VQ_MODEL_LATENT: Signature = Signature {
+    input_count: 1,
+    inputs: &[],
+    outputs: &["output"],
+};
+const T2S_FIRST_STAGE_DECODER: Signature = Signature {
+    input_count: 5,
+    inputs: &["all_phoneme_ids", "bert", "prompt", "top_k", "temperature"],
+    outputs: &["y", "k", "v", "y_emb"],
+};
+const T2S_STAGE_DECODER: Signature = Signature {
+    input_count: 7,
+    inputs: &[
+        "y",
+        "k",
+        "v",
+        "y_emb",
+        "xy_attn_mask",
+        "top_k",
+        "temperature",
+    ],
+    outputs: &["o_k", "o_v", "o_y_emb", "logits", "samples"],
+};
+const VQ_MODEL: Signature = Signature {
+    input_count: 7,
+    inputs: &[
+        "pred_semantic",
+        "text",
+        "org_audio",
+        "hann_window",
+        "refer_mask",
+        "y_lengths",
+        "text_lengths",
+    ],
+    outputs: &["audio"],
+};
+
+/// 单个模型文件，inputs、outputs 为空时使用推理代码的张量名。
+/// 按名字传参的输入和所有输出按推理代码的顺序声明，模型导出的张量名不同时在这里映射
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct ModelFile {
+    pub file: String,
+    /// 模型文件的 FNV-1a 64 校验值
+    #[serde(default)]
+    pub checksum: Option<String>,
+    #[serde(default)]
+    pub inputs: Vec<String>,
+    #[serde(default)]
+    pub outputs: Vec<String>,
+}
+
+impl ModelFile {
+    fn new(file: &str, signature: &Signature) -> Self {
+        ModelFile {
+            file: file.to_string(),
+            checksum: None,
+            inputs: signature.inputs.iter().map(|s| s.to_string()).collect(),
+            outputs: signature.outputs.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+/// 一套导出模型的描述，对应 assets/model_manifest.json
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct ModelManifest {
+    /// GPT-SoVITS 版本
+    pub version: String,
+    /// 音素表版本
+    pub symbols: String,
+    /// 音素表文件，相对于模型目录，json 字符串数组，下标即音素 id。省略时 v1 使用内置音素表，
+    /// v2 使用模型目录下的 symbols_v2.json
+    #[serde(default)]
+    pub symbols_file: Option<String>,
+    pub sample_rate: u32,
+    pub hop_length: usize,
+    pub win_length: usize,
+    pub bert_model: ModelFile,
+    pub ssl_model: ModelFile,
+    pub vq_model_latent: ModelFile,
+    pub t2s_first_stage_decoder: ModelFile,
+    pub t2s_stage_decoder: ModelFile,
+    pub vq_model: ModelFile,
+}
+
+impl Default for ModelManifest {
+    /// 没有 manifest 时按 v1 导出的文件名和张量名加载
+    fn default() -> Self {
+        ModelManifest {
+            version: "v1".to_string(),
+            symbols: "v1".to_string(),
+            symbols_file: None,
+            sample_rate: 32000,
+            hop_length: 640,
+            win_length: 2048,
+            bert_model: ModelFile::new("bert_model.onnx", &BERT_MODEL),
+            ssl_model: ModelFile::new("ssl_model.onnx", &SSL_MODEL),
+            vq_model_latent: ModelFile::new("vq_model_latent.onnx", &VQ_MODEL_LATENT),
+            t2s_first_stage_decoder: ModelFile::new(
+                "t2s_first_stage_decoder.onnx",
+                &T2S_FIRST_STAGE_DECODER,
+            ),
+            t2s_stage_decoder: ModelFile::new("t2s_stage_decoder.onnx", &T2S_STAGE_DECODER),
+            vq_model: ModelFile::new("vq_model.onnx", &VQ_MODEL),
+        }
+    }
+}
+
+/// 文件内容的 FNV-1a 64 校验值
+pub fn file_checksum<P: AsRef<Path>>(path: P) -> std::io::Result<String> {
+    let mut hasher = FnvHasher::default();
+    let mut file = File::open(path)?;
+    let mut buf = vec![0u8; 1 << 20];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.write(&buf[..n]);
+    }
+    Ok(format!("{:016x}", hasher.finish()))
+}
+
+/// 推理代码的张量名对应的模型实际张量名
+#[derive(Debug, Clone)]
+pub struct TensorNames {
+    signature: &'static Signature,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+}
+
+impl TensorNames {
+    fn find<'a>(names: &'a [String], expected: &[&str], name: &str) -> &'a str {
+        let index = expected
+            .iter()
+            .position(|n| *n == name)
+            .unwrap_or_else(|| panic!("{} is not a tensor of the model signature", name));
+        &names[index]
+    }
+
+    /// 推理代码的输入名对应的模型输入名
+    pub fn input(&self, name: &str) -> &str {
+        Self::find(&self.inputs, self.signature.inputs, name)
+    }
+
+    /// 推理代码的输出名对应的模型输出名
+    pub fn output(&self, name: &str) -> &str {
+        Self::find(&self.outputs, self.signature.outputs, name)
+    }
+}
+
+/// 按 manifest 校验过的模型 Session 及其张量名
+pub struct ModelSession {
+    session: Session,
+    names: TensorNames,
+}
+
+impl ModelSession {
+    pub fn input(&self, name: &str) -> &str {
+        self.names.input(name)
+    }
+
+    pub fn output(&self, name: &str) -> &str {
+        self.names.output(name)
+    }
+}
+
+impl Deref for ModelSession {
+    type Target = Session;
+
+    fn deref(&self) -> &Session {
+        &self.session
+    }
+}
+
+fn check_names(
+    model: &str,
+    file: &str,
+    kind: &str,
+    expected: impl Iterator<Item = String>,
+    actual: &[&str],
+    reason: &str,
+) -> anyhow::Result<()> {
+    for name in expected {
+        if !actual.contains(&name.as_str()) {
+            bail!(
+                "{}: {} has no {} '{}' {}, actual {}s: {:?}",
+                model,
+                file,
+                kind,
+                name,
+                reason,
+                kind,
+                actual
+            );
+        }
+    }
+    Ok(())
+}
+
+impl ModelManifest {
+    /// 读取 manifest，文件不存在时使用默认的 v1 描述
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            log::info!(
+                "model manifest {} not found, assume v1 models",
+                path.display()
+            );
+            return Ok(ModelManifest::default());
+        }
+        let file = File::open(path)?;
+        let manifest: ModelManifest = serde_json::from_reader(file)
+            .with_context(|| format!("parse model manifest {}", path.display()))?;
+        manifest.check_version()?;
+        Ok(manifest)
+    }
+
+    fn check_version(&self) -> anyhow::Result<()> {
+        if !SUPPORTED_VERSIONS.contains(&self.version.as_str()) {
+            bail!(
+                "model version {} is not supported, supported versions: {:?}",
+                self.version,
+                SUPPORTED_VERSIONS
+            );
+        }
+        if !SUPPORTED_SYMBOLS.contains(&self.symbols.as_str()) {
+            bail!(
+                "symbol set {} is not supported, supported symbol sets: {:?}",
+                self.symbols,
+                SUPPORTED_SYMBOLS
+            );
+        }
+        Ok(())
+    }
+
+    /// 加载模型使用的音素表
+    pub(crate) fn symbol_table(&self, model_dir: &str) -> anyhow::Result<SymbolTable> {
+        let table = match (&self.symbols_file, self.symbols.as_str()) {
+            (None, "v1") => return Ok(SymbolTable::v1()),
+            (None, "v2") => SymbolTable::load(format!("{}/{}", model_dir, V2_SYMBOLS_FILE))?,
+            (None, symbols) => bail!(
+                "symbol set {} requires symbols_file exported from the model",
+                symbols
+            ),
+            (Some(file), _) => SymbolTable::load(format!("{}/{}", model_dir, file))?,
+        };
+        self.check_symbol_table(&table)?;
+        Ok(table)
+    }
+
+    fn check_symbol_table(&self, table: &SymbolTable) -> anyhow::Result<()> {
+        let file = self.symbols_file.as_deref().unwrap_or(V2_SYMBOLS_FILE);
+        match self.symbols.as_str() {
+            "v1" if table != &SymbolTable::v1() => {
+                bail!("{} is not the v1 symbol set", file)
+            }
+            "v2" if !table.is_v2() => bail!(
+                "{} is not a v2 symbol set, it should start with the v1 symbols and \"[\", \"]\"",
+                file
+            ),
+            _ => {}
+        }
+        if !table.contains("UNK") {
+            bail!("{} has no UNK symbol for unknown phones", file);
+        }
+        Ok(())
+    }
+
+    /// 检查模型文件的校验值
+    pub fn verify_checksum(&self, model: &str, model_path: &str) -> anyhow::Result<()> {
+        let Some(expected) = &self.model_file(model)?.checksum else {
+            return Ok(());
+        };
+        let actual = file_checksum(model_path).with_context(|| format!("read {}", model_path))?;
+        if &actual != expected {
+            bail!(
+                "{}: checksum of {} is {}, manifest expects {}",
+                model,
+                model_path,
+                actual,
+                expected
+            );
+        }
+        Ok(())
+    }
+
+    pub fn model_file(&self, model: &str) -> anyhow::Result<&ModelFile> {
+        Ok(match model {
+            "bert_model" => &self.bert_model,
+            "ssl_model" => &self.ssl_model,
+            "vq_model_latent" => &self.vq_model_latent,
+            "t2s_first_stage_decoder" => &self.t2s_first_stage_decoder,
+            "t2s_stage_decoder" => &self.t2s_stage_decoder,
+            "vq_model" => &self.vq_model,
+            _ => bail!("unknown model {}", model),
+        })
+    }
+
+    fn signature(model: &str) -> anyhow::Result<&'static Signature> {
+        Ok(match model {
+            "bert_model" => &BERT_MODEL,
+            "ssl_model" => &SSL_MODEL,
+            "vq_model_latent" => &VQ_MODEL_LATENT,
+            "t2s_first_stage_decoder" => &T2S_FIRST_STAGE_DECODER,
+            "t2s_stage_decoder" => &T2S_STAGE_DECODER,
+            "vq_model" => &VQ_MODEL,
+            _ => bail!("unknown model {}", model),
+        })
+    }
+
+    /// 推理时使用的张量名，manifest 声明的名字须与推理代码的张量一一对应
+    pub fn tensor_names(&self, model: &str) -> anyhow::Result<TensorNames> {
+        let signature = Self::signature(model)?;
+        let declared = self.model_file(model)?;
+        let resolve = |kind: &str, declared: &[String], expected: &[&str]| {
+            if declared.is_empty() || expected.is_empty() {
+                return Ok(expected.iter().map(|s| s.to_string()).collect());
+            }
+            if declared.len() != expected.len() {
+                bail!(
+                    "{}: manifest declares {} {}s {:?}, expected {} in the order of {:?}",
+                    model,
+                    declared.len(),
+                    kind,
+                    declared,
+                    expected.len(),
+                    expected
+                );
+            }
+            Ok(declared.to_vec())
+        };
+        Ok(TensorNames {
+            signature,
+            inputs: resolve("input", &declared.inputs, signature.inputs)?,
+            outputs: resolve("output", &declared.outputs, signature.outputs)?,
+        })
+    }
+
+    /// 校验 Session 并绑定推理时使用的张量名
+    pub fn model_session(&self, model: &str, session: Session) -> anyhow::Result<ModelSession> {
+        self.validate_session(model, &session)?;
+        Ok(ModelSession {
+            session,
+            names: self.tensor_names(model)?,
+        })
+    }
+
+    /// 用 Session 实际的输入输出校验 manifest 以及推理代码要求的张量名
+    pub fn validate_session(&self, model: &str, session: &Session) -> anyhow::Result<()> {
+        let declared = self.model_file(model)?;
+        let file = &declared.file;
+        let signature = Self::signature(model)?;
+        let names = self.tensor_names(model)?;
+        let inputs: Vec<&str> = session.inputs.iter().map(|i| i.name.as_str()).collect();
+        let outputs: Vec<&str> = session.outputs.iter().map(|o| o.name.as_str()).collect();
+
+        check_names(
+            model,
+            file,
+            "input",
+            declared.inputs.iter().cloned(),
+            &inputs,
+            "declared in manifest",
+        )?;
+        check_names(
+            model,
+            file,
+            "output",
+            declared.outputs.iter().cloned(),
+            &outputs,
+            "declared in manifest",
+        )?;
+
+        let reason = format!(
+            "required by the {} engine, the model may be exported by another GPT-SoVITS version",
+            self.version
+        );
+        if inputs.len() != signature.input_count {
+            bail!(
+                "{}: {} has {} inputs {:?}, expected {} {}",
+                model,
+                file,
+                inputs.len(),
+                inputs,
+                signature.input_count,
+                reason
+            );
+        }
+        check_names(
+            model,
+            file,
+            "input",
+            names.inputs.into_iter(),
+            &inputs,
+            &reason,
+        )?;
+        check_names(
+            model,
+            file,
+            "output",
+            names.outputs.into_iter(),
+            &outputs,
+            &reason,
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest() {
+        let manifest: ModelManifest = serde_json::from_str(
+            r#"{
+                "version": "v1",
+                "symbols": "v1",
+                "sample_rate": 32000,
+                "hop_length": 640,
+                "win_length": 2048,
+                "bert_model": {"file": "bert_model.onnx", "outputs": ["hidden_states"]},
+                "ssl_model": {"file": "ssl_model.onnx"},
+                "vq_model_latent": {"file": "vq_model_latent.onnx"},
+                "t2s_first_stage_decoder": {"file": "t2s_first_stage_decoder.onnx"},
+                "t2s_stage_decoder": {"file": "t2s_stage_decoder.onnx", "checksum": "0000000000000000"},
+                "vq_model": {"file": "vq_model.onnx"}
+            }"#,
+        )
+        .unwrap();
+        assert!(manifest.check_version().is_ok());
+        assert_eq!(
+            manifest.t2s_stage_decoder.checksum.as_deref(),
+            Some("0000000000000000")
+        );
+        assert!(manifest.ssl_model.outputs.is_empty());
+
+        assert_eq!(
+            ModelManifest::load("../assets/model_manifest.json").unwrap(),
+            ModelManifest::default()
+        );
+
+        let unsupported = ModelManifest {
+            version: "v9".to_string(),
+            ..ModelManifest::default()
+        };
+        assert!(unsupported.check_version().is_err());
+
+        let v2 = ModelManifest {
+            version: "v2".to_string(),
+            symbols: "v2".to_string(),
+            ..ModelManifest::default()
+        };
+        assert!(v2.check_version().is_ok());
+        assert!(v2.symbol_table("../assets").unwrap().is_v2());
+        assert!(v2.check_symbol_table(&SymbolTable::v1()).is_err());
+        let mut symbols: Vec<String> = crate::text::symbols::SYMBOLS
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        symbols.extend(["[", "]", "ㄱ"].iter().map(|s| s.to_string()));
+        assert!(v2
+            .check_symbol_table(&SymbolTable::from_symbols(symbols))
+            .is_ok());
+        assert_eq!(
+            ModelManifest::default().symbol_table("../assets").unwrap(),
+            SymbolTable::v1()
+        );
+
+        let err = check_names(
+            "vq_model",
+            "vq_model.onnx",
+            "output",
+            ["audio".to_string()].into_iter(),
+            &["wav"],
+            "declared in manifest",
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "vq_model: vq_model.onnx has no output 'audio' declared in manifest, actual outputs: [\"wav\"]"
+        );
+    }
+
+    #[test]
+    fn test_tensor_names() {
+        let mut manifest = ModelManifest::default();
+        manifest.vq_model.outputs = vec!["wav".to_string()];
+        manifest.ssl_model.outputs = vec![];
+        let names = manifest.tensor_names("vq_model").unwrap();
+        assert_eq!(names.output("audio"), "wav");
+        assert_eq!(names.input("refer_mask"), "refer_mask");
+        let names = manifest.tensor_names("ssl_model").unwrap();
+        assert_eq!(names.output("output"), "output");
+
+        manifest.t2s_stage_decoder.outputs = vec!["o_k".to_string(), "o_v".to_string()];
+        assert!(manifest.tensor_names("t2s_stage_decoder").is_err());
+        assert!(manifest.tensor_names("hifigan").is_err());
+        assert!(manifest.model_file("hifigan").is_err());
+    }
+}
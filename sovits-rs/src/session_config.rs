@@ -0,0 +1,301 @@
+use log::{info, warn};
+use ort::execution_providers::{CPUExecutionProvider, ExecutionProviderDispatch};
+use ort::session::builder::{GraphOptimizationLevel, SessionBuilder};
+use ort::session::Session;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 图优化等级，对应 onnxruntime 的 GraphOptimizationLevel
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum OptimizationLevel {
+    Disable,
+    Level1,
+    Level2,
+    Level3,
+}
+
+impl From<OptimizationLevel> for GraphOptimizationLevel {
+    fn from(level: OptimizationLevel) -> Self {
+        match level {
+            OptimizationLevel::Disable => GraphOptimizationLevel::Disable,
+            OptimizationLevel::Level1 => GraphOptimizationLevel::Level1,
+            OptimizationLevel::Level2 => GraphOptimizationLevel::Level2,
+            OptimizationLevel::Level3 => GraphOptimizationLevel::Level3,
+        }
+    }
+}
+
+/// 执行后端，除 cpu 外都需要打开对应的 Cargo feature
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutionProviderKind {
+    Cpu,
+    Cuda,
+    TensorRT,
+    CoreML,
+    DirectML,
+    OpenVINO,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
+pub struct ExecutionProviderConfig {
+    pub kind: ExecutionProviderKind,
+    #[serde(default)]
+    pub device_id: i32,
+}
+
+/// 单个模型的 Session 参数，未设置的项沿用 onnxruntime 默认值
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct SessionConfig {
+    pub intra_threads: Option<usize>,
+    pub inter_threads: Option<usize>,
+    pub parallel_execution: Option<bool>,
+    pub optimization_level: Option<OptimizationLevel>,
+    pub memory_pattern: Option<bool>,
+    pub cpu_arena: Option<bool>,
+    /// 优化后模型的缓存目录，存在且比原模型新时直接加载
+    pub optimized_model_dir: Option<String>,
+    #[serde(default)]
+    pub execution_providers: Vec<ExecutionProviderConfig>,
+}
+
+impl SessionConfig {
+    /// 用 other 中已设置的项覆盖当前配置
+    pub fn merge(&self, other: &SessionConfig) -> SessionConfig {
+        SessionConfig {
+            intra_threads: other.intra_threads.or(self.intra_threads),
+            inter_threads: other.inter_threads.or(self.inter_threads),
+            parallel_execution: other.parallel_execution.or(self.parallel_execution),
+            optimization_level: other.optimization_level.or(self.optimization_level),
+            memory_pattern: other.memory_pattern.or(self.memory_pattern),
+            cpu_arena: other.cpu_arena.or(self.cpu_arena),
+            optimized_model_dir: other
+                .optimized_model_dir
+                .clone()
+                .or_else(|| self.optimized_model_dir.clone()),
+            execution_providers: if other.execution_providers.is_empty() {
+                self.execution_providers.clone()
+            } else {
+                other.execution_providers.clone()
+            },
+        }
+    }
+
+    fn builder(&self) -> ort::Result<SessionBuilder> {
+        let mut builder = Session::builder()?;
+        if let Some(threads) = self.intra_threads {
+            builder = builder.with_intra_threads(threads)?;
+        }
+        if let Some(threads) = self.inter_threads {
+            builder = builder.with_inter_threads(threads)?;
+        }
+        if let Some(parallel) = self.parallel_execution {
+            builder = builder.with_parallel_execution(parallel)?;
+        }
+        if let Some(level) = self.optimization_level {
+            builder = builder.with_optimization_level(level.into())?;
+        }
+        if let Some(enable) = self.memory_pattern {
+            builder = builder.with_memory_pattern(enable)?;
+        }
+        let providers = self.execution_providers();
+        if !providers.is_empty() {
+            builder = builder.with_execution_providers(providers)?;
+        }
+        Ok(builder)
+    }
+
+    fn execution_providers(&self) -> Vec<ExecutionProviderDispatch> {
+        let mut providers: Vec<ExecutionProviderDispatch> = self
+            .execution_providers
+            .iter()
+            .filter_map(|ep| ep.dispatch(self.cpu_arena.unwrap_or(true)))
+            .collect();
+        // 只配置了内存池时也需要注册 cpu 后端
+        if let Some(arena) = self.cpu_arena {
+            if !self
+                .execution_providers
+                .iter()
+                .any(|ep| ep.kind == ExecutionProviderKind::Cpu)
+            {
+                providers.push(cpu_provider(arena));
+            }
+        }
+        providers
+    }
+
+    /// 按配置加载模型，name 用于生成优化缓存文件名
+    pub fn load(&self, name: &str, model_path: &str) -> ort::Result<Session> {
+        let Some(cache_dir) = &self.optimized_model_dir else {
+            return self.builder()?.commit_from_file(model_path);
+        };
+        let cache_path = PathBuf::from(cache_dir).join(format!("{}.opt.onnx", name));
+        if is_fresh(&cache_path, Path::new(model_path)) {
+            info!("load optimized {} from {}", name, cache_path.display());
+            // 缓存已经是优化后的图，不再重复优化
+            return self
+                .builder()?
+                .with_optimization_level(GraphOptimizationLevel::Disable)?
+                .commit_from_file(&cache_path);
+        }
+        if let Err(e) = std::fs::create_dir_all(cache_dir) {
+            warn!("create optimized model dir {} failed: {}", cache_dir, e);
+            return self.builder()?.commit_from_file(model_path);
+        }
+        self.builder()?
+            .with_optimized_model_path(&cache_path)?
+            .commit_from_file(model_path)
+    }
+}
+
+impl ExecutionProviderConfig {
+    fn dispatch(&self, cpu_arena: bool) -> Option<ExecutionProviderDispatch> {
+        match self.kind {
+            ExecutionProviderKind::Cpu => Some(cpu_provider(cpu_arena)),
+            #[cfg(feature = "cuda")]
+            ExecutionProviderKind::Cuda => Some(
+                ort::execution_providers::CUDAExecutionProvider::default()
+                    .with_device_id(self.device_id)
+                    .build(),
+            ),
+            #[cfg(feature = "tensorrt")]
+            ExecutionProviderKind::TensorRT => Some(
+                ort::execution_providers::TensorRTExecutionProvider::default()
+                    .with_device_id(self.device_id)
+                    .build(),
+            ),
+            #[cfg(feature = "coreml")]
+            ExecutionProviderKind::CoreML => {
+                Some(ort::execution_providers::CoreMLExecutionProvider::default().build())
+            }
+            #[cfg(feature = "directml")]
+            ExecutionProviderKind::DirectML => Some(
+                ort::execution_providers::DirectMLExecutionProvider::default()
+                    .with_device_id(self.device_id)
+                    .build(),
+            ),
+            #[cfg(feature = "openvino")]
+            ExecutionProviderKind::OpenVINO => {
+                Some(ort::execution_providers::OpenVINOExecutionProvider::default().build())
+            }
+            #[allow(unreachable_patterns)]
+            kind => {
+                warn!(
+                    "execution provider {:?} is not enabled in this build, skipped",
+                    kind
+                );
+                None
+            }
+        }
+    }
+}
+
+fn cpu_provider(arena: bool) -> ExecutionProviderDispatch {
+    if arena {
+        CPUExecutionProvider::default()
+            .with_arena_allocator()
+            .build()
+    } else {
+        CPUExecutionProvider::default().build()
+    }
+}
+
+fn is_fresh(cache_path: &Path, model_path: &Path) -> bool {
+    let modified = |p: &Path| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+    match (modified(cache_path), modified(model_path)) {
+        (Some(cache), Some(model)) => cache >= model,
+        _ => false,
+    }
+}
+
+/// 六个模型各自的 Session 参数，default 为公共部分
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Default)]
+pub struct ModelSessionsConfig {
+    #[serde(default)]
+    pub default: SessionConfig,
+    pub bert_model: Option<SessionConfig>,
+    pub ssl_model: Option<SessionConfig>,
+    pub vq_model_latent: Option<SessionConfig>,
+    pub t2s_first_stage_decoder: Option<SessionConfig>,
+    pub t2s_stage_decoder: Option<SessionConfig>,
+    pub vq_model: Option<SessionConfig>,
+    /// 多音字模型，不继承 default，未配置时在 CPU 上运行
+    pub g2pw: Option<SessionConfig>,
+    /// 可选的英文 bert，与 bert_model 一样继承 default
+    pub en_bert: Option<SessionConfig>,
+}
+
+impl ModelSessionsConfig {
+    fn resolve(&self, model: &Option<SessionConfig>) -> SessionConfig {
+        match model {
+            Some(config) => self.default.merge(config),
+            None => self.default.clone(),
+        }
+    }
+
+    pub fn bert_model(&self) -> SessionConfig {
+        self.resolve(&self.bert_model)
+    }
+
+    pub fn ssl_model(&self) -> SessionConfig {
+        self.resolve(&self.ssl_model)
+    }
+
+    pub fn vq_model_latent(&self) -> SessionConfig {
+        self.resolve(&self.vq_model_latent)
+    }
+
+    pub fn t2s_first_stage_decoder(&self) -> SessionConfig {
+        self.resolve(&self.t2s_first_stage_decoder)
+    }
+
+    pub fn t2s_stage_decoder(&self) -> SessionConfig {
+        self.resolve(&self.t2s_stage_decoder)
+    }
+
+    pub fn vq_model(&self) -> SessionConfig {
+        self.resolve(&self.vq_model)
+    }
+
+    pub fn g2pw(&self) -> SessionConfig {
+        self.g2pw.clone().unwrap_or_default()
+    }
+
+    pub fn en_bert(&self) -> SessionConfig {
+        self.resolve(&self.en_bert)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge() {
+        let config: ModelSessionsConfig = serde_json::from_str(
+            r#"{
+                "default": {"intra_threads": 2, "optimization_level": "level3", "cpu_arena": false},
+                "t2s_stage_decoder": {"intra_threads": 4, "execution_providers": [{"kind": "cpu"}]}
+            }"#,
+        )
+        .unwrap();
+
+        let bert = config.bert_model();
+        assert_eq!(bert.intra_threads, Some(2));
+        assert_eq!(bert.optimization_level, Some(OptimizationLevel::Level3));
+        assert!(bert.execution_providers.is_empty());
+
+        let decoder = config.t2s_stage_decoder();
+        assert_eq!(decoder.intra_threads, Some(4));
+        assert_eq!(decoder.optimization_level, Some(OptimizationLevel::Level3));
+        assert_eq!(decoder.cpu_arena, Some(false));
+        assert_eq!(
+            decoder.execution_providers,
+            vec![ExecutionProviderConfig {
+                kind: ExecutionProviderKind::Cpu,
+                device_id: 0
+            }]
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use super::zh_normalization::text_normalization::TextNormalizer;
+use std::collections::HashMap;
+use std::fs;
+
+/// 与 GPT-SoVITS cantonese.py 一致的拆分顺序，零声母音节按韵母开头拆分
+const INITIALS: [&str; 43] = [
+    "aa", "aai", "aak", "aap", "aat", "aau", "ai", "au", "ap", "at", "ak", "a", "p", "b", "e",
+    "ts", "t", "dz", "d", "kw", "k", "gw", "g", "f", "h", "l", "m", "ng", "n", "s", "y", "w", "c",
+    "z", "j", "ong", "on", "ou", "oi", "ok", "o", "uk", "ung",
+];
+
+const PUNCTUATION: [char; 6] = ['!', '?', '…', ',', '.', '-'];
+
+pub struct Cantonese {
+    /// 字、词 -> 粤拼，多个音节以空格分隔
+    jyutping_dict: HashMap<String, String>,
+    max_word_len: usize,
+    text_normalizer: TextNormalizer,
+}
+
+/// 粤拼音节转为 v2 粤语音素：声母 Y+声母，韵母 Y+韵母+声调
+fn jyutping_to_phones(syllable: &str) -> Vec<String> {
+    let (body, tone) = match syllable.char_indices().last() {
+        Some((i, c)) if c.is_ascii_digit() => (&syllable[..i], &syllable[i..]),
+        _ => (syllable, ""),
+    };
+    let Some(initial) = INITIALS.iter().find(|i| body.starts_with(**i)) else {
+        return vec![syllable.to_string()];
+    };
+    let final_ = match &body[initial.len()..] {
+        "" => &initial[initial.len() - 1..],
+        final_ => final_,
+    };
+    vec![format!("Y{}", initial), format!("Y{}{}", final_, tone)]
+}
+
+impl Cantonese {
+    pub fn new(jyutping_dict_path: &str, zh_dict_path: &str) -> Result<Self, String> {
+        let jyutping_dict: HashMap<String, String> =
+            serde_json::from_reader(fs::File::open(jyutping_dict_path).map_err(|e| e.to_string())?)
+                .map_err(|e| e.to_string())?;
+        let max_word_len = jyutping_dict
+            .keys()
+            .map(|w| w.chars().count())
+            .max()
+            .unwrap_or(1);
+        Ok(Self {
+            jyutping_dict,
+            max_word_len,
+            text_normalizer: TextNormalizer::new(zh_dict_path),
+        })
+    }
+
+    /// 数字、日期、时间等按粤语读法转为汉字，保留繁体
+    pub fn text_normalize(&self, text: &str) -> String {
+        self.text_normalizer.normalize_yue(text).concat()
+    }
+
+    /// 按词典最长匹配查粤拼，词典没有的词退回逐字查字表，字表也没有的字原样返回，由音素表报告
+    pub fn g2p(&self, text: &str) -> Vec<String> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut phones = vec![];
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            if c.is_whitespace() {
+                i += 1;
+                continue;
+            }
+            if PUNCTUATION.contains(&c) {
+                phones.push(c.to_string());
+                i += 1;
+                continue;
+            }
+            let matched = (1..=self.max_word_len.min(chars.len() - i))
+                .rev()
+                .find_map(|len| {
+                    let word: String = chars[i..i + len].iter().collect();
+                    self.jyutping_dict
+                        .get(&word)
+                        .map(|jyutping| (len, jyutping))
+                });
+            match matched {
+                Some((len, jyutping)) => {
+                    phones.extend(jyutping.split(' ').flat_map(jyutping_to_phones));
+                    i += len;
+                }
+                None => {
+                    phones.push(c.to_string());
+                    i += 1;
+                }
+            }
+        }
+        phones
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jyutping_to_phones() {
+        assert_eq!(jyutping_to_phones("gwong2"), vec!["Ygw", "Yong2"]);
+        assert_eq!(jyutping_to_phones("ngan4"), vec!["Yng", "Yan4"]);
+        assert_eq!(jyutping_to_phones("m4"), vec!["Ym", "Ym4"]);
+        assert_eq!(jyutping_to_phones("ou3"), vec!["You", "Yu3"]);
+        assert_eq!(jyutping_to_phones("jyut6"), vec!["Yj", "Yyut6"]);
+    }
+
+    #[test]
+    fn test_g2p() {
+        let yue = Cantonese::new("../assets/yue_dict.json", "../assets/zh_dict.json").unwrap();
+        let text = yue.text_normalize("列車將於8點到達香港站。");
+        assert_eq!(text, "列車將於八點到達香港站。");
+        assert_eq!(
+            yue.g2p("銀行到站,"),
+            vec!["Yng", "Yan4", "Yh", "Yong4", "Yd", "You3", "Yz", "Yaam6", ","]
+        );
+        assert_eq!(yue.g2p("於"), vec!["Yj", "Yyu1"]);
+        // 没有收词的词逐字查读音
+        assert_eq!(yue.g2p("一塊"), vec!["Yj", "Yat1", "Yf", "Yaai3"]);
+        assert_eq!(yue.g2p("块"), vec!["Yf", "Yaai3"]);
+        assert_eq!(yue.g2p("毛"), vec!["Ym", "You4"]);
+    }
+}
@@ -1,13 +1,17 @@
+use super::g2pw::G2pw;
 use super::lazy_pinyin::pinyin::LazyPinyin;
 use super::lazy_pinyin::style::Style;
 use super::tone_sandhi::ToneSandhi;
+use super::zh_lexicon::{split_syllable, ChineseLexiconConfig, UserLexicon};
 use super::zh_normalization::opencpop_strict::OPENCPOP_STRICT;
 use super::zh_normalization::text_normalization::TextNormalizer;
 use fancy_regex::{Captures, Regex};
-use jieba_rs::Jieba;
 use lazy_static::lazy_static;
 use log::info;
+use serde::Serialize;
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::RwLock;
 
 lazy_static! {
     // 常量数据
@@ -43,14 +47,40 @@ lazy_static! {
     static ref RE_ENGLISH_LETTER: Regex = Regex::new(r"[a-zA-Z]+").unwrap();
 }
 
+/// 调试用：分词结果和变调过程
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct WordTrace {
+    pub word: String,
+    pub pos: String,
+    pub initials: Vec<String>,
+    /// 变调前的韵母
+    pub finals: Vec<String>,
+    /// 依次生效的变调规则（bu、yi、neural、three）及变调后的韵母
+    pub sandhi: Vec<(String, Vec<String>)>,
+}
+
+impl WordTrace {
+    /// 变调后的韵母，即最后一条生效规则的结果
+    fn modified_finals(&self) -> &[String] {
+        self.sandhi
+            .last()
+            .map_or(&self.finals, |(_, finals)| finals)
+    }
+}
+
 pub struct Chinese {
     rep_map: HashMap<String, String>,
     pinyin_to_symbol_map: HashMap<String, String>,
     pattern: Regex,
     text_normalizer: TextNormalizer,
-    jieba_util: Jieba,
+    /// 用户词典和 jieba，可在运行时重新加载
+    lexicon: RwLock<UserLexicon>,
+    /// 配置的用户词典路径，加载失败时也保留，重新加载时从这里读取
+    lexicon_config: RwLock<ChineseLexiconConfig>,
     tone_modifier: ToneSandhi,
     lazy_pinyin: LazyPinyin,
+    /// 多音字模型，未加载时只查词典
+    g2pw: Option<G2pw>,
 }
 
 impl Chinese {
@@ -82,12 +112,49 @@ impl Chinese {
             pinyin_to_symbol_map,
             pattern,
             text_normalizer: TextNormalizer::new("../assets/zh_dict.json"),
-            jieba_util: Jieba::new(),
+            lexicon: RwLock::new(UserLexicon::default()),
+            lexicon_config: RwLock::new(ChineseLexiconConfig {
+                user_lexicon: None,
+                jieba_dict: None,
+            }),
             tone_modifier: ToneSandhi::new(),
             lazy_pinyin: LazyPinyin::new(phrases_dict_path, pinyin_dict_path).unwrap(),
+            g2pw: None,
         })
     }
 
+    pub fn set_g2pw(&mut self, g2pw: G2pw) {
+        self.g2pw = Some(g2pw);
+    }
+
+    /// 记录配置并加载用户词典和 jieba 自定义词典，整体替换当前词典，返回用户词条数；
+    /// 出错时保留原词典，之后重新加载仍按这份配置读取
+    pub fn load_user_dicts(&self, config: &ChineseLexiconConfig) -> Result<usize, String> {
+        *self.lexicon_config.write().unwrap() = config.clone();
+        let lexicon = self.read_user_lexicon()?;
+        Ok(self.set_user_lexicon(lexicon))
+    }
+
+    /// 整体替换当前用户词典，返回用户词条数
+    pub fn set_user_lexicon(&self, lexicon: UserLexicon) -> usize {
+        let len = lexicon.len();
+        info!(
+            "user lexicon {:?} loaded, {} words, jieba dict {:?}",
+            lexicon.lexicon_path, len, lexicon.jieba_dict_path
+        );
+        *self.lexicon.write().unwrap() = lexicon;
+        len
+    }
+
+    /// 按配置的路径重新读取用户词典和 jieba 自定义词典，不替换当前词典；不存在的文件跳过
+    pub fn read_user_lexicon(&self) -> Result<UserLexicon, String> {
+        let config = self.lexicon_config.read().unwrap().clone();
+        let existing = |path: &Option<String>| path.clone().filter(|p| Path::new(p).exists());
+        let lexicon_path = existing(&config.user_lexicon);
+        let jieba_dict_path = existing(&config.jieba_dict);
+        UserLexicon::load(lexicon_path.as_deref(), jieba_dict_path.as_deref())
+    }
+
     pub fn text_normalize(&self, text: &str) -> String {
         let replaced_text = self.replace_symbol(text);
         self.text_normalizer
@@ -97,6 +164,11 @@ impl Chinese {
             .collect()
     }
 
+    /// 读法标签以及网址、邮箱、ISO 时间、3h20min 等带字母的写法读作中文，语种分段前调用
+    pub fn normalize_latin(&self, text: &str) -> String {
+        self.text_normalizer.normalize_latin(text)
+    }
+
     /// 符号统一替换为英文输入下的符号
     pub fn replace_symbol(&self, text: &str) -> String {
         self.pattern
@@ -140,6 +212,40 @@ impl Chinese {
         (initials, finals)
     }
 
+    /// 查内置词典，g2pW 给出读音的多音字以模型为准；offset 为 part 在句中的字下标
+    fn lookup_pinyin(
+        &self,
+        part: &str,
+        offset: usize,
+        predictions: &HashMap<usize, String>,
+    ) -> (Vec<String>, Vec<String>) {
+        let (mut initials, mut finals) = self.extract_initials_and_finals(part);
+        // 词典结果与字一一对应时才替换
+        if predictions.is_empty() || initials.len() != part.chars().count() {
+            return (initials, finals);
+        }
+        for i in 0..initials.len() {
+            let Some(pinyin) = predictions.get(&(offset + i)) else {
+                continue;
+            };
+            match split_syllable(pinyin) {
+                Ok((initial, final_)) => {
+                    initials[i] = initial;
+                    finals[i] = final_;
+                }
+                Err(e) => info!("g2pw: {}", e),
+            }
+        }
+        (initials, finals)
+    }
+
+    fn predict_polyphones(&self, sentence: &str) -> HashMap<usize, String> {
+        self.g2pw
+            .as_ref()
+            .map(|g2pw| g2pw.predict(sentence))
+            .unwrap_or_default()
+    }
+
     /// 标点符号替换
     fn replace_punctuation(&self, text: &str) -> String {
         let cleaned_text = text.replace("嗯", "恩").replace("呣", "母");
@@ -148,34 +254,74 @@ impl Chinese {
             .to_string()
     }
 
+    /// 按 g2p 的流程规整、分词、注音，返回每个词的分词和变调结果
+    pub fn explain(&self, text: &str) -> Vec<WordTrace> {
+        let text = self.text_normalize(text);
+        let lexicon = self.lexicon.read().unwrap();
+        RE_SENTENCE_SPLIT
+            .replace_all(&text, |caps: &Captures| format!("{}\n", &caps[0]))
+            .split('\n')
+            .filter(|line| !line.trim().is_empty())
+            .flat_map(|seg| self.word_traces(seg, &lexicon))
+            .collect()
+    }
+
+    /// 一段文本的分词、注音和变调，g2p 与 explain 共用
+    fn word_traces(&self, seg: &str, lexicon: &UserLexicon) -> Vec<WordTrace> {
+        // 移除英文字符
+        let rp_seg = RE_ENGLISH_LETTER.replace_all(seg, "").to_string();
+
+        // 分词并处理
+        let seg_cut = self
+            .tone_modifier
+            .pre_merge_for_modify(&lexicon.jieba.tag(&rp_seg, false));
+        let predictions = self.predict_polyphones(&rp_seg);
+        let mut offset = 0;
+        let mut traces = vec![];
+        for (word, pos) in seg_cut {
+            let word_offset = offset;
+            offset += word.chars().count();
+            if pos == "eng" {
+                continue;
+            }
+
+            // 用户词典优先，其次多音字模型，其余查内置词典
+            let (initials, finals) = lexicon.initials_and_finals(&word, |start, part| {
+                self.lookup_pinyin(part, word_offset + start, &predictions)
+            });
+            let mut sandhi = vec![];
+            self.tone_modifier.modified_tone_traced(
+                &word,
+                &pos,
+                finals.clone(),
+                &lexicon.jieba,
+                &mut sandhi,
+            );
+            traces.push(WordTrace {
+                word,
+                pos,
+                initials,
+                finals,
+                sandhi: sandhi
+                    .into_iter()
+                    .map(|(rule, finals)| (rule.to_string(), finals))
+                    .collect(),
+            });
+        }
+        traces
+    }
+
     fn _g2p(&self, segments: &[String]) -> (Vec<String>, Vec<usize>) {
         let mut phones_list: Vec<String> = vec![];
         let mut word2ph: Vec<usize> = vec![];
+        let lexicon = self.lexicon.read().unwrap();
 
         for seg in segments {
-            // 移除英文字符
-            let rp_seg = RE_ENGLISH_LETTER.replace_all(seg, "").to_string();
-
-            // 分词并处理
-            let seg_cut = self
-                .tone_modifier
-                .pre_merge_for_modify(&self.jieba_util.tag(&rp_seg, false));
-
             let mut initials = Vec::new();
             let mut finals = Vec::new();
-
-            for (word, pos) in seg_cut {
-                if pos == "eng" {
-                    continue;
-                }
-
-                let (mut sub_initials, sub_finals) = self.extract_initials_and_finals(&word);
-                let sub_finals =
-                    self.tone_modifier
-                        .modified_tone(&word, &pos, sub_finals, &self.jieba_util);
-
-                initials.append(&mut sub_initials);
-                finals.extend(sub_finals);
+            for trace in self.word_traces(seg, &lexicon) {
+                finals.extend_from_slice(trace.modified_finals());
+                initials.extend(trace.initials);
             }
 
             for (c, v) in initials.into_iter().zip(finals) {
@@ -374,4 +520,100 @@ mod tests {
         );
         assert_eq!(word2ph, vec![2, 2, 2, 2, 2]);
     }
+
+    #[test]
+    fn test_user_lexicon() {
+        let chinese = Chinese::new(
+            "../assets/rep_map.json",
+            "../assets/PHRASES_DICT.json",
+            "../assets/PINYIN_DICT.json",
+        )
+        .unwrap();
+        assert_eq!(chinese.read_user_lexicon().map(|l| l.len()), Ok(0));
+        chinese
+            .load_user_dicts(&ChineseLexiconConfig {
+                user_lexicon: Some("../assets/zh_user_lexicon.json".to_string()),
+                jieba_dict: None,
+            })
+            .unwrap();
+        let (phones, _) = chinese.g2p("前方到站六安站");
+        assert_eq!(&phones[8..12], ["l", "u4", "AA", "an1"]);
+        // 用户词典写原调，“一”的变调仍按规则处理
+        let (phones, word2ph) = chinese.g2p("一维诶爱");
+        assert_eq!(
+            phones,
+            vec!["y", "i4", "w", "ei2", "EE", "ei4", "AA", "ai4"]
+        );
+        assert_eq!(word2ph, vec![2, 2, 2, 2]);
+        assert!(chinese.read_user_lexicon().is_ok_and(|l| l.len() > 0));
+    }
+
+    #[test]
+    fn test_reload_after_failed_load() {
+        let chinese = Chinese::new(
+            "../assets/rep_map.json",
+            "../assets/PHRASES_DICT.json",
+            "../assets/PINYIN_DICT.json",
+        )
+        .unwrap();
+        let path = std::env::temp_dir().join("sovits_test_zh_user_lexicon.json");
+        std::fs::write(&path, "{").unwrap();
+        let config = ChineseLexiconConfig {
+            user_lexicon: Some(path.to_str().unwrap().to_string()),
+            jieba_dict: None,
+        };
+        // 启动时加载失败，修好文件后按配置的路径重新读取
+        assert!(chinese.load_user_dicts(&config).is_err());
+        assert!(chinese.read_user_lexicon().is_err());
+        std::fs::write(&path, r#"{"六安": {"pinyin": "lu4 an1", "pos": "ns"}}"#).unwrap();
+        let lexicon = chinese.read_user_lexicon().unwrap();
+        assert_eq!(chinese.set_user_lexicon(lexicon), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_explain() {
+        let chinese = Chinese::new(
+            "../assets/rep_map.json",
+            "../assets/PHRASES_DICT.json",
+            "../assets/PINYIN_DICT.json",
+        )
+        .unwrap();
+        chinese
+            .load_user_dicts(&ChineseLexiconConfig {
+                user_lexicon: None,
+                jieba_dict: Some("../assets/zh_jieba_dict.txt".to_string()),
+            })
+            .unwrap();
+        let traces = chinese.explain("智行港你好");
+        assert_eq!(traces[0].word, "智行港");
+        assert_eq!(traces[0].pos, "ns");
+        let nihao = &traces[1];
+        assert_eq!(nihao.word, "你好");
+        assert_eq!(nihao.finals, vec!["i3", "ao3"]);
+        // g2p 使用同一份结果
+        assert_eq!(nihao.modified_finals(), ["i2", "ao3"]);
+        assert_eq!(
+            nihao.sandhi,
+            vec![(
+                "three".to_string(),
+                vec!["i2".to_string(), "ao3".to_string()]
+            )]
+        );
+    }
+
+    #[test]
+    fn test_lookup_pinyin() {
+        let chinese = Chinese::new(
+            "../assets/rep_map.json",
+            "../assets/PHRASES_DICT.json",
+            "../assets/PINYIN_DICT.json",
+        )
+        .unwrap();
+        // 模型预测以句中字下标为准，未预测的字查词典
+        let predictions = HashMap::from([(3, "zhang3".to_string())]);
+        let (initials, finals) = chinese.lookup_pinyin("长大", 3, &predictions);
+        assert_eq!(initials, vec!["zh", "d"]);
+        assert_eq!(finals, vec!["ang3", "a4"]);
+    }
 }
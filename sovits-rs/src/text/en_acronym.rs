@@ -0,0 +1,122 @@
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+
+lazy_static! {
+    // 带点的缩写：U.S.A.、a.m.、Ph.D.
+    static ref RE_DOTTED: Regex = Regex::new(r"\b(?:[A-Za-z][a-z]?\.){2,}").unwrap();
+    // 全大写，可带复数 s：GPS、GPUs
+    static ref RE_ALL_CAPS: Regex = Regex::new(r"^([A-Z]{2,})(s?)$").unwrap();
+}
+
+/// 字母名的音素，与 CMUdict 中单个字母的读音一致
+#[rustfmt::skip]
+const LETTER_PHONES: [&[&str]; 26] = [
+    &["EY1"], &["B", "IY1"], &["S", "IY1"], &["D", "IY1"], &["IY1"], &["EH1", "F"],
+    &["JH", "IY1"], &["EY1", "CH"], &["AY1"], &["JH", "EY1"], &["K", "EY1"], &["EH1", "L"],
+    &["EH1", "M"], &["EH1", "N"], &["OW1"], &["P", "IY1"], &["K", "Y", "UW1"], &["AA1", "R"],
+    &["EH1", "S"], &["T", "IY1"], &["Y", "UW1"], &["V", "IY1"],
+    &["D", "AH1", "B", "AH0", "L", "Y", "UW0"], &["EH1", "K", "S"], &["W", "AY1"], &["Z", "IY1"],
+];
+
+/// 不区分大小写也按字母读的缩写
+#[rustfmt::skip]
+const KNOWN_ACRONYMS: &[&str] = &[
+    "api", "atm", "ceo", "cfo", "cpu", "diy", "faq", "gps", "gpu", "html", "http", "https", "pc",
+    "pdf", "phd", "tv", "url", "usb", "vip", "www",
+];
+
+/// 同时是普通单词、只在全大写时按字母读的缩写
+const CAPS_ACRONYMS: &[&str] = &["AI", "ID", "UK", "US"];
+
+/// 全大写但按单词读的缩写
+#[rustfmt::skip]
+const WORD_ACRONYMS: &[&str] = &[
+    "AIDS", "ASEAN", "COVID", "FIFA", "GIF", "IKEA", "JPEG", "LASER", "NASA", "NASDAQ", "NATO",
+    "OPEC", "PIN", "RADAR", "RAM", "ROM", "SARS", "SCUBA", "SIM", "UNESCO", "UNICEF", "WAN", "ZIP",
+];
+
+/// 带点的缩写去掉点并转为大写：U.S.A. -> USA，之后按全大写缩写拼读
+pub(crate) fn join_dotted(text: &str) -> String {
+    RE_DOTTED
+        .replace_all(text, |caps: &Captures| {
+            caps[0].replace('.', "").to_uppercase()
+        })
+        .to_string()
+}
+
+/// 缩写逐个字母读，返回字母名音素；不是缩写时返回 None
+///
+/// 全大写的词在词典中有读音时按单词读（EXIT、HELLO），in_dict 查词典
+pub(crate) fn spell(word: &str, in_dict: impl Fn(&str) -> bool) -> Option<Vec<String>> {
+    let (letters, plural) = if KNOWN_ACRONYMS.contains(&word.to_lowercase().as_str()) {
+        (word.to_string(), false)
+    } else {
+        let caps = RE_ALL_CAPS.captures(word)?;
+        if WORD_ACRONYMS.contains(&&caps[1]) {
+            return None;
+        }
+        if !CAPS_ACRONYMS.contains(&&caps[1]) && (in_dict(word) || in_dict(&caps[1])) {
+            return None;
+        }
+        (caps[1].to_string(), !caps[2].is_empty())
+    };
+
+    let mut phones: Vec<String> = spell_letters(&letters)
+        .into_iter()
+        .map(String::from)
+        .collect();
+    if plural {
+        phones.push("Z".to_string());
+    }
+    Some(phones)
+}
+
+/// 逐个字母读，跳过非字母字符
+pub(crate) fn spell_letters(word: &str) -> Vec<&'static str> {
+    word.bytes()
+        .filter(u8::is_ascii_alphabetic)
+        .flat_map(|b| {
+            LETTER_PHONES[(b.to_ascii_uppercase() - b'A') as usize]
+                .iter()
+                .copied()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spell() {
+        let spell = |word: &str| super::spell(word, |_| false);
+        assert_eq!(spell("IBM").unwrap(), vec!["AY1", "B", "IY1", "EH1", "M"]);
+        assert_eq!(spell("AI").unwrap(), vec!["EY1", "AY1"]);
+        assert_eq!(spell("gps").unwrap(), spell("GPS").unwrap());
+        assert_eq!(spell("GPUs").unwrap().last().unwrap(), "Z");
+        // 按单词读的缩写和普通单词不拼读
+        assert!(spell("NASA").is_none());
+        assert!(spell("Hello").is_none());
+        assert!(spell("A").is_none());
+        assert!(spell("I'M").is_none());
+
+        // 词典中有的全大写单词按单词读，已知缩写仍拼读
+        let in_dict = |w: &str| ["EXIT", "STOP", "US", "GPS"].contains(&w);
+        assert!(super::spell("EXIT", in_dict).is_none());
+        assert!(super::spell("STOPs", in_dict).is_none());
+        assert!(super::spell("US", in_dict).is_some());
+        assert!(super::spell("GPS", in_dict).is_some());
+
+        assert_eq!(
+            spell_letters("Wi-Fi"),
+            vec!["D", "AH1", "B", "AH0", "L", "Y", "UW0", "AY1", "EH1", "F", "AY1"]
+        );
+    }
+
+    #[test]
+    fn test_join_dotted() {
+        assert_eq!(join_dotted("the U.S.A. team"), "the USA team");
+        assert_eq!(join_dotted("a Ph.D. student"), "a PHD student");
+        assert_eq!(join_dotted("end. Next"), "end. Next");
+    }
+}
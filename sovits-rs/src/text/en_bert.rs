@@ -0,0 +1,212 @@
+use crate::session_config::SessionConfig;
+use anyhow::{ensure, Context};
+use ndarray::{s, Array1, Array2, Axis};
+use ort::{inputs, session::Session};
+use tokenizers::Tokenizer;
+
+/// bert 的最大输入长度，超过时不走模型
+const MAX_LEN: usize = 512;
+/// 与中文 bert 特征的维度一致
+pub const FEATURE_DIM: usize = 1024;
+
+/// 英文（或多语言）bert，自带 tokenizer，按 English::g2p_words 的分词把 token 特征对齐到音素
+pub struct EnglishBert {
+    session: Session,
+    tokenizer: Tokenizer,
+    /// 部分模型导出时没有 token_type_ids 输入
+    token_type_ids: bool,
+}
+
+impl EnglishBert {
+    pub fn new(
+        model_path: &str,
+        tokenizer_path: &str,
+        config: &SessionConfig,
+    ) -> anyhow::Result<Self> {
+        let tokenizer = Tokenizer::from_file(tokenizer_path)
+            .map_err(anyhow::Error::msg)
+            .with_context(|| format!("failed to load {}", tokenizer_path))?;
+        let session = config
+            .load("en_bert", model_path)
+            .with_context(|| format!("failed to load {}", model_path))?;
+        let token_type_ids = session
+            .inputs
+            .iter()
+            .any(|input| input.name == "token_type_ids");
+        let bert = Self {
+            session,
+            tokenizer,
+            token_type_ids,
+        };
+
+        // 加载时用空文本跑一次，确认隐层维度与中文 bert 一致
+        let encoding = bert
+            .tokenizer
+            .encode("", true)
+            .map_err(anyhow::Error::msg)?;
+        let hidden_states = bert.run(
+            encoding.get_ids(),
+            encoding.get_attention_mask(),
+            encoding.get_type_ids(),
+        )?;
+        ensure!(
+            hidden_states.ncols() == FEATURE_DIM,
+            "hidden size {} is not {}",
+            hidden_states.ncols(),
+            FEATURE_DIM
+        );
+        Ok(bert)
+    }
+
+    /// words 为 English::g2p_words 切出的词及其音素数，返回 [1024, 音素数] 的特征
+    pub fn features(&self, words: &[(String, usize)]) -> anyhow::Result<Array2<f32>> {
+        let text: String = words.iter().map(|(w, _)| w.as_str()).collect();
+        let encoding = self
+            .tokenizer
+            .encode(text.as_str(), true)
+            .map_err(anyhow::Error::msg)?;
+        ensure!(
+            encoding.len() <= MAX_LEN,
+            "{} tokens exceed the max length {}",
+            encoding.len(),
+            MAX_LEN
+        );
+
+        let hidden_states = self.run(
+            encoding.get_ids(),
+            encoding.get_attention_mask(),
+            encoding.get_type_ids(),
+        )?;
+
+        let token_words = align_tokens(
+            words,
+            encoding.get_offsets(),
+            encoding.get_special_tokens_mask(),
+        );
+        let phone_counts: Vec<usize> = words.iter().map(|&(_, count)| count).collect();
+        Ok(phone_level_features(
+            &hidden_states,
+            &token_words,
+            &phone_counts,
+        ))
+    }
+
+    /// 返回最后一层的 [token 数, 隐层维度]
+    fn run(&self, ids: &[u32], mask: &[u32], type_ids: &[u32]) -> anyhow::Result<Array2<f32>> {
+        let to_input = |values: &[u32]| -> Array2<i64> {
+            Array1::from_iter(values.iter().map(|&x| x as i64)).insert_axis(Axis(0))
+        };
+        let outputs = if self.token_type_ids {
+            self.session.run(inputs![
+                "input_ids" => to_input(ids),
+                "attention_mask" => to_input(mask),
+                "token_type_ids" => to_input(type_ids),
+            ]?)?
+        } else {
+            self.session.run(inputs![
+                "input_ids" => to_input(ids),
+                "attention_mask" => to_input(mask),
+            ]?)?
+        };
+        let hidden_states = outputs[0].try_extract_tensor::<f32>()?;
+        ensure!(
+            hidden_states.ndim() == 3,
+            "expected [batch, tokens, hidden], got {:?}",
+            hidden_states.shape()
+        );
+        Ok(hidden_states.slice(s![0, .., ..]).to_owned())
+    }
+}
+
+/// token 按字节偏移归入所在的词，特殊 token 不属于任何词
+fn align_tokens(
+    words: &[(String, usize)],
+    offsets: &[(usize, usize)],
+    special_tokens_mask: &[u32],
+) -> Vec<Option<usize>> {
+    let mut spans = vec![];
+    let mut start = 0;
+    for (w, _) in words {
+        spans.push((start, start + w.len()));
+        start += w.len();
+    }
+    offsets
+        .iter()
+        .zip(special_tokens_mask)
+        .map(|(&(token_start, _), &special)| {
+            if special == 1 {
+                return None;
+            }
+            spans
+                .iter()
+                .position(|&(start, end)| start <= token_start && token_start < end)
+        })
+        .collect()
+}
+
+/// 词的特征取其 token 特征的平均，重复到该词的每个音素；没有 token 的词（如被 tokenizer 丢掉的符号）为全零
+fn phone_level_features(
+    hidden_states: &Array2<f32>,
+    token_words: &[Option<usize>],
+    phone_counts: &[usize],
+) -> Array2<f32> {
+    let dim = hidden_states.ncols();
+    let total: usize = phone_counts.iter().sum();
+    let mut features = Array2::<f32>::zeros((dim, total));
+    let mut column = 0;
+    for (word, &count) in phone_counts.iter().enumerate() {
+        let rows: Vec<usize> = token_words
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| **w == Some(word))
+            .map(|(i, _)| i)
+            .collect();
+        if count > 0 && !rows.is_empty() {
+            let mean = hidden_states
+                .select(Axis(0), &rows)
+                .mean_axis(Axis(0))
+                .unwrap();
+            for c in column..column + count {
+                features.column_mut(c).assign(&mean);
+            }
+        }
+        column += count;
+    }
+    features
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(pieces: &[(&str, usize)]) -> Vec<(String, usize)> {
+        pieces.iter().map(|&(w, n)| (w.to_string(), n)).collect()
+    }
+
+    #[test]
+    fn test_align_tokens() {
+        // "Hello world" -> [CLS] hello wor ##ld [SEP]
+        let words = words(&[("Hello", 4), (" ", 0), ("world", 4)]);
+        let offsets = [(0, 0), (0, 5), (6, 9), (9, 11), (0, 0)];
+        let special = [1, 0, 0, 0, 1];
+        assert_eq!(
+            align_tokens(&words, &offsets, &special),
+            vec![None, Some(0), Some(2), Some(2), None]
+        );
+    }
+
+    #[test]
+    fn test_phone_level_features() {
+        let hidden_states =
+            Array2::from_shape_vec((4, 2), vec![0.0, 0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+        let token_words = [None, Some(0), Some(2), Some(2)];
+        let features = phone_level_features(&hidden_states, &token_words, &[2, 0, 1, 1]);
+        assert_eq!(features.shape(), &[2, 4]);
+        assert_eq!(features.column(0).to_vec(), vec![1.0, 2.0]);
+        assert_eq!(features.column(1).to_vec(), vec![1.0, 2.0]);
+        // 两个 token 取平均
+        assert_eq!(features.column(2).to_vec(), vec![4.0, 5.0]);
+        // 没有 token 的词为全零
+        assert_eq!(features.column(3).to_vec(), vec![0.0, 0.0]);
+    }
+}
@@ -0,0 +1,186 @@
+use super::symbols::_ARPA;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 英文查词的来源，按 lookup_order 依次查找，都查不到时交给 g2p 模型
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum LexiconSource {
+    /// 用户词典，品牌名、地名、英文句中的拼音人名等
+    User,
+    /// 全大写、带点或已知的缩写逐个字母读
+    Acronym,
+    /// CMUdict 格式的词典
+    Cmudict,
+    /// eng_dict.json
+    EngDict,
+}
+
+/// 英文词典配置
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(default)]
+pub struct EnglishLexiconConfig {
+    /// CMUdict 格式的词典文件，后面的覆盖前面的
+    pub cmudict: Vec<String>,
+    /// JSON 用户词典 {"Huawei": "HH W AA1 W EY2"}
+    pub user_lexicon: Option<String>,
+    pub lookup_order: Vec<LexiconSource>,
+}
+
+impl Default for EnglishLexiconConfig {
+    fn default() -> Self {
+        Self {
+            cmudict: vec![],
+            user_lexicon: Some("../assets/en_user_lexicon.json".to_string()),
+            lookup_order: vec![
+                LexiconSource::User,
+                LexiconSource::Acronym,
+                LexiconSource::Cmudict,
+                LexiconSource::EngDict,
+            ],
+        }
+    }
+}
+
+/// 空格分隔的音素，每个都须在 ARPAbet 音素表中
+fn parse_phones(phones: &str) -> Result<Vec<String>, String> {
+    let phones: Vec<String> = phones.split_whitespace().map(String::from).collect();
+    if phones.is_empty() {
+        return Err("empty pronunciation".to_string());
+    }
+    match phones.iter().find(|ph| !_ARPA.contains(&ph.as_str())) {
+        Some(ph) => Err(format!("unknown ARPAbet phone {}", ph)),
+        None => Ok(phones),
+    }
+}
+
+/// 解析 CMUdict 格式：每行 词 音素...，;;; 或 # 开头为注释，行尾 # 之后为注释；
+/// WORD(1) 等多音词只取第一个读音
+pub(crate) fn parse_cmudict(
+    content: &str,
+    path: &str,
+) -> Result<HashMap<String, Vec<String>>, String> {
+    let mut words = HashMap::new();
+    for (line_no, line) in content.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() || line.starts_with(";;;") {
+            continue;
+        }
+        let (word, phones) = line
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| format!("{}:{} invalid entry: {}", path, line_no + 1, line))?;
+        let phones = parse_phones(phones)
+            .map_err(|e| format!("{}:{} {}: {}", path, line_no + 1, word, e))?;
+        let word = match word.split_once('(') {
+            Some((base, _)) => base,
+            None => word,
+        };
+        words.entry(word.to_uppercase()).or_insert(phones);
+    }
+    Ok(words)
+}
+
+/// 用户词典与 CMUdict 词典，重新加载时整体替换
+#[derive(Default)]
+pub struct EnglishLexicon {
+    pub config: EnglishLexiconConfig,
+    /// 大写的词 -> 音素
+    user_words: HashMap<String, Vec<String>>,
+    cmudict: HashMap<String, Vec<String>>,
+}
+
+impl EnglishLexicon {
+    /// 任何一个文件或一条词条有误都不加载
+    pub fn load(config: &EnglishLexiconConfig) -> Result<Self, String> {
+        let mut cmudict = HashMap::new();
+        for path in &config.cmudict {
+            let content = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+            cmudict.extend(parse_cmudict(&content, path)?);
+        }
+        let entries: HashMap<String, String> = match &config.user_lexicon {
+            Some(path) => serde_json::from_reader(
+                std::fs::File::open(path).map_err(|e| format!("{}: {}", path, e))?,
+            )
+            .map_err(|e| format!("{}: {}", path, e))?,
+            None => HashMap::new(),
+        };
+        let user_words = entries
+            .into_iter()
+            .map(|(word, phones)| {
+                parse_phones(&phones)
+                    .map(|phones| (word.to_uppercase(), phones))
+                    .map_err(|e| format!("{}: {}", word, e))
+            })
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(Self {
+            config: config.clone(),
+            user_words,
+            cmudict,
+        })
+    }
+
+    /// 用户词条数
+    pub fn len(&self) -> usize {
+        self.user_words.len()
+    }
+
+    pub fn cmudict_len(&self) -> usize {
+        self.cmudict.len()
+    }
+
+    pub fn user_word(&self, word: &str) -> Option<&Vec<String>> {
+        self.user_words.get(&word.to_uppercase())
+    }
+
+    pub fn cmudict_word(&self, word: &str) -> Option<&Vec<String>> {
+        self.cmudict.get(&word.to_uppercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cmudict() {
+        let content = ";;; comment\n\
+            HELLO  HH AH0 L OW1\n\
+            TOMATO  T AH0 M EY1 T OW2\n\
+            TOMATO(1)  T AH0 M AA1 T OW2\n\
+            lu'an L UW1 AA1 N # 六安\n";
+        let words = parse_cmudict(content, "test.dict").unwrap();
+        assert_eq!(words.len(), 3);
+        assert_eq!(words["HELLO"], vec!["HH", "AH0", "L", "OW1"]);
+        // 多音词取第一个读音
+        assert_eq!(words["TOMATO"], vec!["T", "AH0", "M", "EY1", "T", "OW2"]);
+        assert_eq!(words["LU'AN"], vec!["L", "UW1", "AA1", "N"]);
+
+        // 不在 ARPAbet 音素表中的音素报错并带上行号
+        let err = parse_cmudict("HELLO  HH AH0 L OW9\n", "test.dict").unwrap_err();
+        assert_eq!(err, "test.dict:1 HELLO: unknown ARPAbet phone OW9");
+        assert!(parse_cmudict("HELLO\n", "test.dict").is_err());
+    }
+
+    #[test]
+    fn test_load() {
+        let lexicon = EnglishLexicon::load(&EnglishLexiconConfig::default()).unwrap();
+        assert_eq!(lexicon.len(), 4);
+        assert_eq!(lexicon.cmudict_len(), 0);
+        assert_eq!(
+            lexicon.user_word("huawei").unwrap(),
+            &vec!["HH", "W", "AA1", "W", "EY2"]
+        );
+        assert!(lexicon.user_word("hello").is_none());
+
+        let config: EnglishLexiconConfig =
+            serde_json::from_str(r#"{"lookup_order": ["cmudict", "eng_dict"]}"#).unwrap();
+        assert_eq!(
+            config.lookup_order,
+            vec![LexiconSource::Cmudict, LexiconSource::EngDict]
+        );
+        assert_eq!(
+            config.user_lexicon.as_deref(),
+            Some("../assets/en_user_lexicon.json")
+        );
+    }
+}
@@ -0,0 +1,615 @@
+use english_numbers::Formatting;
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+
+lazy_static! {
+    static ref RE_COMMA_NUMBER: Regex = Regex::new(r"([0-9][0-9\,]+[0-9])").unwrap();
+    static ref RE_DECIMAL_NUMBER: Regex = Regex::new(r"([0-9]+\.[0-9]+)").unwrap();
+    static ref RE_POUNDS: Regex = Regex::new(r"£([0-9\.\,]*[0-9]+)").unwrap();
+    static ref RE_DOLLARS: Regex = Regex::new(r"\$([0-9\.\,]*[0-9]+)").unwrap();
+    static ref RE_EUROS: Regex = Regex::new(r"€\s?([0-9\.\,]*[0-9]+)").unwrap();
+    static ref RE_YEN: Regex = Regex::new(r"[¥￥]\s?([0-9\.\,]*[0-9]+)").unwrap();
+    static ref RE_ORDINAL: Regex = Regex::new(r"([0-9]+)(st|nd|rd|th)\b").unwrap();
+    static ref RE_NUMBER: Regex = Regex::new(r"[0-9]+").unwrap();
+
+    // 称谓和常见缩写：Dr. Smith、St. Louis、Main St.、etc.
+    static ref RE_ABBREVIATION: Regex = Regex::new(
+        r"\b(Mrs|Mr|Ms|Dr|Prof|St|Jr|Sr|Mt|Ave|Rd|Blvd|Capt|Gen|Lt|Col|Sgt|Inc|Ltd|Co|vs|etc|approx|No)\.(\s*)(\S?)"
+    )
+    .unwrap();
+    static ref RE_EG: Regex = Regex::new(r"\b([eE])\.g\.|\b([iI])\.e\.").unwrap();
+
+    // 电话号码：(123) 456-7890、123-456-7890、+1 123 456 7890
+    static ref RE_PHONE: Regex =
+        Regex::new(r"(?:\+1[\s.-]?)?(?:\((\d{3})\)\s?|\b(\d{3})[\s.-])(\d{3})[\s.-](\d{4})\b").unwrap();
+
+    // 日期：2025-01-18、Jan 18, 2025、January 18th、18 Jan 2025
+    static ref RE_ISO_DATE: Regex =
+        Regex::new(r"\b(\d{4})-(1[0-2]|0[1-9])-(3[01]|[12]\d|0[1-9])\b").unwrap();
+    static ref RE_MONTH_DAY: Regex = Regex::new(&format!(
+        r"\b({})\.?\s+(3[01]|[12]\d|0?[1-9])(?:st|nd|rd|th)?\b(?:,?\s+(\d{{4}})\b)?",
+        MONTH_PATTERN
+    ))
+    .unwrap();
+    static ref RE_DAY_MONTH: Regex = Regex::new(&format!(
+        r"\b(3[01]|[12]\d|0?[1-9])(?:st|nd|rd|th)?\s+({})\.?(?:,?\s+(\d{{4}})\b)?",
+        MONTH_PATTERN
+    ))
+    .unwrap();
+
+    // 时刻：3:45 pm、6:00 AM、3pm、14:30
+    static ref RE_TIME_12H: Regex =
+        Regex::new(r"\b(1[0-2]|0?\d)(?::([0-5]\d))?\s?([AaPp])\.?\s?[Mm]\b\.?").unwrap();
+    static ref RE_TIME: Regex = Regex::new(r"\b([01]?\d|2[0-3]):([0-5]\d)\b").unwrap();
+
+    // 年份需有上下文：in 1998、since 2005；年代：1990s、the 80s
+    static ref RE_YEAR: Regex = Regex::new(
+        r"\b(in|In|since|Since|by|until|from|From|during|before|after|of|year|circa)\s+(1[1-9]\d{2}|20\d{2})\b"
+    )
+    .unwrap();
+    static ref RE_DECADE: Regex = Regex::new(r"\b(1[1-9]|20)?([1-9]|0)0s\b").unwrap();
+
+    // 温度、百分比和单位，单位需紧跟数字
+    static ref RE_TEMPERATURE: Regex =
+        Regex::new(r"(\d+(?:\.\d+)?)\s?(°C|℃|°F|℉)").unwrap();
+    static ref RE_PERCENT: Regex = Regex::new(r"(\d+(?:\.\d+)?)\s?%").unwrap();
+    static ref RE_UNIT: Regex = {
+        let mut keys: Vec<&str> = UNITS.iter().map(|(k, _, _)| *k).collect();
+        keys.sort_by_key(|k| std::cmp::Reverse(k.len()));
+        let alternation = keys.iter().map(|k| regex::escape(k)).collect::<Vec<_>>().join("|");
+        Regex::new(&format!(r"(\d+(?:\.\d+)?)\s?({})\b", alternation)).unwrap()
+    };
+
+    // 范围和负数：10-20、-5
+    static ref RE_RANGE: Regex =
+        Regex::new(r"(^|[^\w.-])(\d+(?:\.\d+)?)\s?[-–~]\s?(\d+(?:\.\d+)?)\b").unwrap();
+    static ref RE_NEGATIVE: Regex = Regex::new(r"(^|[\s(\[])-(\d)").unwrap();
+}
+
+const MONTH_PATTERN: &str = r"Jan(?:uary)?|Feb(?:ruary)?|Mar(?:ch)?|Apr(?:il)?|May|June?|July?|Aug(?:ust)?|Sept?(?:ember)?|Oct(?:ober)?|Nov(?:ember)?|Dec(?:ember)?";
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// 单位：缩写、单数、复数
+#[rustfmt::skip]
+const UNITS: &[(&str, &str, &str)] = &[
+    ("km", "kilometer", "kilometers"), ("m", "meter", "meters"), ("cm", "centimeter", "centimeters"),
+    ("mm", "millimeter", "millimeters"), ("mi", "mile", "miles"), ("ft", "foot", "feet"),
+    ("kg", "kilogram", "kilograms"), ("g", "gram", "grams"), ("mg", "milligram", "milligrams"),
+    ("lb", "pound", "pounds"), ("lbs", "pound", "pounds"), ("oz", "ounce", "ounces"),
+    ("L", "liter", "liters"), ("ml", "milliliter", "milliliters"), ("mL", "milliliter", "milliliters"),
+    ("mph", "mile per hour", "miles per hour"), ("km/h", "kilometer per hour", "kilometers per hour"),
+    ("kph", "kilometer per hour", "kilometers per hour"),
+    ("h", "hour", "hours"), ("hr", "hour", "hours"), ("hrs", "hour", "hours"),
+    ("min", "minute", "minutes"), ("s", "second", "seconds"), ("sec", "second", "seconds"),
+    ("ms", "millisecond", "milliseconds"),
+    ("KB", "kilobyte", "kilobytes"), ("MB", "megabyte", "megabytes"), ("GB", "gigabyte", "gigabytes"),
+    ("TB", "terabyte", "terabytes"), ("Mbps", "megabit per second", "megabits per second"),
+    ("Hz", "hertz", "hertz"), ("kHz", "kilohertz", "kilohertz"), ("MHz", "megahertz", "megahertz"),
+    ("GHz", "gigahertz", "gigahertz"), ("W", "watt", "watts"), ("kW", "kilowatt", "kilowatts"),
+    ("kWh", "kilowatt hour", "kilowatt hours"), ("V", "volt", "volts"),
+    ("mAh", "milliamp hour", "milliamp hours"),
+];
+
+fn formatting() -> Formatting {
+    Formatting {
+        spaces: true,
+        conjunctions: true,
+        ..Default::default()
+    }
+}
+
+fn cardinal(number: i64) -> String {
+    english_numbers::convert(number, formatting())
+}
+
+/// 序数词：23 -> twenty third
+fn ordinal(number: i64) -> String {
+    let words = cardinal(number);
+    let (head, last) = match words.rsplit_once(' ') {
+        Some((head, last)) => (format!("{} ", head), last.to_string()),
+        None => (String::new(), words),
+    };
+    let last = match last.as_str() {
+        "one" => "first".to_string(),
+        "two" => "second".to_string(),
+        "three" => "third".to_string(),
+        "five" => "fifth".to_string(),
+        "eight" => "eighth".to_string(),
+        "nine" => "ninth".to_string(),
+        "twelve" => "twelfth".to_string(),
+        w if w.ends_with('y') => format!("{}ieth", &w[..w.len() - 1]),
+        w => format!("{}th", w),
+    };
+    head + &last
+}
+
+/// 年份按两位一读：1998 -> nineteen ninety eight，2005 -> two thousand five，1905 -> nineteen oh five
+fn year(number: i64) -> String {
+    match (number / 100, number % 100) {
+        (20, 0..=9) => cardinal(number),
+        (century, 0) => format!("{} hundred", cardinal(century)),
+        (century, rest @ 1..=9) => format!("{} oh {}", cardinal(century), cardinal(rest)),
+        (century, rest) => format!("{} {}", cardinal(century), cardinal(rest)),
+    }
+}
+
+fn month_name(text: &str) -> &'static str {
+    let prefix = text.get(..3).unwrap_or(text).to_lowercase();
+    MONTHS
+        .iter()
+        .find(|m| m[..3].to_lowercase() == prefix)
+        .copied()
+        .unwrap_or("January")
+}
+
+fn digits(text: &str) -> String {
+    text.chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|d| match d {
+            0 => "oh".to_string(),
+            d => cardinal(d as i64),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 金额：整数部分 + 两位小数的分，数字留给最后的数字规则
+fn money(amount: &str, unit: (&str, &str), cent: (&str, &str)) -> String {
+    let parts: Vec<&str> = amount.split('.').collect();
+    let whole = parts
+        .first()
+        .and_then(|&d| d.replace(',', "").parse::<i64>().ok())
+        .unwrap_or(0);
+    let cents = parts
+        .get(1)
+        .map(|&c| c.trim_end_matches('0'))
+        .filter(|c| !c.is_empty())
+        .map(|c| {
+            c[..c.len().min(2)].parse::<i64>().unwrap_or(0) * 10_i64.pow(2 - c.len().min(2) as u32)
+        })
+        .unwrap_or(0);
+    let plural =
+        |n: i64, (one, many): (&str, &str)| format!("{} {}", n, if n == 1 { one } else { many });
+
+    match (whole, cents) {
+        (0, 0) => format!("zero {}", unit.1),
+        (d, 0) => plural(d, unit),
+        (0, c) => plural(c, cent),
+        (d, c) => format!("{}, {}", plural(d, unit), plural(c, cent)),
+    }
+}
+
+/// 英文文本规整：缩写、电话、日期、时刻、金额、单位、年份、范围和负数，最后把数字读成单词
+pub fn normalize(text: &str) -> String {
+    let funcs: Vec<fn(&str) -> String> = vec![
+        replace_comma_number,
+        replace_abbreviation,
+        replace_phone,
+        replace_date,
+        replace_time,
+        replace_currency,
+        replace_year,
+        replace_unit,
+        replace_range,
+        replace_negative,
+        replace_number,
+    ];
+
+    funcs
+        .iter()
+        .fold(text.to_string(), |result, func| func(&result))
+}
+
+fn replace_comma_number(text: &str) -> String {
+    RE_COMMA_NUMBER
+        .replace_all(text, |caps: &Captures| caps[1].replace(",", ""))
+        .to_string()
+}
+
+fn replace_abbreviation(text: &str) -> String {
+    let text = RE_ABBREVIATION
+        .replace_all(text, |caps: &Captures| {
+            let next = &caps[3];
+            let next_upper = next.chars().next().is_some_and(char::is_uppercase);
+            let word = match &caps[1] {
+                "Mr" => "Mister",
+                "Mrs" => "Misses",
+                "Ms" => "Miss",
+                "Dr" => "Doctor",
+                "Prof" => "Professor",
+                // St. Louis、Main St.
+                "St" if next_upper => "Saint",
+                "St" => "Street",
+                "Jr" => "Junior",
+                "Sr" => "Senior",
+                "Mt" => "Mount",
+                "Ave" => "Avenue",
+                "Rd" => "Road",
+                "Blvd" => "Boulevard",
+                "Capt" => "Captain",
+                "Gen" => "General",
+                "Lt" => "Lieutenant",
+                "Col" => "Colonel",
+                "Sgt" => "Sergeant",
+                "Inc" => "Incorporated",
+                "Ltd" => "Limited",
+                "Co" => "Company",
+                "vs" => "versus",
+                "etc" => "et cetera",
+                "approx" => "approximately",
+                // No. 5 是编号，No. 单独出现是否定
+                "No" if next.chars().next().is_some_and(|c| c.is_ascii_digit()) => "number",
+                _ => return caps[0].to_string(),
+            };
+            format!("{}{}{}", word, &caps[2], next)
+        })
+        .to_string();
+    RE_EG
+        .replace_all(&text, |caps: &Captures| {
+            if caps.get(1).is_some() {
+                "for example"
+            } else {
+                "that is"
+            }
+        })
+        .to_string()
+}
+
+fn replace_phone(text: &str) -> String {
+    RE_PHONE
+        .replace_all(text, |caps: &Captures| {
+            let area = caps.get(1).or(caps.get(2)).map_or("", |m| m.as_str());
+            format!(
+                "{}, {}, {}",
+                digits(area),
+                digits(&caps[3]),
+                digits(&caps[4])
+            )
+        })
+        .to_string()
+}
+
+fn replace_date(text: &str) -> String {
+    let text = RE_ISO_DATE
+        .replace_all(text, |caps: &Captures| {
+            let month = caps[2].parse::<usize>().unwrap_or(1);
+            format!(
+                "{} {}, {}",
+                MONTHS[month - 1],
+                ordinal(caps[3].parse().unwrap_or(1)),
+                year(caps[1].parse().unwrap_or(0))
+            )
+        })
+        .to_string();
+    let with_year = |caps: &Captures, i: usize| {
+        caps.get(i).map_or(String::new(), |m| {
+            format!(", {}", year(m.as_str().parse().unwrap_or(0)))
+        })
+    };
+    let text = RE_MONTH_DAY
+        .replace_all(&text, |caps: &Captures| {
+            format!(
+                "{} {}{}",
+                month_name(&caps[1]),
+                ordinal(caps[2].parse().unwrap_or(1)),
+                with_year(caps, 3)
+            )
+        })
+        .to_string();
+    RE_DAY_MONTH
+        .replace_all(&text, |caps: &Captures| {
+            format!(
+                "the {} of {}{}",
+                ordinal(caps[1].parse().unwrap_or(1)),
+                month_name(&caps[2]),
+                with_year(caps, 3)
+            )
+        })
+        .to_string()
+}
+
+/// 时、分：3 05 -> three oh five，6 00 -> six o'clock
+fn clock(hour: &str, minute: Option<&str>, o_clock: bool) -> String {
+    let hour = cardinal(hour.parse().unwrap_or(0));
+    match minute.and_then(|m| m.parse::<i64>().ok()) {
+        None | Some(0) if o_clock => format!("{} o'clock", hour),
+        None | Some(0) => hour,
+        Some(m @ 1..=9) => format!("{} oh {}", hour, cardinal(m)),
+        Some(m) => format!("{} {}", hour, cardinal(m)),
+    }
+}
+
+fn replace_time(text: &str) -> String {
+    let text = RE_TIME_12H
+        .replace_all(text, |caps: &Captures| {
+            // AM、PM 由 g2p 按缩写逐个字母读
+            let period = if caps[3].eq_ignore_ascii_case("a") {
+                "AM"
+            } else {
+                "PM"
+            };
+            format!(
+                "{} {}",
+                clock(&caps[1], caps.get(2).map(|m| m.as_str()), false),
+                period
+            )
+        })
+        .to_string();
+    RE_TIME
+        .replace_all(&text, |caps: &Captures| {
+            clock(&caps[1], Some(&caps[2]), true)
+        })
+        .to_string()
+}
+
+fn replace_currency(text: &str) -> String {
+    let text = RE_POUNDS
+        .replace_all(text, |caps: &Captures| {
+            money(&caps[1], ("pound", "pounds"), ("penny", "pence"))
+        })
+        .to_string();
+    let text = RE_DOLLARS
+        .replace_all(&text, |caps: &Captures| {
+            money(&caps[1], ("dollar", "dollars"), ("cent", "cents"))
+        })
+        .to_string();
+    let text = RE_EUROS
+        .replace_all(&text, |caps: &Captures| {
+            money(&caps[1], ("euro", "euros"), ("cent", "cents"))
+        })
+        .to_string();
+    RE_YEN
+        .replace_all(&text, |caps: &Captures| {
+            money(&caps[1], ("yen", "yen"), ("sen", "sen"))
+        })
+        .to_string()
+}
+
+fn replace_unit(text: &str) -> String {
+    let text = RE_TEMPERATURE
+        .replace_all(text, |caps: &Captures| {
+            let scale = match &caps[2] {
+                "°F" | "℉" => "Fahrenheit",
+                _ => "Celsius",
+            };
+            let degree = if &caps[1] == "1" { "degree" } else { "degrees" };
+            format!("{} {} {}", &caps[1], degree, scale)
+        })
+        .to_string();
+    let text = RE_PERCENT
+        .replace_all(&text, |caps: &Captures| format!("{} percent", &caps[1]))
+        .to_string();
+    RE_UNIT
+        .replace_all(&text, |caps: &Captures| {
+            let (_, one, many) = UNITS.iter().find(|(k, _, _)| *k == &caps[2]).unwrap();
+            let unit = if &caps[1] == "1" { one } else { many };
+            format!("{} {}", &caps[1], unit)
+        })
+        .to_string()
+}
+
+fn replace_year(text: &str) -> String {
+    let text = RE_YEAR
+        .replace_all(text, |caps: &Captures| {
+            format!("{} {}", &caps[1], year(caps[2].parse().unwrap_or(0)))
+        })
+        .to_string();
+    RE_DECADE
+        .replace_all(&text, |caps: &Captures| {
+            // 1990s -> nineteen nineties，2000s -> two thousands
+            let decade = caps[2].parse::<i64>().unwrap_or(0) * 10;
+            let decade_words = match decade {
+                0 => "thousands".to_string(),
+                d => {
+                    let words = cardinal(d);
+                    format!("{}ies", &words[..words.len() - 1])
+                }
+            };
+            match caps.get(1) {
+                Some(century) => format!(
+                    "{} {}",
+                    cardinal(century.as_str().parse().unwrap_or(0)),
+                    decade_words
+                ),
+                None => decade_words,
+            }
+        })
+        .to_string()
+}
+
+fn replace_range(text: &str) -> String {
+    RE_RANGE
+        .replace_all(text, |caps: &Captures| {
+            format!("{}{} to {}", &caps[1], &caps[2], &caps[3])
+        })
+        .to_string()
+}
+
+fn replace_negative(text: &str) -> String {
+    RE_NEGATIVE
+        .replace_all(text, |caps: &Captures| {
+            format!("{}minus {}", &caps[1], &caps[2])
+        })
+        .to_string()
+}
+
+fn replace_number(text: &str) -> String {
+    let text = RE_DECIMAL_NUMBER
+        .replace_all(text, |caps: &Captures| caps[1].replace(".", " point "))
+        .to_string();
+    let text = RE_ORDINAL
+        .replace_all(&text, |caps: &Captures| {
+            ordinal(caps[1].trim().parse::<i64>().unwrap_or(0))
+        })
+        .to_string();
+    RE_NUMBER
+        .replace_all(&text, |caps: &Captures| {
+            let number = caps
+                .get(0)
+                .and_then(|m| m.as_str().trim().parse::<i64>().ok())
+                .unwrap_or(0);
+
+            match number {
+                2000 => "two thousand".to_string(),
+                2001..=2009 => format!("two thousand {}", cardinal(number % 100)),
+                2010..=2999 if number % 100 == 0 => {
+                    format!("{} hundred", cardinal(number / 100))
+                }
+                1000..=2999 => cardinal(number).replace(", ", " "),
+                _ => cardinal(number),
+            }
+        })
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(cases: Vec<(&str, &str)>) {
+        for (input, expected) in cases {
+            assert_eq!(normalize(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_ordinal_and_year() {
+        assert_eq!(ordinal(1), "first");
+        assert_eq!(ordinal(23), "twenty third");
+        assert_eq!(ordinal(20), "twentieth");
+        assert_eq!(ordinal(12), "twelfth");
+        assert_eq!(year(1998), "nineteen ninety eight");
+        assert_eq!(year(2025), "twenty twenty five");
+        assert_eq!(year(2005), "two thousand five");
+        assert_eq!(year(1905), "nineteen oh five");
+        assert_eq!(year(1900), "nineteen hundred");
+    }
+
+    #[test]
+    fn test_abbreviation() {
+        check(vec![
+            ("Dr. Smith", "Doctor Smith"),
+            ("Mr. and Mrs. Lee", "Mister and Misses Lee"),
+            ("St. Louis", "Saint Louis"),
+            ("Main St. is closed", "Main Street is closed"),
+            ("apples, pears, etc.", "apples, pears, et cetera"),
+            ("cats vs. dogs", "cats versus dogs"),
+            ("e.g. this", "for example this"),
+            ("No. 5", "number five"),
+            ("No. thanks", "No. thanks"),
+        ]);
+    }
+
+    #[test]
+    fn test_phone() {
+        check(vec![
+            (
+                "(123) 456-7890",
+                "one two three, four five six, seven eight nine oh",
+            ),
+            (
+                "call 800-555-0100",
+                "call eight oh oh, five five five, oh one oh oh",
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_date() {
+        check(vec![
+            ("2025-01-18", "January eighteenth, twenty twenty five"),
+            ("Jan 18, 2025", "January eighteenth, twenty twenty five"),
+            (
+                "January 15th, 2025",
+                "January fifteenth, twenty twenty five",
+            ),
+            ("Sept. 3", "September third"),
+            (
+                "18 Jan 2025",
+                "the eighteenth of January, twenty twenty five",
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_time() {
+        check(vec![
+            ("3:45 pm", "three forty five PM"),
+            ("6:00 AM", "six AM"),
+            ("at 3pm", "at three PM"),
+            ("10:05 a.m.", "ten oh five AM"),
+            ("14:30", "fourteen thirty"),
+            ("9:00", "nine o'clock"),
+        ]);
+    }
+
+    #[test]
+    fn test_currency() {
+        check(vec![
+            ("€5", "five euros"),
+            ("€2.50", "two euros, fifty cents"),
+            ("¥300", "three hundred yen"),
+            ("¥1,000", "one thousand yen"),
+            ("$1", "one dollar"),
+            ("£1", "one pound"),
+            ("£9.99", "nine pounds, ninety nine pence"),
+            ("£0.01", "one penny"),
+        ]);
+    }
+
+    #[test]
+    fn test_unit() {
+        check(vec![
+            ("5 km", "five kilometers"),
+            ("1 km", "one kilometer"),
+            ("70kg", "seventy kilograms"),
+            ("60 mph", "sixty miles per hour"),
+            ("98.6°F", "ninety eight point six degrees Fahrenheit"),
+            ("-10°C", "minus ten degrees Celsius"),
+            ("50%", "fifty percent"),
+            ("16GB", "sixteen gigabytes"),
+            // 前面没有数字的不是单位
+            ("I am here", "I am here"),
+            ("5 kilograms", "five kilograms"),
+        ]);
+    }
+
+    #[test]
+    fn test_year() {
+        check(vec![
+            ("in 1998", "in nineteen ninety eight"),
+            ("since 2005", "since two thousand five"),
+            ("the 1990s", "the nineteen nineties"),
+            ("the 80s", "the eighties"),
+            (
+                "1998 people",
+                "one thousand nine hundred and ninety eight people",
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_range_and_negative() {
+        check(vec![
+            ("10-20 people", "ten to twenty people"),
+            ("pages 5–7", "pages five to seven"),
+            ("-5", "minus five"),
+            ("from -3 to 4", "from minus three to four"),
+            ("COVID-19", "COVID-nineteen"),
+        ]);
+    }
+}
@@ -1,36 +1,48 @@
+use super::en_lexicon::{EnglishLexicon, EnglishLexiconConfig, LexiconSource};
 use super::symbols::SYMBOLS;
-use english_numbers::Formatting;
+use super::{en_acronym, en_normalization};
+#[cfg(feature = "g2p-model")]
 use grapheme_to_phoneme::Model;
 use lazy_static::lazy_static;
-use regex::{Captures, Regex};
+use log::{info, warn};
+use regex::Regex;
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::sync::RwLock;
 
 lazy_static! {
-    static ref RE_COMMA_NUMBER: Regex = Regex::new(r"([0-9][0-9\,]+[0-9])").unwrap();
-    static ref RE_DECIMAL_NUMBER: Regex = Regex::new(r"([0-9]+\.[0-9]+)").unwrap();
-    static ref RE_POUNDS: Regex = Regex::new(r"£([0-9\.\,]*[0-9]+)").unwrap();
-    static ref RE_DOLLARS: Regex = Regex::new(r"\$([0-9\.\,]*[0-9]+)").unwrap();
-    static ref RE_ORDINAL: Regex = Regex::new(r"([0-9]+)(st|nd|rd|th)").unwrap();
-    static ref RE_NUMBER: Regex = Regex::new(r"[0-9]+").unwrap();
     static ref RE_DELIMITER: Regex = Regex::new(r"([,，；;.。？！\-\?\!\s+])").unwrap();
 }
 
 pub struct English {
     eng_dict: HashMap<String, Vec<Vec<String>>>,
     rep_map: HashMap<String, String>,
+    #[cfg(feature = "g2p-model")]
     pho_model: Model,
+    lexicon: RwLock<EnglishLexicon>,
 }
 
 impl English {
     pub fn new(eng_dict_json_path: &str, ph_model_path: &str) -> Result<Self, String> {
-        let eng_dict: HashMap<String, Vec<Vec<String>>> =
+        // eng_dict.json 可以不部署，改用 CMUdict 和用户词典
+        let eng_dict: HashMap<String, Vec<Vec<String>>> = if Path::new(eng_dict_json_path).exists()
+        {
             serde_json::from_reader(fs::File::open(eng_dict_json_path).map_err(|e| e.to_string())?)
-                .map_err(|e| e.to_string())?;
-
+                .map_err(|e| e.to_string())?
+        } else {
+            warn!(
+                "{} not found, english words rely on the lexicons",
+                eng_dict_json_path
+            );
+            HashMap::new()
+        };
+
+        #[cfg(feature = "g2p-model")]
         let pho_model =
             Model::load_from_npz_file(Path::new(ph_model_path)).map_err(|e| e.to_string())?;
+        #[cfg(not(feature = "g2p-model"))]
+        let _ = ph_model_path;
 
         let rep_map: HashMap<String, String> = [(";", ","), (":", ","), ("'", "-"), ("\"", "-")]
             .iter()
@@ -40,98 +52,41 @@ impl English {
         Ok(Self {
             eng_dict,
             rep_map,
+            #[cfg(feature = "g2p-model")]
             pho_model,
+            lexicon: RwLock::new(EnglishLexicon::default()),
         })
     }
 
-    pub fn text_normalize(&self, text: &str) -> String {
-        self.normalize_numbers(text)
+    /// 加载 CMUdict 词典和用户词典，整体替换当前词典，返回用户词条数；出错时保留原词典
+    pub fn load_lexicons(&self, config: &EnglishLexiconConfig) -> Result<usize, String> {
+        let lexicon = EnglishLexicon::load(config)?;
+        Ok(self.set_lexicons(lexicon))
     }
 
-    fn normalize_numbers(&self, text: &str) -> String {
-        let text = RE_COMMA_NUMBER
-            .replace_all(text, |caps: &Captures| caps[1].replace(",", ""))
-            .to_string();
-
-        let text = RE_POUNDS
-            .replace_all(&text, |caps: &Captures| format!("{} pounds", &caps[1]))
-            .to_string();
-        let text = RE_DOLLARS
-            .replace_all(&text, |caps: &Captures| {
-                let num = &caps[1];
-                let parts: Vec<&str> = num.split('.').collect();
-                let dollars = parts
-                    .first()
-                    .and_then(|&d| d.parse::<i32>().ok())
-                    .unwrap_or(0);
-                let cents = parts
-                    .get(1)
-                    .map(|&c| c.trim_end_matches('0'))
-                    .filter(|c| !c.is_empty())
-                    .map(|c| {
-                        c[..c.len().min(2)].parse::<i32>().unwrap_or(0)
-                            * 10_i32.pow(2 - c.len().min(2) as u32)
-                    })
-                    .unwrap_or(0);
-
-                match (dollars, cents) {
-                    (0, 0) => "zero dollars".to_string(),
-                    (d, 0) => format!("{} dollar{}", d, if d == 1 { "" } else { "s" }),
-                    (0, c) => format!("{} cent{}", c, if c == 1 { "" } else { "s" }),
-                    (d, c) => format!(
-                        "{} dollar{}, {} cent{}",
-                        d,
-                        if d == 1 { "" } else { "s" },
-                        c,
-                        if c == 1 { "" } else { "s" }
-                    ),
-                }
-            })
-            .to_string();
-        let text = RE_DECIMAL_NUMBER
-            .replace_all(&text, |caps: &Captures| caps[1].replace(".", " point "))
-            .to_string();
-        let text = RE_ORDINAL
-            .replace_all(&text, |caps: &Captures| {
-                english_numbers::convert(
-                    caps[1].trim().parse::<i64>().unwrap_or(0),
-                    Formatting {
-                        spaces: true,
-                        conjunctions: true,
-                        ..Default::default()
-                    },
-                )
-            })
-            .to_string();
-        RE_NUMBER
-            .replace_all(&text, |caps: &Captures| {
-                let formatting = Formatting {
-                    spaces: true,
-                    conjunctions: true,
-                    ..Default::default()
-                };
-                let number = caps
-                    .get(0)
-                    .and_then(|m| m.as_str().trim().parse::<i64>().ok())
-                    .unwrap_or(0);
-
-                match number {
-                    2000 => "two thousand".to_string(),
-                    2001..=2009 => format!(
-                        "two thousand {}",
-                        english_numbers::convert(number % 100, formatting)
-                    ),
-                    2010..=2999 if number % 100 == 0 => {
-                        format!(
-                            "{} hundred",
-                            english_numbers::convert(number / 100, formatting)
-                        )
-                    }
-                    1000..=2999 => english_numbers::convert(number, formatting).replace(", ", " "),
-                    _ => english_numbers::convert(number, formatting),
-                }
-            })
-            .to_string()
+    /// 整体替换当前词典，返回用户词条数
+    pub fn set_lexicons(&self, lexicon: EnglishLexicon) -> usize {
+        let len = lexicon.len();
+        info!(
+            "english user lexicon {:?} loaded, {} words, cmudict {:?}, {} words",
+            lexicon.config.user_lexicon,
+            len,
+            lexicon.config.cmudict,
+            lexicon.cmudict_len()
+        );
+        *self.lexicon.write().unwrap() = lexicon;
+        len
+    }
+
+    /// 按当前配置重新读取词典，不替换当前词典
+    pub fn read_lexicons(&self) -> Result<EnglishLexicon, String> {
+        let config = self.lexicon.read().unwrap().config.clone();
+        EnglishLexicon::load(&config)
+    }
+
+    /// 缩写、日期、时刻、金额、单位等规整见 en_normalization
+    pub fn text_normalize(&self, text: &str) -> String {
+        en_normalization::normalize(text)
     }
 
     fn replace_phonemes(&self, phones: Vec<String>) -> Vec<String> {
@@ -147,41 +102,83 @@ impl English {
             .collect()
     }
 
+    #[cfg(test)]
     pub fn g2p(&self, text: &str) -> Vec<String> {
-        let words = self.split_with_delimiter(text);
-        let mut phones = vec![];
+        self.g2p_words(text)
+            .into_iter()
+            .flat_map(|(_, phones)| phones)
+            .collect()
+    }
 
-        for mut w in words {
-            if let Some(phns) = self.eng_dict.get(&w.to_uppercase()) {
-                phones.extend(phns.iter().flat_map(|ph| ph.iter().cloned()));
-            } else if !w.trim().is_empty() {
-                // 去除首尾非字母数字字符，保留单独的符号
-                if w.len() > 1 {
-                    w = w
-                        .trim_start_matches(|c: char| !c.is_alphanumeric())
-                        .trim_end_matches(|c: char| !c.is_alphanumeric())
-                        .to_string();
-                }
-                // 根据清理后的单词生成音素
-                let phone_list = if w.chars().next().map_or(false, char::is_alphanumeric)
-                    && w.chars().next_back().map_or(false, char::is_alphanumeric)
-                {
-                    self.pho_model.predict_phonemes_strs(&w).ok()
-                } else {
-                    Some(vec![w.as_str()])
-                };
-                if let Some(phone_list) = phone_list {
-                    phones.extend(
-                        phone_list
-                            .into_iter()
-                            .filter(|ph| !ph.is_empty())
-                            .map(String::from),
-                    );
-                }
+    /// 按分隔符切分后每段（词、标点或空白）及其音素，各段拼接即缩写去点后的原文，供英文 bert 对齐
+    pub fn g2p_words(&self, text: &str) -> Vec<(String, Vec<String>)> {
+        let words = self.split_with_delimiter(&en_acronym::join_dotted(text));
+        let lexicon = self.lexicon.read().unwrap();
+
+        words
+            .into_iter()
+            .map(|w| {
+                let phones = self.word_phones(&w, &lexicon);
+                (w, self.replace_phonemes(phones))
+            })
+            .collect()
+    }
+
+    /// 按词典配置的顺序查词，都查不到时交给 g2p 模型
+    fn word_phones(&self, w: &str, lexicon: &EnglishLexicon) -> Vec<String> {
+        let mut phones = vec![];
+        let word = w.trim_matches(|c: char| !c.is_alphanumeric());
+        let found = lexicon
+            .config
+            .lookup_order
+            .iter()
+            .find_map(|source| match source {
+                LexiconSource::User => lexicon.user_word(word).cloned(),
+                LexiconSource::Acronym => en_acronym::spell(word, |w| {
+                    lexicon.cmudict_word(w).is_some()
+                        || self.eng_dict.contains_key(&w.to_uppercase())
+                }),
+                LexiconSource::Cmudict => lexicon.cmudict_word(word).cloned(),
+                LexiconSource::EngDict => self
+                    .eng_dict
+                    .get(&w.to_uppercase())
+                    .map(|phns| phns.iter().flat_map(|ph| ph.iter().cloned()).collect()),
+            });
+        if let Some(phns) = found {
+            phones.extend(phns);
+        } else if !w.trim().is_empty() {
+            // 去除首尾非字母数字字符，保留单独的符号
+            let w = if w.len() > 1 { word } else { w };
+            // 根据清理后的单词生成音素
+            let phone_list = if w.chars().next().is_some_and(char::is_alphanumeric)
+                && w.chars().next_back().is_some_and(char::is_alphanumeric)
+            {
+                self.predict_phones(w)
+            } else {
+                Some(vec![w])
+            };
+            if let Some(phone_list) = phone_list {
+                phones.extend(
+                    phone_list
+                        .into_iter()
+                        .filter(|ph| !ph.is_empty())
+                        .map(String::from),
+                );
             }
         }
+        phones
+    }
+
+    /// 未登录词交给 g2p 模型
+    #[cfg(feature = "g2p-model")]
+    fn predict_phones(&self, w: &str) -> Option<Vec<&'static str>> {
+        self.pho_model.predict_phonemes_strs(w).ok()
+    }
 
-        self.replace_phonemes(phones)
+    /// 未启用 g2p 模型时未登录词逐个字母读
+    #[cfg(not(feature = "g2p-model"))]
+    fn predict_phones(&self, w: &str) -> Option<Vec<&'static str>> {
+        Some(en_acronym::spell_letters(w))
     }
 
     fn split_with_delimiter(&self, input: &str) -> Vec<String> {
@@ -236,16 +233,23 @@ mod tests {
         assert_eq!(eng.text_normalize("$1.5"), "one dollar, fifty cents");
         assert_eq!(eng.text_normalize("$2500"), "twenty five hundred dollars");
         assert_eq!(eng.text_normalize("£23"), "twenty three pounds");
-        assert_eq!(eng.text_normalize("£9.99"), "nine point ninety nine pounds");
+        assert_eq!(
+            eng.text_normalize("£9.99"),
+            "nine pounds, ninety nine pence"
+        );
 
         // 序数词
-        assert_eq!(eng.text_normalize("23rd"), "twenty three");
+        assert_eq!(eng.text_normalize("23rd"), "twenty third");
 
         // 温度数
-        assert_eq!(eng.text_normalize("22.5°C"), "twenty two point five°C");
+        assert_eq!(
+            eng.text_normalize("22.5°C"),
+            "twenty two point five degrees Celsius"
+        );
     }
 
     #[test]
+    #[cfg(feature = "g2p-model")]
     fn test_g2p() {
         let eng: English = English::new("../assets/eng_dict.json", "../assets/model.npz").unwrap();
 
@@ -281,6 +285,35 @@ mod tests {
     }
 
     #[test]
+    fn test_acronyms() {
+        let eng = English::new("../assets/eng_dict.json", "../assets/model.npz").unwrap();
+
+        // 全大写、带点的缩写逐个字母读
+        assert_eq!(eng.g2p("IBM"), vec!["AY1", "B", "IY1", "EH1", "M"]);
+        assert_eq!(eng.g2p("U.S."), vec!["Y", "UW1", "EH1", "S"]);
+        assert_eq!(eng.g2p("NASA"), eng.g2p("nasa"));
+
+        // 词典中有的全大写单词按单词读
+        let cmudict = std::env::temp_dir().join("sovits_test_caps.dict");
+        std::fs::write(&cmudict, "EXIT EH1 G Z AH0 T\nSTOP S T AA1 P\n").unwrap();
+        let config = EnglishLexiconConfig {
+            cmudict: vec![cmudict.to_string_lossy().to_string()],
+            ..Default::default()
+        };
+        eng.load_lexicons(&config).unwrap();
+        assert_eq!(eng.g2p("EXIT"), eng.g2p("exit"));
+        assert_eq!(eng.g2p("STOP"), vec!["S", "T", "AA1", "P"]);
+        assert_eq!(eng.g2p("IBM"), vec!["AY1", "B", "IY1", "EH1", "M"]);
+        std::fs::remove_file(cmudict).unwrap();
+
+        // 用户词典优先于缩写拼读
+        assert_eq!(eng.load_lexicons(&EnglishLexiconConfig::default()), Ok(4));
+        assert_eq!(eng.g2p("SQL"), vec!["S", "IY1", "K", "W", "AH0", "L"]);
+        assert_eq!(eng.g2p("huawei"), vec!["HH", "W", "AA1", "W", "EY2"]);
+    }
+
+    #[test]
+    #[cfg(feature = "g2p-model")]
     fn test_g2p_edge_cases() {
         let eng = English::new("../assets/eng_dict.json", "../assets/model.npz").unwrap();
         //数字
@@ -298,6 +331,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "g2p-model")]
     fn test_edge_cases() {
         let eng = English::new("../assets/eng_dict.json", "../assets/model.npz").unwrap();
 
@@ -331,6 +365,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "g2p-model")]
     fn test_samples() {
         let eng = English::new("../assets/eng_dict.json", "../assets/model.npz").unwrap();
 
@@ -0,0 +1,204 @@
+use crate::session_config::SessionConfig;
+use anyhow::Context;
+use log::warn;
+use ndarray::{Array1, Array2, Axis};
+use ort::{inputs, session::Session};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use tokenizers::Tokenizer;
+
+/// bert 的最大输入长度，超过时不走模型
+const MAX_LEN: usize = 512;
+
+/// g2pW 的多音字表，每行：字\t带声调拼音
+///
+/// 标签按拼音排序、字按字排序，与模型训练时的顺序一致
+pub struct PolyphonicVocab {
+    labels: Vec<String>,
+    char_ids: HashMap<char, usize>,
+    /// 字 -> 可选读音在 labels 中的下标
+    char_labels: HashMap<char, Vec<usize>>,
+}
+
+impl PolyphonicVocab {
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut pairs = vec![];
+        for (line_no, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (c, label) = line
+                .split_once('\t')
+                .ok_or_else(|| format!("line {}: expected char<TAB>pinyin", line_no + 1))?;
+            let mut chars = c.chars();
+            let (Some(c), None) = (chars.next(), chars.next()) else {
+                return Err(format!("line {}: {} is not a single char", line_no + 1, c));
+            };
+            pairs.push((c, label.trim().to_string()));
+        }
+        let labels: Vec<String> = pairs
+            .iter()
+            .map(|(_, label)| label.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let char_ids = pairs
+            .iter()
+            .map(|(c, _)| *c)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .enumerate()
+            .map(|(i, c)| (c, i))
+            .collect();
+        let mut char_labels: HashMap<char, Vec<usize>> = HashMap::new();
+        for (c, label) in &pairs {
+            let id = labels.binary_search(label).unwrap();
+            char_labels.entry(*c).or_default().push(id);
+        }
+        Ok(Self {
+            labels,
+            char_ids,
+            char_labels,
+        })
+    }
+
+    pub fn is_polyphonic(&self, c: char) -> bool {
+        self.char_labels.contains_key(&c)
+    }
+
+    /// 只保留该字可选读音的概率，取最大者
+    fn decode(&self, c: char, probs: &[f32]) -> Option<&str> {
+        self.char_labels[&c]
+            .iter()
+            .max_by(|a, b| probs[**a].total_cmp(&probs[**b]))
+            .map(|id| self.labels[*id].as_str())
+    }
+}
+
+/// g2pW 多音字模型，复用 bert 的 tokenizer，只预测多音字表中的字
+pub struct G2pw {
+    session: Session,
+    tokenizer: Arc<Tokenizer>,
+    vocab: PolyphonicVocab,
+}
+
+impl G2pw {
+    pub fn new(
+        model_path: &str,
+        vocab_path: &str,
+        config: &SessionConfig,
+        tokenizer: Arc<Tokenizer>,
+    ) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(vocab_path)
+            .with_context(|| format!("failed to read {}", vocab_path))?;
+        let vocab = PolyphonicVocab::parse(&content)
+            .map_err(anyhow::Error::msg)
+            .with_context(|| format!("invalid polyphonic chars {}", vocab_path))?;
+        let session = config
+            .load("g2pw", model_path)
+            .with_context(|| format!("failed to load {}", model_path))?;
+        Ok(Self {
+            session,
+            tokenizer,
+            vocab,
+        })
+    }
+
+    /// 逐字转为 token id，首尾加 [CLS]、[SEP]
+    fn input_ids(&self, chars: &[char]) -> Vec<i64> {
+        let id = |token: &str| self.tokenizer.token_to_id(token);
+        let unk = id("[UNK]").unwrap_or(0);
+        let mut ids = vec![id("[CLS]").unwrap_or(unk) as i64];
+        ids.extend(
+            chars
+                .iter()
+                .map(|c| id(&c.to_string()).unwrap_or(unk) as i64),
+        );
+        ids.push(id("[SEP]").unwrap_or(unk) as i64);
+        ids
+    }
+
+    /// 预测句中多音字的读音，返回 字的下标 -> 带声调拼音；模型出错时返回空，退回词典
+    pub fn predict(&self, sentence: &str) -> HashMap<usize, String> {
+        let chars: Vec<char> = sentence.chars().collect();
+        let queries: Vec<usize> = (0..chars.len())
+            .filter(|i| self.vocab.is_polyphonic(chars[*i]))
+            .collect();
+        if queries.is_empty() || chars.len() + 2 > MAX_LEN {
+            return HashMap::new();
+        }
+        match self.run(&chars, &queries) {
+            Ok(probs) => queries
+                .iter()
+                .zip(probs.outer_iter())
+                .filter_map(|(i, probs)| {
+                    let probs = probs.to_vec();
+                    self.vocab
+                        .decode(chars[*i], &probs)
+                        .map(|label| (*i, label.to_string()))
+                })
+                .collect(),
+            Err(e) => {
+                warn!("g2pw failed on {}: {:#}", sentence, e);
+                HashMap::new()
+            }
+        }
+    }
+
+    /// 每个多音字一条样本，共享同一句的 input_ids
+    fn run(&self, chars: &[char], queries: &[usize]) -> anyhow::Result<Array2<f32>> {
+        let batch = queries.len();
+        let ids = Array1::from_vec(self.input_ids(chars)).insert_axis(Axis(0));
+        let input_ids = ids
+            .broadcast((batch, ids.len()))
+            .context("broadcast input_ids")?
+            .to_owned();
+        let token_type_ids = Array2::<i64>::zeros(input_ids.raw_dim());
+        let attention_mask = Array2::<i64>::ones(input_ids.raw_dim());
+        let mut phoneme_mask = Array2::<f32>::zeros((batch, self.vocab.labels.len()));
+        for (row, i) in queries.iter().enumerate() {
+            for id in &self.vocab.char_labels[&chars[*i]] {
+                phoneme_mask[[row, *id]] = 1.0;
+            }
+        }
+        let char_ids: Array1<i64> = queries
+            .iter()
+            .map(|i| self.vocab.char_ids[&chars[*i]] as i64)
+            .collect();
+        // input_ids 开头有 [CLS]
+        let position_ids: Array1<i64> = queries.iter().map(|i| *i as i64 + 1).collect();
+
+        let outputs = self.session.run(inputs![
+            "input_ids" => input_ids,
+            "token_type_ids" => token_type_ids,
+            "attention_mask" => attention_mask,
+            "phoneme_mask" => phoneme_mask,
+            "char_ids" => char_ids,
+            "position_ids" => position_ids,
+        ]?)?;
+        let probs = outputs[0].try_extract_tensor::<f32>()?;
+        Ok(probs
+            .view()
+            .to_shape((batch, self.vocab.labels.len()))?
+            .to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_polyphonic_vocab() {
+        let vocab =
+            PolyphonicVocab::parse("行\thang2\n行\txing2\n长\tchang2\n长\tzhang3\n").unwrap();
+        assert_eq!(vocab.labels, vec!["chang2", "hang2", "xing2", "zhang3"]);
+        assert_eq!(vocab.char_ids[&'行'], 0);
+        assert_eq!(vocab.char_ids[&'长'], 1);
+        assert!(vocab.is_polyphonic('行'));
+        assert!(!vocab.is_polyphonic('人'));
+        // 不属于该字的读音即使概率更高也不选
+        assert_eq!(vocab.decode('行', &[0.9, 0.02, 0.05, 0.03]), Some("xing2"));
+        assert!(PolyphonicVocab::parse("行行\thang2").is_err());
+    }
+}
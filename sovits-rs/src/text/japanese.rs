@@ -0,0 +1,433 @@
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::fs;
+
+lazy_static! {
+    static ref RE_NUMBER: Regex =
+        Regex::new(r"([0-9]+)(?:\.([0-9]+))?(番線|号車|時|分|人|円|階|番)?").unwrap();
+    static ref RE_COMMA_NUMBER: Regex = Regex::new(r"([0-9]),([0-9]{3})").unwrap();
+
+    /// 平假名（含拗音）到音素，与 pyopenjtalk 的音素一致
+    static ref KANA_MAP: HashMap<&'static str, &'static str> = HashMap::from([
+        ("あ", "a"), ("い", "i"), ("う", "u"), ("え", "e"), ("お", "o"),
+        ("ぁ", "a"), ("ぃ", "i"), ("ぅ", "u"), ("ぇ", "e"), ("ぉ", "o"),
+        ("か", "k a"), ("き", "k i"), ("く", "k u"), ("け", "k e"), ("こ", "k o"),
+        ("が", "g a"), ("ぎ", "g i"), ("ぐ", "g u"), ("げ", "g e"), ("ご", "g o"),
+        ("さ", "s a"), ("し", "sh i"), ("す", "s u"), ("せ", "s e"), ("そ", "s o"),
+        ("ざ", "z a"), ("じ", "j i"), ("ず", "z u"), ("ぜ", "z e"), ("ぞ", "z o"),
+        ("た", "t a"), ("ち", "ch i"), ("つ", "ts u"), ("て", "t e"), ("と", "t o"),
+        ("だ", "d a"), ("ぢ", "j i"), ("づ", "z u"), ("で", "d e"), ("ど", "d o"),
+        ("な", "n a"), ("に", "n i"), ("ぬ", "n u"), ("ね", "n e"), ("の", "n o"),
+        ("は", "h a"), ("ひ", "h i"), ("ふ", "f u"), ("へ", "h e"), ("ほ", "h o"),
+        ("ば", "b a"), ("び", "b i"), ("ぶ", "b u"), ("べ", "b e"), ("ぼ", "b o"),
+        ("ぱ", "p a"), ("ぴ", "p i"), ("ぷ", "p u"), ("ぺ", "p e"), ("ぽ", "p o"),
+        ("ま", "m a"), ("み", "m i"), ("む", "m u"), ("め", "m e"), ("も", "m o"),
+        ("や", "y a"), ("ゆ", "y u"), ("よ", "y o"),
+        ("ゃ", "y a"), ("ゅ", "y u"), ("ょ", "y o"),
+        ("ら", "r a"), ("り", "r i"), ("る", "r u"), ("れ", "r e"), ("ろ", "r o"),
+        ("わ", "w a"), ("ゐ", "i"), ("ゑ", "e"), ("を", "o"), ("ん", "N"), ("っ", "cl"),
+        ("ゔ", "v u"),
+        ("きゃ", "ky a"), ("きゅ", "ky u"), ("きょ", "ky o"),
+        ("ぎゃ", "gy a"), ("ぎゅ", "gy u"), ("ぎょ", "gy o"),
+        ("しゃ", "sh a"), ("しゅ", "sh u"), ("しょ", "sh o"), ("しぇ", "sh e"),
+        ("じゃ", "j a"), ("じゅ", "j u"), ("じょ", "j o"), ("じぇ", "j e"),
+        ("ちゃ", "ch a"), ("ちゅ", "ch u"), ("ちょ", "ch o"), ("ちぇ", "ch e"),
+        ("ぢゃ", "j a"), ("ぢゅ", "j u"), ("ぢょ", "j o"),
+        ("にゃ", "ny a"), ("にゅ", "ny u"), ("にょ", "ny o"),
+        ("ひゃ", "hy a"), ("ひゅ", "hy u"), ("ひょ", "hy o"),
+        ("びゃ", "by a"), ("びゅ", "by u"), ("びょ", "by o"),
+        ("ぴゃ", "py a"), ("ぴゅ", "py u"), ("ぴょ", "py o"),
+        ("みゃ", "my a"), ("みゅ", "my u"), ("みょ", "my o"),
+        ("りゃ", "ry a"), ("りゅ", "ry u"), ("りょ", "ry o"),
+        ("ふぁ", "f a"), ("ふぃ", "f i"), ("ふぇ", "f e"), ("ふぉ", "f o"),
+        ("てぃ", "t i"), ("でぃ", "d i"), ("とぅ", "t u"), ("どぅ", "d u"),
+        ("でゅ", "dy u"), ("うぃ", "w i"), ("うぇ", "w e"), ("うぉ", "w o"),
+        ("つぁ", "ts a"), ("つぃ", "ts i"), ("つぇ", "ts e"), ("つぉ", "ts o"),
+        ("ゔぁ", "v a"), ("ゔぃ", "v i"), ("ゔぇ", "v e"), ("ゔぉ", "v o"),
+    ]);
+}
+
+const DIGITS: [&str; 10] = [
+    "ぜろ",
+    "いち",
+    "に",
+    "さん",
+    "よん",
+    "ご",
+    "ろく",
+    "なな",
+    "はち",
+    "きゅう",
+];
+
+/// 量词、普通读法和特殊读法，特殊读法只用于整个数字
+type Counter = (&'static str, &'static str, &'static [(u64, &'static str)]);
+
+const COUNTERS: [Counter; 8] = [
+    ("番線", "ばんせん", &[]),
+    ("号車", "ごうしゃ", &[]),
+    ("番", "ばん", &[]),
+    ("円", "えん", &[(4, "よえん")]),
+    ("時", "じ", &[(4, "よじ"), (7, "しちじ"), (9, "くじ")]),
+    (
+        "分",
+        "ふん",
+        &[
+            (1, "いっぷん"),
+            (3, "さんぷん"),
+            (4, "よんぷん"),
+            (6, "ろっぷん"),
+            (8, "はっぷん"),
+            (10, "じゅっぷん"),
+        ],
+    ),
+    ("人", "にん", &[(1, "ひとり"), (2, "ふたり"), (4, "よにん")]),
+    (
+        "階",
+        "かい",
+        &[
+            (1, "いっかい"),
+            (3, "さんがい"),
+            (6, "ろっかい"),
+            (8, "はっかい"),
+            (10, "じゅっかい"),
+        ],
+    ),
+];
+
+const PUNCTUATION: [char; 5] = ['!', '?', '…', ',', '.'];
+
+/// 词典外的常用假名名词，其后的 は、へ 同样是助词
+const KANA_NOUNS: [&str; 14] = [
+    "これ",
+    "それ",
+    "あれ",
+    "どれ",
+    "ここ",
+    "そこ",
+    "あそこ",
+    "どこ",
+    "わたし",
+    "あなた",
+    "みなさま",
+    "みなさん",
+    "きょう",
+    "あした",
+];
+
+/// 名词后的格助词，其后的 は 也是助词，如 では、には、からは
+const CASE_PARTICLES: [&str; 6] = ["で", "に", "と", "から", "まで", "より"];
+
+pub struct Japanese {
+    /// 表记 -> 发音（片假名，长音写作 ー）
+    lexicon: HashMap<String, String>,
+    max_word_len: usize,
+}
+
+/// 片假名转平假名，其他字符不变
+fn to_hiragana(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'ァ'..='ヶ' => char::from_u32(c as u32 - 0x60).unwrap(),
+            _ => c,
+        })
+        .collect()
+}
+
+fn is_hiragana(c: char) -> bool {
+    ('ぁ'..='ゖ').contains(&c)
+}
+
+fn starts_with(chars: &[char], word: &str) -> bool {
+    let len = word.chars().count();
+    chars.len() >= len && chars[..len].iter().copied().eq(word.chars())
+}
+
+/// 全角英数转半角
+fn to_half_width(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '０'..='９' | 'Ａ'..='Ｚ' | 'ａ'..='ｚ' => {
+                char::from_u32(c as u32 - 0xFEE0).unwrap()
+            }
+            '　' => ' ',
+            _ => c,
+        })
+        .collect()
+}
+
+/// 万以下的读法，千、百有连浊和促音
+fn read_below_10000(n: u64) -> String {
+    let mut out = String::new();
+    let (thousand, hundred, ten, one) = (n / 1000, n / 100 % 10, n / 10 % 10, n % 10);
+    out += match thousand {
+        0 => "",
+        1 => "せん",
+        3 => "さんぜん",
+        8 => "はっせん",
+        d => return format!("{}せん{}", DIGITS[d as usize], read_below_10000(n % 1000)),
+    };
+    out += match hundred {
+        0 => "",
+        1 => "ひゃく",
+        3 => "さんびゃく",
+        6 => "ろっぴゃく",
+        8 => "はっぴゃく",
+        d => {
+            return format!(
+                "{}{}ひゃく{}",
+                out,
+                DIGITS[d as usize],
+                read_below_10000(n % 100)
+            )
+        }
+    };
+    if ten > 0 {
+        if ten > 1 {
+            out += DIGITS[ten as usize];
+        }
+        out += "じゅう";
+    }
+    if one > 0 {
+        out += DIGITS[one as usize];
+    }
+    out
+}
+
+/// 整数的读法，支持到京，u64 的最大值不到一万京
+pub fn number_to_kana(n: u64) -> String {
+    if n == 0 {
+        return DIGITS[0].to_string();
+    }
+    let mut out = String::new();
+    let mut rest = n;
+    for (unit, name) in [
+        (1_0000_0000_0000_0000, "けい"),
+        (1_0000_0000_0000, "ちょう"),
+        (1_0000_0000, "おく"),
+        (1_0000, "まん"),
+    ] {
+        if rest >= unit {
+            out += &read_below_10000(rest / unit);
+            out += name;
+            rest %= unit;
+        }
+    }
+    out + &read_below_10000(rest)
+}
+
+impl Japanese {
+    pub fn new(lexicon_path: &str) -> Result<Self, String> {
+        let lexicon: HashMap<String, String> =
+            serde_json::from_reader(fs::File::open(lexicon_path).map_err(|e| e.to_string())?)
+                .map_err(|e| e.to_string())?;
+        let max_word_len = lexicon.keys().map(|w| w.chars().count()).max().unwrap_or(1);
+        Ok(Self {
+            lexicon,
+            max_word_len,
+        })
+    }
+
+    /// 全角转半角，数字和量词转为假名
+    pub fn text_normalize(&self, text: &str) -> String {
+        let text = to_half_width(text)
+            .replace(['・', '『', '』'], ",")
+            .replace('〜', "ー");
+        let text = RE_COMMA_NUMBER.replace_all(&text, "$1$2").to_string();
+        RE_NUMBER
+            .replace_all(&text, |caps: &Captures| {
+                let Ok(n) = caps[1].parse::<u64>() else {
+                    return caps[0].to_string();
+                };
+                let mut out = number_to_kana(n);
+                if let Some(decimal) = caps.get(2) {
+                    out += "てん";
+                    decimal
+                        .as_str()
+                        .bytes()
+                        .for_each(|d| out += DIGITS[(d - b'0') as usize]);
+                }
+                if let Some(counter) = caps.get(3) {
+                    let (_, reading, special) = COUNTERS
+                        .iter()
+                        .find(|(c, _, _)| *c == counter.as_str())
+                        .unwrap();
+                    match special
+                        .iter()
+                        .find(|(k, _)| caps.get(2).is_none() && *k == n)
+                    {
+                        Some((_, special)) => out = special.to_string(),
+                        None => out += reading,
+                    }
+                }
+                out
+            })
+            .to_string()
+    }
+
+    /// 按词典最长匹配把汉字转为假名。只有名词之后的 は、へ 读作助词わ、え，
+    /// 名词为以汉字或片假名结尾的词典词、片假名词和常用假名名词；
+    /// 动词、形容词活用形以平假名结尾，其后的 は 仍按原音读，如 食べはじめる
+    fn to_kana(&self, text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut out = String::new();
+        let mut i = 0;
+        let mut after_noun = false;
+        while i < chars.len() {
+            let matched = (1..=self.max_word_len.min(chars.len() - i))
+                .rev()
+                .find_map(|len| {
+                    let word: String = chars[i..i + len].iter().collect();
+                    self.lexicon.get(&word).map(|kana| (len, kana.as_str()))
+                })
+                .or_else(|| {
+                    KANA_NOUNS
+                        .iter()
+                        .find(|noun| starts_with(&chars[i..], noun))
+                        .map(|noun| (noun.chars().count(), *noun))
+                });
+            if let Some((len, kana)) = matched {
+                out += kana;
+                i += len;
+                after_noun = !is_hiragana(chars[i - 1]) || KANA_NOUNS.contains(&kana);
+                continue;
+            }
+            if after_noun {
+                let case = CASE_PARTICLES
+                    .iter()
+                    .find(|p| {
+                        starts_with(&chars[i..], p)
+                            && chars.get(i + p.chars().count()) == Some(&'は')
+                    })
+                    .copied();
+                match (case, chars[i]) {
+                    (Some(case), _) => {
+                        out += case;
+                        out.push('わ');
+                        i += case.chars().count() + 1;
+                        after_noun = false;
+                        continue;
+                    }
+                    (None, 'は') => out.push('わ'),
+                    (None, 'へ') => out.push('え'),
+                    (None, c) => out.push(c),
+                }
+            } else {
+                out.push(chars[i]);
+            }
+            // 词典外的片假名词、汉字也按名词处理
+            after_noun = !is_hiragana(chars[i]) && chars[i].is_alphabetic();
+            i += 1;
+        }
+        to_hiragana(&out)
+    }
+
+    /// 假名转音素，ー 重复前一个元音，无法转换的字符原样返回，由音素表报告
+    fn kana_to_phones(kana: &str) -> Vec<String> {
+        let chars: Vec<char> = kana.chars().collect();
+        let mut phones: Vec<String> = vec![];
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            if c.is_whitespace() {
+                i += 1;
+                continue;
+            }
+            if c == 'ー' {
+                if let Some(vowel) = phones
+                    .last()
+                    .filter(|p| ["a", "i", "u", "e", "o"].contains(&p.as_str()))
+                {
+                    phones.push(vowel.clone());
+                }
+                i += 1;
+                continue;
+            }
+            if PUNCTUATION.contains(&c) {
+                phones.push(c.to_string());
+                i += 1;
+                continue;
+            }
+            let pair: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+            let (ph, len) = match KANA_MAP.get(pair.as_str()) {
+                Some(ph) if pair.chars().count() == 2 => (ph.to_string(), 2),
+                _ => match KANA_MAP.get(c.to_string().as_str()) {
+                    Some(ph) => (ph.to_string(), 1),
+                    None => (c.to_string(), 1),
+                },
+            };
+            phones.extend(ph.split(' ').map(String::from));
+            i += len;
+        }
+        phones
+    }
+
+    pub fn g2p(&self, text: &str) -> Vec<String> {
+        Self::kana_to_phones(&self.to_kana(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_normalize() {
+        let ja = Japanese::new("../assets/ja_lexicon.json").unwrap();
+        assert_eq!(number_to_kana(0), "ぜろ");
+        assert_eq!(number_to_kana(11), "じゅういち");
+        assert_eq!(number_to_kana(300), "さんびゃく");
+        assert_eq!(number_to_kana(1964), "せんきゅうひゃくろくじゅうよん");
+        assert_eq!(number_to_kana(8000), "はっせん");
+        assert_eq!(number_to_kana(20000), "にまん");
+        // 1844京6744兆737億955万1615
+        assert_eq!(
+            number_to_kana(u64::MAX),
+            "せんはっぴゃくよんじゅうよんけいろくせんななひゃくよんじゅうよんちょう\
+             ななひゃくさんじゅうななおくきゅうひゃくごじゅうごまんせんろっぴゃくじゅうご"
+        );
+        assert_eq!(ja.text_normalize("３番線"), "さんばんせん");
+        assert_eq!(ja.text_normalize("9時10分"), "くじじゅっぷん");
+        assert_eq!(ja.text_normalize("1,500円"), "せんごひゃくえん");
+        assert_eq!(ja.text_normalize("2.5"), "にてんご");
+    }
+
+    #[test]
+    fn test_g2p() {
+        let ja = Japanese::new("../assets/ja_lexicon.json").unwrap();
+        assert_eq!(
+            ja.g2p("東京駅は次です."),
+            vec![
+                "t", "o", "o", "ky", "o", "o", "e", "k", "i", "w", "a", "ts", "u", "g", "i", "d",
+                "e", "s", "u", "."
+            ]
+        );
+        assert_eq!(
+            ja.g2p("チケット,"),
+            vec!["ch", "i", "k", "e", "cl", "t", "o", ","]
+        );
+        assert_eq!(
+            ja.g2p("ありがとう"),
+            vec!["a", "r", "i", "g", "a", "t", "o", "o"]
+        );
+        assert_eq!(
+            ja.g2p("キャンセル"),
+            vec!["ky", "a", "N", "s", "e", "r", "u"]
+        );
+    }
+
+    #[test]
+    fn test_particles() {
+        let ja = Japanese::new("../assets/ja_lexicon.json").unwrap();
+        assert_eq!(ja.to_kana("これは"), "これわ");
+        assert_eq!(ja.to_kana("ホテルへ"), "ほてるえ");
+        assert_eq!(ja.to_kana("東京駅では"), "とーきょーえきでわ");
+        assert_eq!(ja.to_kana("京都へ行きます"), "きょーとえいきます");
+        assert_eq!(ja.to_kana("食べはじめる"), "たべはじめる");
+        assert_eq!(ja.to_kana("はい"), "はい");
+        assert_eq!(ja.to_kana("ははは"), "ははは");
+        assert_eq!(ja.to_kana("新しい電車"), "あたらしいでんしゃ");
+        assert_eq!(ja.to_kana("私は学生です"), "わたしわがくせーです");
+    }
+}
@@ -0,0 +1,453 @@
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+
+lazy_static! {
+    /// 数字和量词，量词决定用固有词还是汉字词读法
+    static ref RE_NUMBER: Regex = Regex::new(
+        r"([0-9]+)(?:\.([0-9]+))?(\s?)(번째|시간|사람|마리|개|명|시|살|잔|대|권|장|호선|번|분|초|원|층|년|월|일|km|kg|%)?"
+    )
+    .unwrap();
+    static ref RE_COMMA_NUMBER: Regex = Regex::new(r"([0-9]),([0-9]{3})").unwrap();
+}
+
+/// 固有词读法的量词
+const NATIVE_COUNTERS: [&str; 11] = [
+    "번째", "시간", "사람", "마리", "개", "명", "시", "살", "잔", "대", "권",
+];
+
+const SINO_DIGITS: [&str; 10] = ["영", "일", "이", "삼", "사", "오", "육", "칠", "팔", "구"];
+const NATIVE_ONES: [&str; 10] = [
+    "", "하나", "둘", "셋", "넷", "다섯", "여섯", "일곱", "여덟", "아홉",
+];
+/// 量词前的固有词冠形形式
+const NATIVE_ONES_PRENOUN: [&str; 10] = [
+    "", "한", "두", "세", "네", "다섯", "여섯", "일곱", "여덟", "아홉",
+];
+const NATIVE_TENS: [&str; 10] = [
+    "", "열", "스물", "서른", "마흔", "쉰", "예순", "일흔", "여든", "아흔",
+];
+
+/// 初声、中声、终声在兼容字母区的写法
+const CHOSEONG: [char; 19] = [
+    'ㄱ', 'ㄲ', 'ㄴ', 'ㄷ', 'ㄸ', 'ㄹ', 'ㅁ', 'ㅂ', 'ㅃ', 'ㅅ', 'ㅆ', 'ㅇ', 'ㅈ', 'ㅉ', 'ㅊ', 'ㅋ',
+    'ㅌ', 'ㅍ', 'ㅎ',
+];
+const JUNGSEONG: [char; 21] = [
+    'ㅏ', 'ㅐ', 'ㅑ', 'ㅒ', 'ㅓ', 'ㅔ', 'ㅕ', 'ㅖ', 'ㅗ', 'ㅘ', 'ㅙ', 'ㅚ', 'ㅛ', 'ㅜ', 'ㅝ', 'ㅞ',
+    'ㅟ', 'ㅠ', 'ㅡ', 'ㅢ', 'ㅣ',
+];
+const JONGSEONG: [Option<char>; 28] = [
+    None,
+    Some('ㄱ'),
+    Some('ㄲ'),
+    Some('ㄳ'),
+    Some('ㄴ'),
+    Some('ㄵ'),
+    Some('ㄶ'),
+    Some('ㄷ'),
+    Some('ㄹ'),
+    Some('ㄺ'),
+    Some('ㄻ'),
+    Some('ㄼ'),
+    Some('ㄽ'),
+    Some('ㄾ'),
+    Some('ㄿ'),
+    Some('ㅀ'),
+    Some('ㅁ'),
+    Some('ㅂ'),
+    Some('ㅄ'),
+    Some('ㅅ'),
+    Some('ㅆ'),
+    Some('ㅇ'),
+    Some('ㅈ'),
+    Some('ㅊ'),
+    Some('ㅋ'),
+    Some('ㅌ'),
+    Some('ㅍ'),
+    Some('ㅎ'),
+];
+
+/// 复合元音拆为 v2 音素表中的单元音，与 GPT-SoVITS korean.py 一致
+fn divide_vowel(vowel: char) -> &'static [char] {
+    match vowel {
+        'ㅑ' => &['ㅣ', 'ㅏ'],
+        'ㅒ' => &['ㅣ', 'ㅐ'],
+        'ㅕ' => &['ㅣ', 'ㅓ'],
+        'ㅖ' => &['ㅣ', 'ㅔ'],
+        'ㅘ' => &['ㅗ', 'ㅏ'],
+        'ㅙ' => &['ㅗ', 'ㅐ'],
+        'ㅚ' => &['ㅗ', 'ㅣ'],
+        'ㅛ' => &['ㅣ', 'ㅗ'],
+        'ㅝ' => &['ㅜ', 'ㅓ'],
+        'ㅞ' => &['ㅜ', 'ㅔ'],
+        'ㅟ' => &['ㅜ', 'ㅣ'],
+        'ㅠ' => &['ㅣ', 'ㅜ'],
+        'ㅢ' => &['ㅡ', 'ㅣ'],
+        'ㅏ' => &['ㅏ'],
+        'ㅐ' => &['ㅐ'],
+        'ㅓ' => &['ㅓ'],
+        'ㅔ' => &['ㅔ'],
+        'ㅗ' => &['ㅗ'],
+        'ㅜ' => &['ㅜ'],
+        'ㅡ' => &['ㅡ'],
+        _ => &['ㅣ'],
+    }
+}
+
+/// 复合终声拆为 (留在本音节的, 连音移到下一音节的)
+fn split_final(final_: char) -> (Option<char>, char) {
+    match final_ {
+        'ㄳ' => (Some('ㄱ'), 'ㅅ'),
+        'ㄵ' => (Some('ㄴ'), 'ㅈ'),
+        'ㄶ' => (Some('ㄴ'), 'ㅎ'),
+        'ㄺ' => (Some('ㄹ'), 'ㄱ'),
+        'ㄻ' => (Some('ㄹ'), 'ㅁ'),
+        'ㄼ' => (Some('ㄹ'), 'ㅂ'),
+        'ㄽ' => (Some('ㄹ'), 'ㅅ'),
+        'ㄾ' => (Some('ㄹ'), 'ㅌ'),
+        'ㄿ' => (Some('ㄹ'), 'ㅍ'),
+        'ㅀ' => (Some('ㄹ'), 'ㅎ'),
+        'ㅄ' => (Some('ㅂ'), 'ㅅ'),
+        c => (None, c),
+    }
+}
+
+/// 终声的代表音
+fn neutralize(final_: char) -> char {
+    match final_ {
+        'ㄱ' | 'ㄲ' | 'ㅋ' | 'ㄳ' | 'ㄺ' => 'ㄱ',
+        'ㄷ' | 'ㅅ' | 'ㅆ' | 'ㅈ' | 'ㅊ' | 'ㅌ' | 'ㅎ' => 'ㄷ',
+        'ㅂ' | 'ㅍ' | 'ㅄ' | 'ㄿ' => 'ㅂ',
+        'ㄵ' | 'ㄶ' => 'ㄴ',
+        'ㄻ' => 'ㅁ',
+        'ㄼ' | 'ㄽ' | 'ㄾ' | 'ㅀ' => 'ㄹ',
+        c => c,
+    }
+}
+
+fn aspirate(c: char) -> Option<char> {
+    match c {
+        'ㄱ' => Some('ㅋ'),
+        'ㄷ' => Some('ㅌ'),
+        'ㅂ' => Some('ㅍ'),
+        'ㅈ' => Some('ㅊ'),
+        _ => None,
+    }
+}
+
+fn tense(c: char) -> char {
+    match c {
+        'ㄱ' => 'ㄲ',
+        'ㄷ' => 'ㄸ',
+        'ㅂ' => 'ㅃ',
+        'ㅅ' => 'ㅆ',
+        'ㅈ' => 'ㅉ',
+        c => c,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Syllable {
+    initial: char,
+    vowel: char,
+    final_: Option<char>,
+}
+
+fn decompose(c: char) -> Option<Syllable> {
+    let idx = (c as u32).checked_sub(0xAC00).filter(|&i| i < 11172)? as usize;
+    Some(Syllable {
+        initial: CHOSEONG[idx / 588],
+        vowel: JUNGSEONG[idx % 588 / 28],
+        final_: JONGSEONG[idx % 28],
+    })
+}
+
+/// 万以下的汉字词读法，十、百、千前的一省略
+fn sino_below_10000(n: u64) -> String {
+    let mut out = String::new();
+    for (unit, name) in [(1000, "천"), (100, "백"), (10, "십")] {
+        let d = n / unit % 10;
+        if d > 1 {
+            out += SINO_DIGITS[d as usize];
+        }
+        if d > 0 {
+            out += name;
+        }
+    }
+    if !n.is_multiple_of(10) {
+        out += SINO_DIGITS[(n % 10) as usize];
+    }
+    out
+}
+
+/// 汉字词数字读法：일, 이, 삼 ... 십, 백, 천, 만, 억, 조
+pub fn sino_number(n: u64) -> String {
+    if n == 0 {
+        return SINO_DIGITS[0].to_string();
+    }
+    let mut out = String::new();
+    let mut rest = n;
+    for (unit, name) in [
+        (1_0000_0000_0000, "조"),
+        (1_0000_0000, "억"),
+        (1_0000, "만"),
+    ] {
+        if rest >= unit {
+            let high = rest / unit;
+            // 10000 读作 만，不读 일만
+            if !(high == 1 && name == "만") {
+                out += &sino_below_10000(high);
+            }
+            out += name;
+            rest %= unit;
+        }
+    }
+    out + &sino_below_10000(rest)
+}
+
+/// 固有词数字读法，只有 1 到 99，prenoun 为量词前的形式（한, 두, 세, 네, 스무）
+pub fn native_number(n: u64, prenoun: bool) -> Option<String> {
+    if n == 0 || n >= 100 {
+        return None;
+    }
+    let (tens, ones) = ((n / 10) as usize, (n % 10) as usize);
+    if prenoun && tens == 2 && ones == 0 {
+        return Some("스무".to_string());
+    }
+    let ones = if prenoun {
+        NATIVE_ONES_PRENOUN[ones]
+    } else {
+        NATIVE_ONES[ones]
+    };
+    Some(format!("{}{}", NATIVE_TENS[tens], ones))
+}
+
+#[derive(Default)]
+pub struct Korean;
+
+impl Korean {
+    /// 数字按量词读作固有词或汉字词，其余交给 g2p
+    pub fn text_normalize(&self, text: &str) -> String {
+        let text = RE_COMMA_NUMBER.replace_all(text, "$1$2").to_string();
+        RE_NUMBER
+            .replace_all(&text, |caps: &Captures| {
+                let Ok(n) = caps[1].parse::<u64>() else {
+                    return caps[0].to_string();
+                };
+                let space = &caps[3];
+                let counter = caps.get(4).map_or("", |m| m.as_str());
+                if caps.get(2).is_none() && NATIVE_COUNTERS.contains(&counter) {
+                    if let Some(native) = native_number(n, true) {
+                        return format!("{}{}{}", native, space, counter);
+                    }
+                }
+                let mut out = match (n, counter) {
+                    (6, "월") => "유".to_string(),
+                    (10, "월") => "시".to_string(),
+                    _ => sino_number(n),
+                };
+                if let Some(decimal) = caps.get(2) {
+                    out += "점";
+                    decimal
+                        .as_str()
+                        .bytes()
+                        .for_each(|d| out += SINO_DIGITS[(d - b'0') as usize]);
+                }
+                let counter = match counter {
+                    "km" => "킬로미터",
+                    "kg" => "킬로그램",
+                    "%" => "퍼센트",
+                    c => c,
+                };
+                format!("{}{}{}", out, space, counter)
+            })
+            .to_string()
+    }
+
+    /// 一个词（不含空格）内按音变规则得到发音：ㅎ 的激音化与脱落、腭化、连音、
+    /// 终声代表音、鼻音化、流音化和紧音化
+    fn pronounce(word: &[Syllable]) -> Vec<Syllable> {
+        let mut s = word.to_vec();
+        for i in 0..s.len().saturating_sub(1) {
+            let Some(final_) = s[i].final_ else {
+                continue;
+            };
+            let (rest, moved) = split_final(final_);
+            let next = s[i + 1].initial;
+
+            // ㅎ 终声：与后面的 ㄱㄷㅈ 合为激音，在 ㅇ 前脱落，在 ㄴ 前读 ㄴ
+            if moved == 'ㅎ' {
+                if let Some(aspirated) = aspirate(next) {
+                    s[i].final_ = rest;
+                    s[i + 1].initial = aspirated;
+                    continue;
+                }
+                if next == 'ㅇ' {
+                    s[i].final_ = rest;
+                    continue;
+                }
+                if next == 'ㄴ' {
+                    s[i].final_ = Some(rest.unwrap_or('ㄴ'));
+                    continue;
+                }
+            }
+            // ㄱㄷㅂㅈ 终声与后面的 ㅎ 合为激音，ㄷ + 히 腭化为 치
+            if next == 'ㅎ' {
+                if let Some(aspirated) = aspirate(neutralize(moved)) {
+                    s[i].final_ = rest;
+                    s[i + 1].initial = match (aspirated, s[i + 1].vowel) {
+                        ('ㅌ', 'ㅣ') => 'ㅊ',
+                        (c, _) => c,
+                    };
+                    continue;
+                }
+            }
+            if next == 'ㅇ' && moved != 'ㅇ' {
+                // 腭化：ㄷ、ㅌ + 이 -> 지、치
+                let moved = match (moved, s[i + 1].vowel) {
+                    ('ㄷ', 'ㅣ') => 'ㅈ',
+                    ('ㅌ', 'ㅣ') => 'ㅊ',
+                    (c, _) => c,
+                };
+                s[i].final_ = rest;
+                s[i + 1].initial = moved;
+            }
+        }
+
+        for syllable in s.iter_mut() {
+            syllable.final_ = syllable.final_.map(neutralize);
+        }
+
+        for i in 0..s.len().saturating_sub(1) {
+            let Some(final_) = s[i].final_ else {
+                continue;
+            };
+            let next = s[i + 1].initial;
+            match (final_, next) {
+                // 流音化
+                ('ㄴ', 'ㄹ') => s[i].final_ = Some('ㄹ'),
+                ('ㄹ', 'ㄴ') => s[i + 1].initial = 'ㄹ',
+                // ㄹ 在 ㄹ 以外的终声后读 ㄴ，随后终声鼻音化
+                (_, 'ㄹ') if final_ != 'ㄹ' => {
+                    s[i + 1].initial = 'ㄴ';
+                    s[i].final_ = Some(match final_ {
+                        'ㄱ' => 'ㅇ',
+                        'ㄷ' => 'ㄴ',
+                        'ㅂ' => 'ㅁ',
+                        c => c,
+                    });
+                }
+                // 鼻音化
+                ('ㄱ', 'ㄴ' | 'ㅁ') => s[i].final_ = Some('ㅇ'),
+                ('ㄷ', 'ㄴ' | 'ㅁ') => s[i].final_ = Some('ㄴ'),
+                ('ㅂ', 'ㄴ' | 'ㅁ') => s[i].final_ = Some('ㅁ'),
+                // 紧音化
+                ('ㄱ' | 'ㄷ' | 'ㅂ', _) => s[i + 1].initial = tense(next),
+                _ => {}
+            }
+        }
+        s
+    }
+
+    /// 韩文音节转为音素，空格为“空”，无法转换的字符原样返回，由音素表报告
+    pub fn g2p(&self, text: &str) -> Vec<String> {
+        let mut phones = vec![];
+        let mut word: Vec<Syllable> = vec![];
+        let flush = |word: &mut Vec<Syllable>, phones: &mut Vec<String>| {
+            for syllable in Self::pronounce(word) {
+                phones.push(syllable.initial.to_string());
+                phones.extend(divide_vowel(syllable.vowel).iter().map(|v| v.to_string()));
+                if let Some(final_) = syllable.final_ {
+                    phones.push(final_.to_string());
+                }
+            }
+            word.clear();
+        };
+        for c in text.chars() {
+            if let Some(syllable) = decompose(c) {
+                word.push(syllable);
+                continue;
+            }
+            flush(&mut word, &mut phones);
+            if c.is_whitespace() {
+                if phones.last().is_some_and(|p| p != "空") {
+                    phones.push("空".to_string());
+                }
+            } else {
+                phones.push(c.to_string());
+            }
+        }
+        flush(&mut word, &mut phones);
+        if phones.last().is_some_and(|p| p == "空") {
+            phones.pop();
+        }
+        phones
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pron(text: &str) -> String {
+        let syllables: Vec<Syllable> = text.chars().filter_map(decompose).collect();
+        Korean::pronounce(&syllables)
+            .into_iter()
+            .map(|s| {
+                let i = CHOSEONG.iter().position(|&c| c == s.initial).unwrap();
+                let v = JUNGSEONG.iter().position(|&c| c == s.vowel).unwrap();
+                let f = JONGSEONG.iter().position(|&c| c == s.final_).unwrap();
+                char::from_u32(0xAC00 + (i * 588 + v * 28 + f) as u32).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pronounce() {
+        // 连音
+        assert_eq!(pron("음악"), "으막");
+        assert_eq!(pron("앉아"), "안자");
+        // 鼻音化
+        assert_eq!(pron("합니다"), "함니다");
+        assert_eq!(pron("국물"), "궁물");
+        assert_eq!(pron("국립"), "궁닙");
+        // 流音化
+        assert_eq!(pron("신라"), "실라");
+        // 紧音化
+        assert_eq!(pron("학교"), "학꾜");
+        assert_eq!(pron("역삼"), "역쌈");
+        // ㅎ
+        assert_eq!(pron("좋아"), "조아");
+        assert_eq!(pron("축하"), "추카");
+        assert_eq!(pron("놓고"), "노코");
+        // 腭化
+        assert_eq!(pron("같이"), "가치");
+    }
+
+    #[test]
+    fn test_text_normalize() {
+        let ko = Korean;
+        assert_eq!(sino_number(10000), "만");
+        assert_eq!(sino_number(2024), "이천이십사");
+        assert_eq!(sino_number(110), "백십");
+        assert_eq!(native_number(21, false).unwrap(), "스물하나");
+        assert_eq!(ko.text_normalize("2호선"), "이호선");
+        assert_eq!(ko.text_normalize("3시 30분"), "세시 삼십분");
+        assert_eq!(ko.text_normalize("20명"), "스무명");
+        assert_eq!(ko.text_normalize("6월 10일"), "유월 십일");
+        assert_eq!(ko.text_normalize("1,500원"), "천오백원");
+    }
+
+    #[test]
+    fn test_g2p() {
+        let ko = Korean;
+        assert_eq!(
+            ko.g2p("문이 닫힙니다."),
+            vec![
+                "ㅁ", "ㅜ", "ㄴ", "ㅣ", "空", "ㄷ", "ㅏ", "ㅊ", "ㅣ", "ㅁ", "ㄴ", "ㅣ", "ㄷ", "ㅏ",
+                "."
+            ]
+        );
+        assert_eq!(ko.g2p("역"), vec!["ㅇ", "ㅣ", "ㅓ", "ㄱ"]);
+    }
+}
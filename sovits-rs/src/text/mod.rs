@@ -1,6 +1,15 @@
-pub mod zh_normalization;
+pub mod cantonese;
 pub mod chinese;
-mod tone_sandhi;
-pub  mod symbols;
+mod en_acronym;
+pub mod en_bert;
+pub mod en_lexicon;
+mod en_normalization;
 pub mod english;
-pub mod lazy_pinyin;
\ No newline at end of file
+pub mod g2pw;
+pub mod japanese;
+pub mod korean;
+pub mod lazy_pinyin;
+pub mod symbols;
+mod tone_sandhi;
+pub mod zh_lexicon;
+pub mod zh_normalization;
@@ -1,3 +1,8 @@
+use anyhow::Context;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+
 pub const SYMBOLS: [&str; 322] = {
     [
         "!", ",", "-", ".", "?", "AA", "AA0", "AA1", "AA2", "AE0", "AE1", "AE2", "AH0", "AH1",
@@ -38,3 +43,85 @@ pub const _ARPA: [&str; 71] = {
         "SH",
     ]
 };
+
+/// v2 在 v1 之后追加的日文声调符号，其后是韩文和粤语音素
+pub const V2_TONE_MARKS: [&str; 2] = ["[", "]"];
+
+/// 模型使用的音素表，音素 id 为表中的下标
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolTable {
+    symbols: Vec<String>,
+    symbol_to_id: HashMap<String, usize>,
+}
+
+impl SymbolTable {
+    pub fn from_symbols(symbols: Vec<String>) -> Self {
+        let symbol_to_id = symbols
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.to_string(), i))
+            .collect();
+        SymbolTable {
+            symbols,
+            symbol_to_id,
+        }
+    }
+
+    pub fn v1() -> Self {
+        Self::from_symbols(SYMBOLS.iter().map(|s| s.to_string()).collect())
+    }
+
+    /// 从 json 数组读取音素表，例如 GPT-SoVITS 中导出的 symbols2.symbols
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let file =
+            File::open(path).with_context(|| format!("open symbol table {}", path.display()))?;
+        let symbols: Vec<String> = serde_json::from_reader(file)
+            .with_context(|| format!("parse symbol table {}", path.display()))?;
+        Ok(Self::from_symbols(symbols))
+    }
+
+    /// v2 音素表以 v1 音素表和日文声调符号开头
+    pub fn is_v2(&self) -> bool {
+        let prefix = SYMBOLS.iter().chain(V2_TONE_MARKS.iter());
+        self.symbols.len() > SYMBOLS.len() + V2_TONE_MARKS.len()
+            && prefix.zip(self.symbols.iter()).all(|(a, b)| a == b)
+    }
+
+    pub fn id(&self, symbol: &str) -> Option<usize> {
+        self.symbol_to_id.get(symbol).cloned()
+    }
+
+    pub fn contains(&self, symbol: &str) -> bool {
+        self.symbol_to_id.contains_key(symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbol_table() {
+        let v1 = SymbolTable::v1();
+        assert_eq!(v1.id("!"), Some(0));
+        assert_eq!(v1.id("UNK"), Some(86));
+        assert_eq!(v1.id("ㄱ"), None);
+        assert!(!v1.is_v2());
+
+        let mut symbols: Vec<String> = SYMBOLS.iter().map(|s| s.to_string()).collect();
+        symbols.extend(["[", "]", "ㄱ", "Yaa1"].iter().map(|s| s.to_string()));
+        let v2 = SymbolTable::from_symbols(symbols);
+        assert!(v2.is_v2());
+        assert_eq!(v2.id("a1"), v1.id("a1"));
+        assert_eq!(v2.id("ㄱ"), Some(324));
+
+        let v2 = SymbolTable::load("../assets/symbols_v2.json").unwrap();
+        assert!(v2.is_v2());
+        assert_eq!(v2.id("a1"), v1.id("a1"));
+        assert_eq!(v2.id("ㄱ"), Some(324));
+        for symbol in ["空", "停", "Ygw", "Yng", "Yaam6", "Yyut6", "You"] {
+            assert!(v2.contains(symbol), "{}", symbol);
+        }
+    }
+}
@@ -120,18 +120,33 @@ impl ToneSandhi {
         })
     }
 
-    pub fn modified_tone(
+    /// 依次应用 bu、yi、neural、three 变调，trace 中记录改变了韵母的规则及改变后的韵母
+    pub fn modified_tone_traced(
         &self,
         word: &str,
         pos: &str,
         finals: Vec<String>,
         jieba_util: &Jieba,
+        trace: &mut Vec<(&'static str, Vec<String>)>,
     ) -> Vec<String> {
+        let mut record = |rule: &'static str, before: Vec<String>, after: &Vec<String>| {
+            if before != *after {
+                trace.push((rule, after.clone()));
+            }
+        };
+        let before = finals.clone();
         let finals = ToneSandhi::bu_sandhi(word, finals);
+        record("bu", before, &finals);
+        let before = finals.clone();
         let finals = self.yi_sandhi(word, finals);
+        record("yi", before, &finals);
+        let before = finals.clone();
         let finals = self.neural_sandhi(word, pos, finals, jieba_util);
-
-        self.three_sandhi(word, finals, jieba_util)
+        record("neural", before, &finals);
+        let before = finals.clone();
+        let finals = self.three_sandhi(word, finals, jieba_util);
+        record("three", before, &finals);
+        finals
     }
 
     fn neural_sandhi(
@@ -604,11 +619,11 @@ impl ToneSandhi {
                 let prev_tone_is_three = prev_finals
                     .last()
                     .and_then(|f| f.chars().last())
-                    .map_or(false, |c| c == '3');
+                    .is_some_and(|c| c == '3');
                 let curr_tone_is_three = curr_finals
                     .first()
                     .and_then(|f| f.chars().last())
-                    .map_or(false, |c| c == '3');
+                    .is_some_and(|c| c == '3');
 
                 if prev_tone_is_three
                     && curr_tone_is_three
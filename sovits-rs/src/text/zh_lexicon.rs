@@ -0,0 +1,253 @@
+use super::lazy_pinyin::style::{get_initials, to_initials_tone3};
+use jieba_rs::Jieba;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 未填写词性时按名词处理，地名、产品名多为名词
+const DEFAULT_POS: &str = "n";
+
+/// 中文用户词典配置，不存在的文件跳过，重新加载时仍按这里的路径读取
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(default)]
+pub struct ChineseLexiconConfig {
+    /// JSON 用户词典 {"六安": {"pinyin": "lu4 an1", "pos": "ns"}}
+    pub user_lexicon: Option<String>,
+    /// jieba 格式的自定义词典
+    pub jieba_dict: Option<String>,
+}
+
+impl Default for ChineseLexiconConfig {
+    fn default() -> Self {
+        Self {
+            user_lexicon: Some("../assets/zh_user_lexicon.json".to_string()),
+            jieba_dict: Some("../assets/zh_jieba_dict.txt".to_string()),
+        }
+    }
+}
+
+/// 用户词典中的一条：带数字声调的拼音，音节以空格分隔，如 "lu4 an1"
+#[derive(Deserialize, Debug, Clone)]
+pub struct UserWord {
+    pub pinyin: String,
+    /// jieba 词性，影响轻声、变调的判断
+    #[serde(default)]
+    pub pos: Option<String>,
+}
+
+/// 用户词典与加入了用户词、自定义词的 jieba，重新加载时整体替换
+pub struct UserLexicon {
+    pub lexicon_path: Option<String>,
+    /// jieba 格式的自定义词典，每行：词 [词频] [词性]
+    pub jieba_dict_path: Option<String>,
+    pub jieba: Jieba,
+    /// 词 -> (声母, 韵母+声调)，与 lazy_pinyin 的 Initials、InitialsTone3 风格一致
+    words: HashMap<String, (Vec<String>, Vec<String>)>,
+    max_word_len: usize,
+}
+
+/// 单个音节转为声母和带声调的韵母：yi1 -> ("", "i1")，lv4 -> ("l", "v4")
+pub(crate) fn split_syllable(syllable: &str) -> Result<(String, String), String> {
+    let final_ = to_initials_tone3(syllable, true, false, true);
+    let tone_ok = final_
+        .chars()
+        .last()
+        .is_some_and(|c| ('1'..='5').contains(&c));
+    if final_.len() < 2 || !tone_ok {
+        return Err(format!("invalid pinyin syllable: {}", syllable));
+    }
+    Ok((get_initials(syllable, true), final_))
+}
+
+/// 读取 jieba 自定义词典，# 开头为注释；不写词频时按能切出该词的词频加入
+fn load_jieba_dict(jieba: &mut Jieba, path: &str) -> Result<usize, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let mut count = 0;
+    for (line_no, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let (freq, pos) = match parts[1..] {
+            [] => (None, None),
+            [freq, pos] => (
+                Some(freq.parse::<usize>().map_err(|e| {
+                    format!("{}:{} invalid frequency {}: {}", path, line_no + 1, freq, e)
+                })?),
+                Some(pos),
+            ),
+            [freq_or_pos] => match freq_or_pos.parse::<usize>() {
+                Ok(freq) => (Some(freq), None),
+                Err(_) => (None, Some(freq_or_pos)),
+            },
+            _ => return Err(format!("{}:{} invalid entry: {}", path, line_no + 1, line)),
+        };
+        jieba.add_word(parts[0], freq, pos);
+        count += 1;
+    }
+    Ok(count)
+}
+
+impl Default for UserLexicon {
+    fn default() -> Self {
+        Self {
+            lexicon_path: None,
+            jieba_dict_path: None,
+            jieba: Jieba::new(),
+            words: HashMap::new(),
+            max_word_len: 0,
+        }
+    }
+}
+
+impl UserLexicon {
+    /// 读取 JSON 用户词典 {"六安": {"pinyin": "lu4 an1", "pos": "ns"}} 和 jieba 自定义词典，
+    /// 两者都可以不配置，任何一条有误都不加载
+    pub fn load(lexicon_path: Option<&str>, jieba_dict_path: Option<&str>) -> Result<Self, String> {
+        let mut jieba = Jieba::new();
+        if let Some(path) = jieba_dict_path {
+            load_jieba_dict(&mut jieba, path)?;
+        }
+        let entries: HashMap<String, UserWord> = match lexicon_path {
+            Some(path) => {
+                serde_json::from_reader(std::fs::File::open(path).map_err(|e| e.to_string())?)
+                    .map_err(|e| format!("{}: {}", path, e))?
+            }
+            None => HashMap::new(),
+        };
+        let mut lexicon = Self::from_words(jieba, entries)?;
+        lexicon.lexicon_path = lexicon_path.map(String::from);
+        lexicon.jieba_dict_path = jieba_dict_path.map(String::from);
+        Ok(lexicon)
+    }
+
+    /// 用户词在自定义词典之后加入，词性以用户词典为准
+    pub fn from_words(
+        mut jieba: Jieba,
+        entries: HashMap<String, UserWord>,
+    ) -> Result<Self, String> {
+        let mut words = HashMap::new();
+        for (word, entry) in entries {
+            let syllables = entry
+                .pinyin
+                .split_whitespace()
+                .map(split_syllable)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("{}: {}", word, e))?;
+            if syllables.len() != word.chars().count() {
+                return Err(format!(
+                    "{}: {} syllables for {} characters",
+                    word,
+                    syllables.len(),
+                    word.chars().count()
+                ));
+            }
+            jieba.add_word(
+                &word,
+                None,
+                Some(entry.pos.as_deref().unwrap_or(DEFAULT_POS)),
+            );
+            words.insert(word, syllables.into_iter().unzip());
+        }
+        let max_word_len = words.keys().map(|w| w.chars().count()).max().unwrap_or(0);
+        Ok(Self {
+            lexicon_path: None,
+            jieba_dict_path: None,
+            jieba,
+            words,
+            max_word_len,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// 按最长匹配拆分：用户词给出声母、韵母，其余片段交给 lookup 查内置词典，
+    /// lookup 的第一个参数为片段在 word 中的字下标
+    pub fn initials_and_finals<F>(&self, word: &str, lookup: F) -> (Vec<String>, Vec<String>)
+    where
+        F: Fn(usize, &str) -> (Vec<String>, Vec<String>),
+    {
+        if self.words.is_empty() {
+            return lookup(0, word);
+        }
+        let chars: Vec<char> = word.chars().collect();
+        let mut initials = vec![];
+        let mut finals = vec![];
+        let flush =
+            |from: usize, to: usize, initials: &mut Vec<String>, finals: &mut Vec<String>| {
+                if from < to {
+                    let (i, f) = lookup(from, &chars[from..to].iter().collect::<String>());
+                    initials.extend(i);
+                    finals.extend(f);
+                }
+            };
+        let mut start = 0;
+        let mut i = 0;
+        while i < chars.len() {
+            let matched = (1..=self.max_word_len.min(chars.len() - i))
+                .rev()
+                .find_map(|len| {
+                    let sub: String = chars[i..i + len].iter().collect();
+                    self.words.get(&sub).map(|entry| (len, entry))
+                });
+            match matched {
+                Some((len, (i_list, f_list))) => {
+                    flush(start, i, &mut initials, &mut finals);
+                    initials.extend(i_list.iter().cloned());
+                    finals.extend(f_list.iter().cloned());
+                    i += len;
+                    start = i;
+                }
+                None => i += 1,
+            }
+        }
+        flush(start, chars.len(), &mut initials, &mut finals);
+        (initials, finals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_syllable() {
+        assert_eq!(
+            split_syllable("yi1").unwrap(),
+            ("".to_string(), "i1".to_string())
+        );
+        assert_eq!(
+            split_syllable("zhong4").unwrap(),
+            ("zh".to_string(), "ong4".to_string())
+        );
+        assert_eq!(
+            split_syllable("de").unwrap(),
+            ("d".to_string(), "e5".to_string())
+        );
+        assert!(split_syllable("xx1").is_err());
+    }
+
+    #[test]
+    fn test_load() {
+        let lexicon = UserLexicon::load(
+            Some("../assets/zh_user_lexicon.json"),
+            Some("../assets/zh_jieba_dict.txt"),
+        )
+        .unwrap();
+        assert_eq!(lexicon.len(), 12);
+        assert_eq!(
+            lexicon.jieba.cut("智行港位于无人之境", false),
+            vec!["智行港", "位于", "无人之境"]
+        );
+        let entries = HashMap::from([(
+            "六安".to_string(),
+            UserWord {
+                pinyin: "lu4".to_string(),
+                pos: None,
+            },
+        )]);
+        assert!(UserLexicon::from_words(Jieba::new(), entries).is_err());
+    }
+}
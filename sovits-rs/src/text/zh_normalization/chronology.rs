@@ -6,8 +6,8 @@ lazy_static! {
     // 普通日期 2023年10月25日
     static ref RE_DATE: Regex = Regex::new(r"(\d{4}|\d{2})年((1[0-2]|0?[1-9])月)?(([12]\d|30|31|0?[1-9])([日号]))?").unwrap();
 
-    // 日期后带“日”或“号” 2023-10-25
-    static ref RE_DATE2: Regex = Regex::new(r"(\d{4}|\d{2})[- /.](1[0-2]|0?[1-9])[- /.]([12]\d|30|31|0?[1-9])([日号])?").unwrap();
+    // 日期后带“日”或“号” 2023-10-25，前后分隔符需一致
+    static ref RE_DATE2: Regex = Regex::new(r"(?<![\d/-])(\d{4}|\d{2})([- /.])(1[0-2]|0?[1-9])\2([12]\d|30|31|0?[1-9])([日号])?").unwrap();
 
     // 时间范围，如8:30-12:30
     static ref RE_TIME_RANGE: Regex = Regex::new(r"([01]?\d|2[0-3]):([0-5]\d)(:([0-5]\d))?(~|-)([01]?\d|2[0-3]):([0-5]\d)(:([0-5]\d))?").unwrap();
@@ -15,6 +15,56 @@ lazy_static! {
     // 时刻表达式
     static ref RE_TIME: Regex = Regex::new(r"([01]?\d|2[0-3]):([0-5]\d)(:([0-5]\d))?").unwrap();
 
+    // ISO 时间 2025-01-18T08:30:00、2025-01-18T08:30:00+08:00，时区不读
+    static ref RE_ISO_DATETIME: Regex = Regex::new(
+        r"(\d{4})-(1[0-2]|0[1-9])-(3[01]|[12]\d|0[1-9])T([01]\d|2[0-3]):([0-5]\d)(?::([0-5]\d)(?:\.\d+)?)?(?:Z|[+-]\d{2}:?\d{2})?"
+    )
+    .unwrap();
+
+    // 只有月日 10月1日、10月1-7日
+    static ref RE_MONTH_DAY: Regex = Regex::new(
+        r"(?<!\d)(1[0-2]|0?[1-9])月([12]\d|3[01]|0?[1-9])(?:[-~～]([12]\d|3[01]|0?[1-9]))?([日号])"
+    )
+    .unwrap();
+
+    // 日期范围的后半段 10月1日-7日、10月1日-11月7日，需在年月日转换之后、月日转换之前
+    static ref RE_DAY_RANGE: Regex = Regex::new(
+        r"([日号])[-~～](?:(1[0-2]|0?[1-9])月)?([12]\d|3[01]|0?[1-9])([日号])"
+    )
+    .unwrap();
+
+    // 斜杠分隔的月日 1/18，与分数相同，需结合上下文判断
+    static ref RE_MONTH_DAY_SLASH: Regex =
+        Regex::new(r"(?<![\d/.])(1[0-2]|0?[1-9])/([12]\d|3[01]|0?[1-9])(?:[-~～至到](1[0-2]|0?[1-9])/([12]\d|3[01]|0?[1-9]))?(?![\d/])").unwrap();
+    static ref RE_MONTH_DAY_BEFORE: Regex =
+        Regex::new(r"(日期|今天|明天|后天|昨天|前天|截至|截止|定于|将于)$").unwrap();
+    static ref RE_MONTH_DAY_AFTER: Regex =
+        Regex::new(r"^([日号周]|星期|礼拜|[(（](周|星期)|\d{1,2}:\d{2})").unwrap();
+
+    // 星期 周1、星期7
+    static ref RE_WEEKDAY: Regex = Regex::new(r"(周|星期|礼拜)([1-7])(?!\d)").unwrap();
+
+    // 时长 3h20min、1.5h、1小时30分、45min
+    static ref RE_DURATION: Regex = Regex::new(
+        r"(?<![A-Za-z0-9.])(\d+(?:\.\d+)?)\s?(?:hrs|hr|h|小时)(?:\s?(\d{1,2})\s?(min|m|分钟|分))?(?:\s?(\d{1,2})\s?(s|秒))?(?![A-Za-z])"
+    )
+    .unwrap();
+    static ref RE_DURATION_MINUTE: Regex = Regex::new(
+        r"(?<![A-Za-z0-9.])(\d+)\s?mins?(?:\s?(\d{1,2})\s?s)?(?![A-Za-z])"
+    )
+    .unwrap();
+
+    // 12 小时制 8:30am、8pm
+    static ref RE_TIME_12H: Regex = Regex::new(
+        r"(?<![\d:])(1[0-2]|0?\d)(?::([0-5]\d))?\s?([aApP])\.?[mM]\.?(?![A-Za-z])"
+    )
+    .unwrap();
+
+    // 中文时刻 下午3点、2点半、3点05分
+    static ref RE_CLOCK: Regex = Regex::new(
+        r"(凌晨|早上|上午|中午|下午|傍晚|晚上)?(?<![\d.])([01]?\d|2[0-3])点(半|整|钟|([0-5]?\d)分)?"
+    )
+    .unwrap();
 }
 
 pub struct Chronology {
@@ -26,12 +76,35 @@ impl Chronology {
         Self { num: Num }
     }
 
+    /// ISO 时间、时长、12 小时制在日期之前处理，避免日期和数字规则拆散
     pub fn normalize(&self, chronology_str: &str) -> String {
         let funcs: Vec<fn(&Self, &str) -> String> = vec![
+            Self::normalize_latin,
             Self::replace_date,
             Self::replace_date2,
+            Self::replace_day_range,
+            Self::replace_month_day,
+            Self::replace_month_day_slash,
+            Self::replace_weekday,
             Self::replace_time_range,
             Self::replace_time,
+            Self::replace_clock,
+        ];
+
+        funcs
+            .iter()
+            .fold(chronology_str.to_string(), |result, func| {
+                func(self, &result)
+            })
+    }
+
+    /// 带字母的写法：2025-01-18T08:30、3h20min、8:30am，语种分段前也会调用
+    pub fn normalize_latin(&self, chronology_str: &str) -> String {
+        let funcs: Vec<fn(&Self, &str) -> String> = vec![
+            Self::replace_iso_datetime,
+            Self::replace_duration,
+            Self::replace_duration_minute,
+            Self::replace_time_12h,
         ];
 
         funcs
@@ -50,6 +123,41 @@ impl Chronology {
         result
     }
 
+    /// 2 作钟点、时长时读“两”
+    fn count2str(&self, num_str: &str) -> String {
+        match self.num.num2str(num_str, false).as_str() {
+            "二" => "两".to_string(),
+            result => result.to_string(),
+        }
+    }
+
+    fn date2str(&self, year: &str, month: &str, day: &str) -> String {
+        format!(
+            "{}年{}月{}日",
+            self.num.verbalize_digits(year, false),
+            self.num.verbalize_cardinal(month, false),
+            self.num.verbalize_cardinal(day, false)
+        )
+    }
+
+    /// 钟点：8、30、None -> 八点半
+    fn clock2str(&self, hour: &str, minute: Option<&str>, second: Option<&str>) -> String {
+        // 整分的秒不读：08:30:00 -> 八点半
+        let second = second.filter(|s| !s.chars().all(|c| c == '0'));
+        let mut result = format!("{}点", self.count2str(hour));
+        if let Some(minute) = minute.map(|m| self.time_num2str(m)) {
+            if minute == "三十" {
+                result.push('半');
+            } else if minute != "零" || second.is_some() {
+                result.push_str(&format!("{}分", minute));
+            }
+        }
+        if let Some(second) = second {
+            result.push_str(&format!("{}秒", self.time_num2str(second)));
+        }
+        result
+    }
+
     /// 日期转化
     fn replace_date(&self, date_str: &str) -> String {
         RE_DATE
@@ -85,10 +193,10 @@ impl Chronology {
                     .get(1)
                     .map(|m| format!("{}年", self.num.verbalize_digits(m.as_str(), false)));
                 let month = caps
-                    .get(2)
+                    .get(3)
                     .map(|m| format!("{}月", self.num.verbalize_cardinal(m.as_str(), false)));
-                let day = caps.get(3).map(|m| {
-                    let suffix = caps.get(4).map_or("日", |m| m.as_str());
+                let day = caps.get(4).map(|m| {
+                    let suffix = caps.get(5).map_or("日", |m| m.as_str());
                     format!(
                         "{}{}",
                         self.num.verbalize_cardinal(m.as_str(), false),
@@ -113,51 +221,188 @@ impl Chronology {
         self._replace_time(time_str, &RE_TIME_RANGE)
     }
 
-    fn _replace_time(&self, time_str: &str, regex: &Regex) -> String {
-        regex
+    fn replace_iso_datetime(&self, time_str: &str) -> String {
+        RE_ISO_DATETIME
             .replace_all(time_str, |caps: &Captures| {
-                let hour = caps.get(1).map(|m| self.num.num2str(m.as_str(), false));
-                let minute = caps.get(2).map(|m| self.time_num2str(m.as_str()));
-                let second = caps
-                    .get(4)
-                    .map(|m| format!("{}秒", self.time_num2str(m.as_str())));
-
-                let mut result = hour.map(|h| format!("{}点", h)).unwrap_or_default();
-
-                if let Some(minute) = minute {
-                    if minute == "三十" {
-                        result.push('半');
-                    } else if minute != "零" {
-                        result.push_str(&format!("{}分", minute));
-                    }
+                format!(
+                    "{}{}",
+                    self.date2str(&caps[1], &caps[2], &caps[3]),
+                    self.clock2str(&caps[4], Some(&caps[5]), caps.get(6).map(|m| m.as_str()))
+                )
+            })
+            .to_string()
+    }
+
+    fn replace_month_day(&self, date_str: &str) -> String {
+        RE_MONTH_DAY
+            .replace_all(date_str, |caps: &Captures| {
+                let month = self.num.verbalize_cardinal(&caps[1], false);
+                let day = self.num.verbalize_cardinal(&caps[2], false);
+                match caps.get(3) {
+                    Some(day2) => format!(
+                        "{}月{}至{}{}",
+                        month,
+                        day,
+                        self.num.verbalize_cardinal(day2.as_str(), false),
+                        &caps[4]
+                    ),
+                    None => format!("{}月{}{}", month, day, &caps[4]),
+                }
+            })
+            .to_string()
+    }
+
+    /// 日-7日 -> 日至七日，前半段由其他规则转换
+    fn replace_day_range(&self, date_str: &str) -> String {
+        RE_DAY_RANGE
+            .replace_all(date_str, |caps: &Captures| {
+                let month = caps.get(2).map_or(String::new(), |m| {
+                    format!("{}月", self.num.verbalize_cardinal(m.as_str(), false))
+                });
+                format!(
+                    "{}至{}{}{}",
+                    &caps[1],
+                    month,
+                    self.num.verbalize_cardinal(&caps[3], false),
+                    &caps[4]
+                )
+            })
+            .to_string()
+    }
+
+    /// 1/18 前有“日期”“截至”等，或后接日、星期、时刻、另一个月日时才按日期读，其余留给分数
+    fn replace_month_day_slash(&self, date_str: &str) -> String {
+        RE_MONTH_DAY_SLASH
+            .replace_all(date_str, |caps: &Captures| {
+                let m = caps.get(0).unwrap();
+                let before = &date_str[..m.start()];
+                let after = &date_str[m.end()..];
+                // 1/18-1/20 这样的范围总是日期
+                let is_date = caps.get(3).is_some()
+                    || RE_MONTH_DAY_BEFORE.is_match(before).unwrap_or(false)
+                    || RE_MONTH_DAY_AFTER.is_match(after).unwrap_or(false);
+                if !is_date {
+                    return m.as_str().to_string();
                 }
-                if let Some(second) = second {
-                    result.push_str(&second);
+                let suffix = if after.starts_with(['日', '号']) {
+                    ""
+                } else {
+                    "日"
+                };
+                let month_day = |month: &str, day: &str| {
+                    format!(
+                        "{}月{}",
+                        self.num.verbalize_cardinal(month, false),
+                        self.num.verbalize_cardinal(day, false)
+                    )
+                };
+                match (caps.get(3), caps.get(4)) {
+                    (Some(month2), Some(day2)) => format!(
+                        "{}日至{}{}",
+                        month_day(&caps[1], &caps[2]),
+                        month_day(month2.as_str(), day2.as_str()),
+                        suffix
+                    ),
+                    _ => format!("{}{}", month_day(&caps[1], &caps[2]), suffix),
                 }
+            })
+            .to_string()
+    }
 
+    fn replace_weekday(&self, date_str: &str) -> String {
+        RE_WEEKDAY
+            .replace_all(date_str, |caps: &Captures| {
+                let day = match &caps[2] {
+                    "7" if &caps[1] == "礼拜" => "天".to_string(),
+                    "7" => "日".to_string(),
+                    d => self.num.verbalize_digits(d, false),
+                };
+                format!("{}{}", &caps[1], day)
+            })
+            .to_string()
+    }
+
+    fn replace_duration(&self, time_str: &str) -> String {
+        RE_DURATION
+            .replace_all(time_str, |caps: &Captures| {
+                let mut result = format!("{}小时", self.count2str(&caps[1]));
+                if let Some(minute) = caps.get(2) {
+                    let unit = match &caps[3] {
+                        "分" => "分",
+                        _ => "分钟",
+                    };
+                    result.push_str(&format!("{}{}", self.count2str(minute.as_str()), unit));
+                }
+                if let Some(second) = caps.get(4) {
+                    result.push_str(&format!("{}秒", self.count2str(second.as_str())));
+                }
+                result
+            })
+            .to_string()
+    }
+
+    fn replace_duration_minute(&self, time_str: &str) -> String {
+        RE_DURATION_MINUTE
+            .replace_all(time_str, |caps: &Captures| {
+                let mut result = format!("{}分钟", self.count2str(&caps[1]));
+                if let Some(second) = caps.get(2) {
+                    result.push_str(&format!("{}秒", self.count2str(second.as_str())));
+                }
+                result
+            })
+            .to_string()
+    }
+
+    /// 8:30am -> 上午八点半，12pm -> 中午十二点
+    fn replace_time_12h(&self, time_str: &str) -> String {
+        RE_TIME_12H
+            .replace_all(time_str, |caps: &Captures| {
+                let hour: u32 = caps[1].parse().unwrap_or(0);
+                let am = caps[3].eq_ignore_ascii_case("a");
+                let period = match (am, hour) {
+                    (true, 0..=4) | (true, 12) => "凌晨",
+                    (true, _) => "上午",
+                    (false, 12) => "中午",
+                    (false, 1..=5) => "下午",
+                    (false, _) => "晚上",
+                };
+                format!(
+                    "{}{}",
+                    period,
+                    self.clock2str(&caps[1], caps.get(2).map(|m| m.as_str()), None)
+                )
+            })
+            .to_string()
+    }
+
+    /// 带时段或“半、整、分”时才是钟点，3点也可能是得分，只读数值
+    fn replace_clock(&self, time_str: &str) -> String {
+        RE_CLOCK
+            .replace_all(time_str, |caps: &Captures| {
+                if caps.get(1).is_none() && caps.get(3).is_none() {
+                    return caps[0].to_string();
+                }
+                let period = caps.get(1).map_or("", |m| m.as_str());
+                let hour = format!("{}点", self.count2str(&caps[2]));
+                let rest = match (caps.get(3), caps.get(4)) {
+                    (_, Some(minute)) => format!("{}分", self.time_num2str(minute.as_str())),
+                    (Some(suffix), None) => suffix.as_str().to_string(),
+                    (None, None) => String::new(),
+                };
+                format!("{}{}{}", period, hour, rest)
+            })
+            .to_string()
+    }
+
+    fn _replace_time(&self, time_str: &str, regex: &Regex) -> String {
+        regex
+            .replace_all(time_str, |caps: &Captures| {
+                let text = |i: usize| caps.get(i).map(|m| m.as_str());
+                let mut result = self.clock2str(&caps[1], text(2), text(4));
                 if caps.len() > 5 {
                     result.push('至');
-                    let hour2 = caps.get(6).map(|m| self.num.num2str(m.as_str(), false));
-                    let minute2 = caps.get(7).map(|m| self.time_num2str(m.as_str()));
-                    let second2 = caps
-                        .get(9)
-                        .map(|m| format!("{}秒", self.time_num2str(m.as_str())));
-
-                    if let Some(h2) = hour2 {
-                        result.push_str(&format!("{}点", h2));
-                    }
-                    if let Some(minute2) = minute2 {
-                        if minute2 == "三十" {
-                            result.push('半');
-                        } else if minute2 != "零" {
-                            result.push_str(&format!("{}分", minute2));
-                        }
-                    }
-                    if let Some(second2) = second2 {
-                        result.push_str(&second2);
-                    }
+                    result.push_str(&self.clock2str(&caps[6], text(7), text(9)));
                 }
-
                 result
             })
             .to_string()
@@ -236,6 +481,64 @@ mod tests {
         // 非法时间
         assert_eq!(chronology.replace_time("25:60"), "25:60");
     }
+    #[test]
+    fn test_replace_dates_extended() {
+        let chronology = Chronology::new();
+
+        let cases = vec![
+            ("2025-01-18T08:30:00", "二零二五年一月十八日八点半"),
+            (
+                "2025-01-18T14:05:09+08:00",
+                "二零二五年一月十八日十四点零五分零九秒",
+            ),
+            ("10月1日-7日", "十月一日至七日"),
+            ("10月1-7日", "十月一至七日"),
+            ("10月1日~11月7日", "十月一日至十一月七日"),
+            ("2023年10月1日-7日", "二零二三年十月一日至七日"),
+            ("周1至周5", "周一至周五"),
+            ("星期7", "星期日"),
+            ("礼拜7", "礼拜天"),
+            ("截至1/18", "截至一月十八日"),
+            ("1/18（周六）", "一月十八日（周六）"),
+            ("1/18-1/20", "一月十八日至一月二十日"),
+            ("1/18号", "一月十八号"),
+            // 没有日期上下文时按分数处理
+            ("约占1/3", "约占1/3"),
+            ("减少到1/2", "减少到1/2"),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(chronology.normalize(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_replace_times_extended() {
+        let chronology = Chronology::new();
+
+        let cases = vec![
+            ("3h20min", "三小时二十分钟"),
+            ("2h", "两小时"),
+            ("1.5h", "一点五小时"),
+            ("1小时30分", "一小时三十分"),
+            ("45min", "四十五分钟"),
+            ("8:30am", "上午八点半"),
+            ("12pm", "中午十二点"),
+            ("9:15 PM", "晚上九点十五分"),
+            ("下午3点", "下午三点"),
+            ("2点半", "两点半"),
+            ("凌晨2点05分", "凌晨两点零五分"),
+            ("2:30", "两点半"),
+            // 没有时段的“3点”可能是得分
+            ("得了3点", "得了3点"),
+            ("60km/h", "60km/h"),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(chronology.normalize(input), expected, "{}", input);
+        }
+    }
+
     #[test]
     fn test_time_num2str() {
         let chronology = Chronology::new();
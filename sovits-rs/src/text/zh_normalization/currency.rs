@@ -0,0 +1,169 @@
+use super::num::Num;
+use fancy_regex::{Captures, Regex};
+use lazy_static::lazy_static;
+
+lazy_static! {
+    // 千分位：3,000、1,234,567.89
+    static ref RE_THOUSANDS: Regex = Regex::new(r"(?<![\d.,])\d{1,3}(?:,\d{3})+(?![\d,])").unwrap();
+
+    // 符号或代码在前：¥12.50、US$20、HK$ 100、€5、RMB 3000、¥12.5万
+    static ref RE_CURRENCY_PREFIX: Regex = Regex::new(
+        r"(US\$|HK\$|NT\$|JP¥|RMB|CNY|USD|HKD|EUR|GBP|JPY|[¥￥$€£])\s*(\d+(?:\.\d+)?)(万亿|亿|万|千|百)?(?:元)?"
+    )
+    .unwrap();
+
+    // 代码在后：100USD、20 EUR
+    static ref RE_CURRENCY_SUFFIX: Regex =
+        Regex::new(r"(\d+(?:\.\d+)?)\s*(USD|HKD|EUR|GBP|JPY|RMB|CNY)(?![A-Za-z])").unwrap();
+
+    // 人民币的角、分：12.50元、0.05元
+    static ref RE_YUAN_DECIMAL: Regex = Regex::new(r"(\d+)\.(\d{1,2})元").unwrap();
+}
+
+/// 货币符号、代码对应的读法，人民币按元角分读
+fn currency_name(symbol: &str) -> &'static str {
+    match symbol {
+        "US$" | "USD" | "$" => "美元",
+        "HK$" | "HKD" => "港币",
+        "NT$" => "新台币",
+        "EUR" | "€" => "欧元",
+        "GBP" | "£" => "英镑",
+        "JP¥" | "JPY" => "日元",
+        _ => "元",
+    }
+}
+
+pub struct Currency {
+    num: Num,
+}
+
+impl Currency {
+    pub(crate) fn new() -> Self {
+        Self { num: Num }
+    }
+
+    pub fn normalize(&self, sentence: &str) -> String {
+        let funcs: Vec<fn(&Self, &str) -> String> = vec![
+            Self::replace_prefix,
+            Self::replace_suffix,
+            Self::replace_yuan_decimal,
+        ];
+
+        funcs
+            .iter()
+            .fold(sentence.to_string(), |result, func| func(self, &result))
+    }
+
+    /// 去掉金额中的千分位逗号，需在按逗号分句之前调用
+    pub fn remove_thousands_separator(&self, sentence: &str) -> String {
+        RE_THOUSANDS
+            .replace_all(sentence, |caps: &Captures| caps[0].replace(',', ""))
+            .to_string()
+    }
+
+    /// 人民币金额：12.05 -> 十二元零五分，0.5 -> 五角
+    fn yuan(&self, int: &str, dec: &str) -> String {
+        let int_value = int.trim_start_matches('0');
+        let mut digits = dec.chars().chain("00".chars()).take(2);
+        let (jiao, fen) = (digits.next().unwrap(), digits.next().unwrap());
+        if jiao == '0' && fen == '0' {
+            return format!("{}元", self.num.num2str(int, false));
+        }
+        let mut result = String::new();
+        if !int_value.is_empty() {
+            result.push_str(&self.num.num2str(int, false));
+            result.push('元');
+        }
+        if jiao != '0' {
+            result.push_str(&self.num.verbalize_digits(&jiao.to_string(), false));
+            result.push('角');
+        } else if !result.is_empty() {
+            result.push('零');
+        }
+        if fen != '0' {
+            result.push_str(&self.num.verbalize_digits(&fen.to_string(), false));
+            result.push('分');
+        }
+        result
+    }
+
+    fn amount(&self, value: &str, magnitude: &str, name: &str) -> String {
+        match value.split_once('.') {
+            Some((int, dec)) if name == "元" && magnitude.is_empty() && dec.len() <= 2 => {
+                self.yuan(int, dec)
+            }
+            _ => format!("{}{}{}", self.num.num2str(value, false), magnitude, name),
+        }
+    }
+
+    fn replace_prefix(&self, sentence: &str) -> String {
+        RE_CURRENCY_PREFIX
+            .replace_all(sentence, |caps: &Captures| {
+                let magnitude = caps.get(3).map_or("", |m| m.as_str());
+                self.amount(&caps[2], magnitude, currency_name(&caps[1]))
+            })
+            .to_string()
+    }
+
+    fn replace_suffix(&self, sentence: &str) -> String {
+        RE_CURRENCY_SUFFIX
+            .replace_all(sentence, |caps: &Captures| {
+                self.amount(&caps[1], "", currency_name(&caps[2]))
+            })
+            .to_string()
+    }
+
+    fn replace_yuan_decimal(&self, sentence: &str) -> String {
+        RE_YUAN_DECIMAL
+            .replace_all(sentence, |caps: &Captures| self.yuan(&caps[1], &caps[2]))
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_currency_normalize() {
+        let currency = Currency::new();
+
+        let cases = vec![
+            ("¥12.50", "十二元五角"),
+            ("￥3000", "三千元"),
+            ("￥0.05", "五分"),
+            ("票价¥12.05元", "票价十二元零五分"),
+            ("US$20", "二十美元"),
+            ("$19.99", "十九点九九美元"),
+            ("€5", "五欧元"),
+            ("HK$ 100", "一百港币"),
+            ("£2.5", "二点五英镑"),
+            ("RMB 200", "二百元"),
+            ("¥12.5万", "十二点五万元"),
+            ("100USD", "一百美元"),
+            ("20 EUR", "二十欧元"),
+            ("找零8.5元", "找零八元五角"),
+            ("没有金额", "没有金额"),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(currency.normalize(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_remove_thousands_separator() {
+        let currency = Currency::new();
+
+        let cases = vec![
+            ("￥3,000", "￥3000"),
+            ("1,234,567.89美元", "1234567.89美元"),
+            ("1,2,3", "1,2,3"),
+            ("12,34", "12,34"),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(currency.remove_thousands_separator(input), expected);
+        }
+    }
+}
@@ -1,6 +1,9 @@
 mod chronology;
+mod currency;
 mod num;
 pub mod opencpop_strict;
 mod phonecode;
 mod quantifier;
 pub mod text_normalization;
+pub(crate) mod transport;
+mod web;
@@ -13,6 +13,22 @@ lazy_static! {
     static ref RE_NUMBER: Regex = Regex::new(r"(-?)((\d+)(\.\d+)?)|(\.(\d+))").unwrap();
     static ref RE_INTEGER: Regex = Regex::new(r"(-)(\d+)").unwrap();
     static ref RE_DEFAULT_NUM: Regex = Regex::new(r"\d{7}\d*").unwrap();
+    // 编号类上下文逐位读：房间号是404、工号：1024
+    static ref RE_DIGITS_AFTER_KEYWORD: Regex = Regex::new(
+        r"(房间号?|房号|门牌号?|号码|编号|车次|工号|学号|账号|单号|订单号|验证码|尾号|邮编|分机号?|座位号?|航班号?|代码|密码)(是|为|：|:)?(\d+)(?![\d.])"
+    )
+    .unwrap();
+    // 后接室、房、号、车次的三位以上数字：404室、1024号、1461次列车；“第100号”是序数
+    static ref RE_DIGITS_BEFORE_SUFFIX: Regex = Regex::new(
+        r"(?<![\d.第])(\d{3,})(?=室|房|号(?!线)|次(?:列车|动车|高铁))"
+    )
+    .unwrap();
+    // 年份：2024款、2019级、2023赛季
+    static ref RE_YEAR_BEFORE_SUFFIX: Regex =
+        Regex::new(r"(?<![\d.])((?:19|20)\d{2})(?=款|届|级|赛季|年度|财年|版)").unwrap();
+    // 指定读法：<digits>2024</digits>、<cardinal>404</cardinal>
+    static ref RE_SAY_AS: Regex =
+        Regex::new(r"<(digits|cardinal)>\s*(\d+(?:\.\d+)?)\s*</\1>").unwrap();
     static ref RE_POSITIVE_QUANTIFIERS: Regex = Regex::new(
         r"(\d+)([多余几\+])?(封|艘|把|目|套|段|人|所|朵|匹|张|座|回|场|尾|条|个|首|阙|阵|网|炮|顶|丘|棵|只|支|袭|辆|挑|担|颗|\
         壳|窠|曲|墙|群|腔|砣|座|客|贯|扎|捆|刀|令|打|手|罗|坡|山|岭|江|溪|钟|队|单|双|对|出|口|头|脚|\
@@ -40,6 +56,7 @@ pub struct Num;
 impl Num {
     pub fn normalize(&self, num_str: &str) -> String {
         let funcs: Vec<fn(&Self, &str) -> String> = vec![
+            Self::replace_digit_context,
             Self::replace_scientific,
             Self::replace_frac,
             Self::replace_percentage,
@@ -110,6 +127,33 @@ impl Num {
             .replace('一', if alt_one { "幺" } else { "一" })
     }
 
+    /// 按上下文把编号、年份读成逐位数字，其余留给后面的规则按数值读
+    fn replace_digit_context(&self, value: &str) -> String {
+        let value = self.replace_with_regex(value, &RE_DIGITS_AFTER_KEYWORD, |caps| {
+            let sep = caps.get(2).map_or("", |m| m.as_str());
+            format!(
+                "{}{}{}",
+                &caps[1],
+                sep,
+                self.verbalize_digits(&caps[3], true)
+            )
+        });
+        let value = self.replace_with_regex(&value, &RE_DIGITS_BEFORE_SUFFIX, |caps| {
+            self.verbalize_digits(&caps[1], true)
+        });
+        self.replace_with_regex(&value, &RE_YEAR_BEFORE_SUFFIX, |caps| {
+            self.verbalize_digits(&caps[1], false)
+        })
+    }
+
+    /// 标签指定的读法优先于上下文规则，需在特殊符号替换之前调用
+    pub fn replace_say_as(&self, value: &str) -> String {
+        self.replace_with_regex(value, &RE_SAY_AS, |caps| match &caps[1] {
+            "digits" => self.verbalize_digits(&caps[2], false).replace('.', "点"),
+            _ => self.num2str(&caps[2], false),
+        })
+    }
+
     fn replace_with_regex<F>(&self, value: &str, regex: &Regex, replacer: F) -> String
     where
         F: Fn(&Captures) -> String,
@@ -370,6 +414,45 @@ mod tests {
         assert_eq!(num.verbalize_cardinal("10001", true), "一万零一");
     }
 
+    #[test]
+    fn test_digit_context() {
+        let num = Num;
+
+        let cases = vec![
+            ("房间号是404", "房间号是四零四"),
+            ("工号：1024", "工号：幺零二四"),
+            ("请到301室", "请到三零幺室"),
+            ("1461次列车", "幺四六幺次列车"),
+            ("2024款", "二零二四款"),
+            ("2019级新生", "二零一九级新生"),
+            // 没有编号上下文的按数值读
+            ("共有404人", "共有四百零四人"),
+            ("第100号", "第一百号"),
+            ("跑了100次", "跑了一百次"),
+            ("3号线", "三号线"),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(num.normalize(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_say_as() {
+        let num = Num;
+        assert_eq!(num.replace_say_as("<digits>2024</digits>"), "二零二四");
+        assert_eq!(
+            num.replace_say_as("房间<cardinal>404</cardinal>"),
+            "房间四百零四"
+        );
+        assert_eq!(num.replace_say_as("<digits>3.14</digits>"), "三点一四");
+        // 标签不匹配时保持原样
+        assert_eq!(
+            num.replace_say_as("<digits>404</cardinal>"),
+            "<digits>404</cardinal>"
+        );
+    }
+
     #[test]
     fn test_seq() {
         let num = Num;
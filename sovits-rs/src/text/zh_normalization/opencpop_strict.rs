@@ -432,4 +432,3 @@ pub const OPENCPOP_STRICT: [(&str, &str); 430] = {
         ("zuo", "z uo"),
     ]
 };
-
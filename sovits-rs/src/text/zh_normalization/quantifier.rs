@@ -4,52 +4,99 @@ use lazy_static::lazy_static;
 use std::collections::HashMap;
 
 lazy_static! {
-    // 匹配温度格式：-10°C、20℃、35度、40.5摄氏度
-    static ref RE_TEMPERATURE: Regex = Regex::new(r"(-?)(\d+(\.\d+)?)(°C|℃|度|摄氏度)").unwrap();
-
-    // 单位转换表，按长度优先匹配
-    static ref MEASURE_DICT: HashMap<&'static str, &'static str> = HashMap::from([
-        // 面积和体积相关
-        ("cm2", "平方厘米"), ("cm²", "平方厘米"), ("cm3", "立方厘米"), ("cm³", "立方厘米"),
-        ("cm", "厘米"), ("m2", "平方米"), ("m²", "平方米"), ("m³", "立方米"),
-        ("m3", "立方米"), ("ml", "毫升"), ("m", "米"), ("mm", "毫米"),
-
-        // 质量相关
-        ("kg", "千克"), ("g", "克"),
-
-        // 时间相关
-        ("s", "秒"), ("ds", "毫秒"),
-
-        // 声学相关
-        ("db", "分贝"),
-
-        // 长度相关
-        ("km", "千米"),
-
-        // 速度相关
-        ("m/s", "米每秒"), ("km/s", "千米每秒"), ("km/h", "千米每小时"), ("mm/s", "毫米每秒")
-    ]);
-
-    // 按长度降序排列的单位键，用于匹配替换
-    static ref MEASURE_KEYS: Vec<&'static str> = {
-        let mut keys: Vec<&str> = MEASURE_DICT.keys().cloned().collect();
-        keys.sort_by_key(|b| std::cmp::Reverse(b.len()));
-        keys
-    };
+    // 匹配温度格式：-10°C、20℃、35度、40.5摄氏度、98.6℉
+    static ref RE_TEMPERATURE: Regex =
+        Regex::new(r"(-?)(\d+(\.\d+)?)(°C|℃|°F|℉|度|摄氏度|华氏度)").unwrap();
+
+    // 千分比、百万分比读在数字之前：5‰、300ppm
+    static ref RE_RATIO: Regex = Regex::new(r"(\d+(?:\.\d+)?)\s*(‰|ppm)(?![A-Za-z])").unwrap();
 }
 
+/// 内置单位表，可由 zh_units.json 补充或覆盖
+#[rustfmt::skip]
+const DEFAULT_UNITS: &[(&str, &str)] = &[
+    // 长度、面积、体积
+    ("km", "千米"), ("m", "米"), ("cm", "厘米"), ("mm", "毫米"),
+    ("km2", "平方千米"), ("km²", "平方千米"), ("m2", "平方米"), ("m²", "平方米"),
+    ("cm2", "平方厘米"), ("cm²", "平方厘米"), ("m3", "立方米"), ("m³", "立方米"),
+    ("cm3", "立方厘米"), ("cm³", "立方厘米"), ("L", "升"), ("mL", "毫升"), ("ml", "毫升"),
+    // 质量
+    ("t", "吨"), ("kg", "千克"), ("g", "克"), ("mg", "毫克"),
+    // 时间
+    ("h", "小时"), ("s", "秒"), ("ms", "毫秒"),
+    // 电学
+    ("kWh", "千瓦时"), ("kW", "千瓦"), ("W", "瓦"), ("V", "伏"), ("A", "安"), ("mAh", "毫安时"),
+    // 频率
+    ("Hz", "赫兹"), ("kHz", "千赫兹"), ("MHz", "兆赫兹"), ("GHz", "吉赫兹"),
+    // 存储与带宽
+    ("KB", "千字节"), ("kB", "千字节"), ("MB", "兆字节"), ("GB", "吉字节"), ("TB", "太字节"),
+    ("Mbps", "兆比特每秒"), ("Gbps", "吉比特每秒"),
+    // 声学
+    ("dB", "分贝"), ("db", "分贝"),
+];
+
 pub struct Quantifier {
     num: Num,
+    units: HashMap<String, String>,
+    // 数字后的单位，可带“/单位”：60km/h、8L/100km
+    re_measure: Regex,
+    // 中文后的“/单位”：元/kg
+    re_per_unit: Regex,
 }
 
 impl Quantifier {
     pub(crate) fn new() -> Self {
-        Self { num: Num }
+        Self::with_units(HashMap::new())
+    }
+
+    /// 读取 JSON 单位表 {"kPa": "千帕"}，与内置单位合并
+    pub(crate) fn load(path: &str) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let units: HashMap<String, String> =
+            serde_json::from_str(&content).map_err(|e| format!("{}: {}", path, e))?;
+        Ok(Self::with_units(units))
+    }
+
+    fn with_units(extra: HashMap<String, String>) -> Self {
+        let mut units: HashMap<String, String> = DEFAULT_UNITS
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        units.extend(extra);
+        // 按长度优先匹配：kWh 先于 kW
+        let mut keys: Vec<&String> = units.keys().collect();
+        keys.sort_by_key(|k| std::cmp::Reverse(k.len()));
+        let alternation = keys
+            .iter()
+            .map(|k| fancy_regex::escape(k))
+            .collect::<Vec<_>>()
+            .join("|");
+        // 单位后不能再接字母，避免拆坏英文单词；5A级 是等级
+        let re_measure = Regex::new(&format!(
+            r"(?<![A-Za-z])(\d+(?:\.\d+)?)(\s*)({0})(?:/(\d*)({0}))?(?![A-Za-z级])",
+            alternation
+        ))
+        .unwrap();
+        let re_per_unit = Regex::new(&format!(
+            r"(?<=[\u4e00-\u9fa5])/(\d*)({})(?![A-Za-z])",
+            alternation
+        ))
+        .unwrap();
+        Self {
+            num: Num,
+            units,
+            re_measure,
+            re_per_unit,
+        }
     }
 
     pub fn normalize(&self, sentence: &str) -> String {
-        let funcs: Vec<fn(&Self, &str) -> String> =
-            vec![Self::replace_measure, Self::replace_temperature];
+        let funcs: Vec<fn(&Self, &str) -> String> = vec![
+            Self::replace_measure,
+            Self::replace_per_unit,
+            Self::replace_ratio,
+            Self::replace_temperature,
+        ];
 
         funcs
             .iter()
@@ -67,6 +114,7 @@ impl Quantifier {
 
                 let unit = match caps.get(4).map_or("", |m| m.as_str()) {
                     "摄氏度" => "摄氏度",
+                    "°F" | "℉" | "华氏度" => "华氏度",
                     _ => "度",
                 };
                 format!("{}{}{}", sign, temperature, unit)
@@ -74,20 +122,51 @@ impl Quantifier {
             .to_string()
     }
 
+    fn replace_ratio(&self, ratio_str: &str) -> String {
+        RE_RATIO
+            .replace_all(ratio_str, |caps: &Captures| {
+                let prefix = match &caps[2] {
+                    "‰" => "千分之",
+                    _ => "百万分之",
+                };
+                format!("{}{}", prefix, self.num.num2str(&caps[1], false))
+            })
+            .to_string()
+    }
+
+    /// “每”后的数量：100 -> 百，百公里不读“一百”，120 仍读“一百二十”
+    fn per(&self, count: &str, unit: &str) -> String {
+        let count = match self.num.num2str(count, false).as_str() {
+            c @ ("一十" | "一百" | "一千") => c.trim_start_matches('一').to_string(),
+            c => c.to_string(),
+        };
+        format!("每{}{}", count, self.units[unit])
+    }
+
     pub fn replace_measure(&self, measure_str: &str) -> String {
-        MEASURE_KEYS
-            .iter()
-            .fold(measure_str.to_string(), |result, &key| {
-                let regex = Regex::new(&format!(r"(\d+(\.\d+)?)(\s*){}", key)).unwrap();
-                regex
-                    .replace_all(&result, |caps: &Captures| {
-                        let number_part = caps.get(1).map_or("", |m| m.as_str());
-                        let number_str = self.num.num2str(number_part, false);
-                        let unit_str = MEASURE_DICT.get(key).unwrap_or(&key);
-                        format!("{}{}", number_str, unit_str)
-                    })
-                    .to_string()
+        self.re_measure
+            .replace_all(measure_str, |caps: &Captures| {
+                // 单字母单位（A、V、t、h 等）与数字之间有空格时多是英文正文，不按单位读
+                if !caps[2].is_empty() && caps[3].chars().count() == 1 {
+                    return caps[0].to_string();
+                }
+                // 二千瓦会听成 2000 瓦，单位前的 2 读“两”
+                let number_str = match self.num.num2str(&caps[1], false).as_str() {
+                    "二" => "两".to_string(),
+                    number_str => number_str.to_string(),
+                };
+                let per = caps
+                    .get(5)
+                    .map_or(String::new(), |unit| self.per(&caps[4], unit.as_str()));
+                format!("{}{}{}", number_str, self.units[&caps[3]], per)
             })
+            .to_string()
+    }
+
+    fn replace_per_unit(&self, measure_str: &str) -> String {
+        self.re_per_unit
+            .replace_all(measure_str, |caps: &Captures| self.per(&caps[1], &caps[2]))
+            .to_string()
     }
 }
 
@@ -136,6 +215,54 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_replace_measure_extended() {
+        let quantifier = Quantifier::new();
+
+        let cases = vec![
+            ("耗电10kWh", "耗电十千瓦时"),
+            ("功率2kW", "功率两千瓦"),
+            ("电池5000mAh", "电池五千毫安时"),
+            ("220V 10A", "二百二十伏 十安"),
+            ("2.4GHz和50Hz", "二点四吉赫兹和五十赫兹"),
+            ("内存16GB，硬盘2TB", "内存十六吉字节，硬盘两太字节"),
+            ("带宽100Mbps", "带宽一百兆比特每秒"),
+            ("1.5L水", "一点五升水"),
+            ("延迟20ms", "延迟二十毫秒"),
+            ("载重5t", "载重五吨"),
+            ("面积9600km²", "面积九千六百平方千米"),
+            ("油耗8L/100km", "油耗八升每百千米"),
+            ("12元/kg", "12元每千克"),
+            // 单位后接字母的不是单位
+            ("5 apples", "5 apples"),
+            ("3 mins", "3 mins"),
+            ("5A级景区", "5A级景区"),
+            ("油耗8L/120km", "油耗八升每一百二十千米"),
+            ("流量1GB/1000s", "流量一吉字节每千秒"),
+            // 单字母单位前有空格时不按单位读
+            ("Chapter 3 A quick tour", "Chapter 3 A quick tour"),
+            ("3 V 5 W 2 t", "3 V 5 W 2 t"),
+            ("50 GB", "五十吉字节"),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(quantifier.normalize(input), expected, "{}", input);
+        }
+
+        assert_eq!(quantifier.normalize("98.6℉"), "九十八点六华氏度");
+        assert_eq!(quantifier.normalize("浓度5‰"), "浓度千分之五");
+        assert_eq!(quantifier.normalize("300ppm"), "百万分之三百");
+    }
+
+    #[test]
+    fn test_load_units() {
+        let quantifier = Quantifier::load("../assets/zh_units.json").unwrap();
+        assert_eq!(quantifier.normalize("气压101kPa"), "气压一百零一千帕");
+        // 内置单位仍然可用
+        assert_eq!(quantifier.normalize("70kg"), "七十千克");
+        assert!(Quantifier::load("../assets/missing_units.json").is_err());
+    }
+
     #[test]
     fn test_combined_replace() {
         let quantifier = Quantifier::new();
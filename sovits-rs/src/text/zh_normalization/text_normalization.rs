@@ -1,12 +1,17 @@
 use super::chronology::Chronology;
+use super::currency::Currency;
 use super::num::Num;
 use super::phonecode::Phonecode;
 use super::quantifier::Quantifier;
+use super::transport::Transport;
+use super::web::Web;
 use fnv::FnvHashMap;
 use lazy_static::lazy_static;
+use log::warn;
 use regex::{Captures, Regex};
 use serde::Deserialize;
 use std::collections::HashMap;
+use std::path::Path;
 
 lazy_static! {
     static ref RE_SENTENCE_SPLITOR: Regex = Regex::new(r"([：、，；。？！,;?!][”’]?)").unwrap();
@@ -35,7 +40,10 @@ pub struct TextNormalizer {
     t2s_dict: HashMap<char, char>,
     special_symbol_mapping: HashMap<String, String>,
     chronology: Chronology,
+    currency: Currency,
     quantifier: Quantifier,
+    transport: Transport,
+    web: Web,
     phonecode: Phonecode,
     num: Num,
 }
@@ -67,23 +75,57 @@ impl TextNormalizer {
             t2s_dict,
             special_symbol_mapping: zh_dict.special_symbol_mapping,
             chronology: Chronology::new(),
-            quantifier: Quantifier::new(),
+            currency: Currency::new(),
+            quantifier: Self::load_quantifier(dict_path),
+            transport: Transport::new(),
+            web: Web::new(),
             phonecode: Phonecode::new(),
             num: Num,
         }
     }
 
+    /// 与 zh_dict.json 同目录的 zh_units.json 为自定义单位表，没有时只用内置单位
+    fn load_quantifier(dict_path: &str) -> Quantifier {
+        let units_path = Path::new(dict_path).with_file_name("zh_units.json");
+        if !units_path.exists() {
+            return Quantifier::new();
+        }
+        Quantifier::load(&units_path.to_string_lossy()).unwrap_or_else(|e| {
+            warn!("failed to load units, using built-in units: {}", e);
+            Quantifier::new()
+        })
+    }
+
     pub(crate) fn normalize(&self, sentence: &str) -> Vec<String> {
         let sentences = self.split(sentence, "zh");
         sentences
             .into_iter()
-            .map(|sent| self.normalize_sentence(&sent))
+            .map(|sent| self.normalize_sentence(&sent, "zh"))
+            .collect()
+    }
+
+    /// 粤语：保留繁体字，电话号码中的 1 读作“一”
+    pub(crate) fn normalize_yue(&self, sentence: &str) -> Vec<String> {
+        let sentences = self.split(sentence, "zh");
+        sentences
+            .into_iter()
+            .map(|sent| self.normalize_sentence(&sent, "yue").replace('幺', "一"))
             .collect()
     }
 
+    /// 读法标签、网址、邮箱、IP、版本号以及带字母的时间写法，在语种分段前调用，其余字母留给英文读
+    pub(crate) fn normalize_latin(&self, sentence: &str) -> String {
+        let sentence = self.num.replace_say_as(sentence);
+        self.chronology
+            .normalize_latin(&self.web.normalize(&sentence))
+    }
+
     fn split(&self, sentence: &str, lang: &str) -> Vec<String> {
         let mut sentences: Vec<String> = vec![];
-        let mut text = sentence.to_string();
+        // 千分位的逗号不能当作分句符号
+        let mut text = self.currency.remove_thousands_separator(sentence);
+        // 网址、邮箱中的 . @ / 以及读法标签不能当作分句符号和特殊符号
+        text = self.web.normalize(&self.num.replace_say_as(&text));
         if lang == "zh" {
             text = text.replace(" ", "");
             text = RE_SPECIAL_SYMBOL.replace_all(&text, ",").to_string();
@@ -172,11 +214,17 @@ impl TextNormalizer {
             .collect()
     }
 
-    fn normalize_sentence(&self, sentence: &str) -> String {
+    fn normalize_sentence(&self, sentence: &str, lang: &str) -> String {
         let mut sentence = sentence.to_string();
 
-        sentence = self.tranditional_to_simplified(&sentence);
+        if lang == "zh" {
+            sentence = self.tranditional_to_simplified(&sentence);
+        }
         sentence = self.translate(&sentence);
+        // ¥12.50、US$20、€5、HK$100、12.5万元
+        sentence = self.currency.normalize(&sentence);
+        // 沪A·12345、G1234次、MU5137、K71路
+        sentence = self.transport.normalize(&sentence);
         // 日期2021年5月26日
         // 日期2021/5/26日、21/5/26
         // 8:30-12:30
@@ -229,6 +277,100 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_normalize_currency() {
+        let normalizer = TextNormalizer::new("../assets/zh_dict.json");
+        let cases = vec![
+            ("票价￥3,000。", vec!["票价三千元。"]),
+            ("总投资12.5万元", vec!["总投资十二点五万元"]),
+            ("营收3.8亿美元", vec!["营收三点八亿美元"]),
+            (
+                "单价¥12.50，共US$20",
+                vec!["单价十二元五角，", "共二十美元"],
+            ),
+        ];
+
+        for (input, expected) in cases {
+            let result = normalizer.normalize(input);
+            assert_eq!(result, expected, "Test failed for input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_normalize_transport() {
+        let normalizer = TextNormalizer::new("../assets/zh_dict.json");
+        let cases = vec![
+            ("乘坐G1234次列车", vec!["乘坐吉幺二三四次列车"]),
+            ("车牌京B12345D。", vec!["车牌京比幺二三四五迪。"]),
+            ("换乘71路或3号线", vec!["换乘七十一路或三号线"]),
+        ];
+
+        for (input, expected) in cases {
+            let result = normalizer.normalize(input);
+            assert_eq!(result, expected, "Test failed for input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_normalize_chronology() {
+        let normalizer = TextNormalizer::new("../assets/zh_dict.json");
+        let cases = vec![
+            ("国庆10月1日-7日放假", vec!["国庆十月一日至七日放假"]),
+            (
+                "截至1/18，完成了1/3",
+                vec!["截至一月十八日，", "完成了三分之一"],
+            ),
+            ("热线400-123-4567", vec!["热线四零零，幺二三，四五六七"]),
+        ];
+
+        for (input, expected) in cases {
+            let result = normalizer.normalize(input);
+            assert_eq!(result, expected, "Test failed for input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_normalize_digit_context() {
+        let normalizer = TextNormalizer::new("../assets/zh_dict.json");
+        let cases = vec![
+            ("房间号是 404", vec!["房间号是四零四"]),
+            ("共有<digits>404</digits>人", vec!["共有四零四人"]),
+            ("工号<cardinal>1024</cardinal>", vec!["工号一千零二十四"]),
+        ];
+
+        for (input, expected) in cases {
+            let result = normalizer.normalize(input);
+            assert_eq!(result, expected, "Test failed for input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_normalize_web() {
+        let normalizer = TextNormalizer::new("../assets/zh_dict.json");
+        let cases = vec![
+            ("网关192.168.1.1。", vec!["网关一九二点一六八点一点一。"]),
+            ("请升级到v2.3.1", vec!["请升级到版本二点三点一"]),
+        ];
+
+        for (input, expected) in cases {
+            let result = normalizer.normalize(input);
+            assert_eq!(result, expected, "Test failed for input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_normalize_yue() {
+        let normalizer = TextNormalizer::new("../assets/zh_dict.json");
+        assert_eq!(
+            normalizer.normalize_yue("港鐵熱線：28818888。"),
+            vec!["港鐵熱線：", "二八八一八八八八。"]
+        );
+        assert_eq!(
+            normalizer.normalize_yue("下一班列車8:30開出"),
+            vec!["下一班列車八点半開出"]
+        );
+    }
+
     #[test]
     fn test_text_normalizer_cases() {
         let normalizer = TextNormalizer::new("../assets/zh_dict.json");
@@ -257,7 +399,7 @@ mod tests {
             ),
             (
                 "电话：1234567890，金额：￥100.00。",
-                vec!["电话：", "十二亿三千四百五十六万七千八百九十，", "金额：", "￥一百。"]
+                vec!["电话：", "十二亿三千四百五十六万七千八百九十，", "金额：", "一百元。"]
             ),
             (
                 "混合测试：①A～Z，123；中文。",
@@ -0,0 +1,256 @@
+use super::num::Num;
+use fancy_regex::{Captures, Regex};
+use lazy_static::lazy_static;
+
+lazy_static! {
+    // 车牌：沪A·12345、京B12345D、粤B D12345
+    static ref RE_PLATE: Regex = Regex::new(
+        r"([京津沪渝冀豫云辽黑湘皖鲁新苏浙赣鄂桂甘晋蒙陕吉闽贵粤青藏川宁琼])([A-Z])[·•\s-]?([A-Z0-9]{5,6})(?![A-Za-z0-9])"
+    )
+    .unwrap();
+
+    // 车次：G1234、D301次、K21，纯数字车次需带“次列车”：1461次列车；
+    // 字母车次后面没有“次”时，句中需有列车相关的词
+    static ref RE_TRAIN: Regex =
+        Regex::new(r"(?<![A-Za-z0-9])(?:([GDCZTKLSY])(\d{1,4})|(\d{4})(?=次(?:列车|动车|高铁)))(?![A-Za-z0-9])").unwrap();
+
+    // 航班号：MU5137、CA1234、3U8888、9C8501，句中需有航班相关的词
+    static ref RE_FLIGHT: Regex =
+        Regex::new(r"(?<![A-Za-z0-9])([A-Z]{2}|[A-Z][0-9]|[0-9][A-Z])(\d{3,4})(?![A-Za-z0-9])").unwrap();
+
+    // 线路：K71路、B2路、S1线；字母按字母读，数字按数值读
+    static ref RE_ROUTE: Regex =
+        Regex::new(r"(?<![A-Za-z0-9])([A-Z]{1,2})(\d{1,3})(路|线)").unwrap();
+
+    // 车次、航班号的上下文，避免 G20 峰会、C919、GB2312 之类的写法按编号读
+    static ref RE_TRAIN_CONTEXT: Regex =
+        Regex::new(r"列车|动车|高铁|火车|车次|车站|站台|检票|候车|乘车").unwrap();
+    static ref RE_FLIGHT_CONTEXT: Regex =
+        Regex::new(r"航班|班机|航空|机场|登机|起飞|降落|飞往|航站楼").unwrap();
+}
+
+/// 字母的中文读法，字母会被中文 g2p 去掉，需要先转为汉字
+const LETTER_NAMES: [&str; 26] = [
+    "诶",
+    "比",
+    "西",
+    "迪",
+    "伊",
+    "艾弗",
+    "吉",
+    "艾尺",
+    "艾",
+    "杰",
+    "开",
+    "艾勒",
+    "艾姆",
+    "恩",
+    "欧",
+    "屁",
+    "吉吾",
+    "阿",
+    "艾斯",
+    "提",
+    "优",
+    "维",
+    "达不溜",
+    "艾克斯",
+    "歪",
+    "贼德",
+];
+
+pub struct Transport {
+    num: Num,
+}
+
+/// 编号在句中的结束位置是否有对应的上下文
+type ContextCheck = fn(&str, usize) -> bool;
+
+/// 车次后接“次”或句中有列车相关的词才按编号读
+fn train_in_context(sentence: &str, end: usize) -> bool {
+    sentence[end..].starts_with('次') || RE_TRAIN_CONTEXT.is_match(sentence).unwrap_or(false)
+}
+
+/// 句中有航班相关的词才按航班号读
+fn flight_in_context(sentence: &str, _end: usize) -> bool {
+    RE_FLIGHT_CONTEXT.is_match(sentence).unwrap_or(false)
+}
+
+/// 交通编号在句中的位置，语种分段时整体留在中文里
+pub(crate) fn transport_code_spans(sentence: &str) -> Vec<(usize, usize)> {
+    let always = |_: &str, _: usize| true;
+    let rules: [(&Regex, ContextCheck); 4] = [
+        (&RE_PLATE, always),
+        (&RE_ROUTE, always),
+        (&RE_TRAIN, train_in_context),
+        (&RE_FLIGHT, flight_in_context),
+    ];
+    let mut spans: Vec<(usize, usize)> = vec![];
+    for (regex, in_context) in rules {
+        for m in regex.find_iter(sentence).flatten() {
+            if !in_context(sentence, m.end()) {
+                continue;
+            }
+            if !spans.iter().any(|(s, e)| m.start() < *e && *s < m.end()) {
+                spans.push((m.start(), m.end()));
+            }
+        }
+    }
+    spans.sort();
+    spans
+}
+
+impl Transport {
+    pub(crate) fn new() -> Self {
+        Self { num: Num }
+    }
+
+    pub fn normalize(&self, sentence: &str) -> String {
+        let funcs: Vec<fn(&Self, &str) -> String> = vec![
+            Self::replace_plate,
+            Self::replace_route,
+            Self::replace_train,
+            Self::replace_flight,
+        ];
+
+        funcs
+            .iter()
+            .fold(sentence.to_string(), |result, func| func(self, &result))
+    }
+
+    /// 字母读字母名，数字逐位读，1 读作幺
+    fn spell(&self, code: &str) -> String {
+        code.chars()
+            .map(|c| match c {
+                'A'..='Z' => LETTER_NAMES[(c as u8 - b'A') as usize].to_string(),
+                _ => self.num.verbalize_digits(&c.to_string(), true),
+            })
+            .collect()
+    }
+
+    fn replace_plate(&self, sentence: &str) -> String {
+        RE_PLATE
+            .replace_all(sentence, |caps: &Captures| {
+                format!(
+                    "{}{}{}",
+                    &caps[1],
+                    self.spell(&caps[2]),
+                    self.spell(&caps[3])
+                )
+            })
+            .to_string()
+    }
+
+    fn replace_route(&self, sentence: &str) -> String {
+        RE_ROUTE
+            .replace_all(sentence, |caps: &Captures| {
+                format!(
+                    "{}{}{}",
+                    self.spell(&caps[1]),
+                    self.num.num2str(&caps[2], false),
+                    &caps[3]
+                )
+            })
+            .to_string()
+    }
+
+    /// 上下文已确认的编号直接读出：语种分段后编号单独成段，段里已没有“次”、航班等词
+    pub(crate) fn read_code(&self, code: &str) -> String {
+        let always = |_: &str, _: usize| true;
+        let code = self.replace_route(&self.replace_plate(code));
+        self.spell_flights(&self.spell_trains(&code, always))
+    }
+
+    fn replace_train(&self, sentence: &str) -> String {
+        self.spell_trains(sentence, train_in_context)
+    }
+
+    fn spell_trains(&self, sentence: &str, in_context: ContextCheck) -> String {
+        RE_TRAIN
+            .replace_all(sentence, |caps: &Captures| {
+                let m = caps.get(0).unwrap();
+                if !in_context(sentence, m.end()) {
+                    return m.as_str().to_string();
+                }
+                match caps.get(1) {
+                    Some(letter) => {
+                        format!("{}{}", self.spell(letter.as_str()), self.spell(&caps[2]))
+                    }
+                    None => self.spell(&caps[3]),
+                }
+            })
+            .to_string()
+    }
+
+    fn replace_flight(&self, sentence: &str) -> String {
+        if !flight_in_context(sentence, 0) {
+            return sentence.to_string();
+        }
+        self.spell_flights(sentence)
+    }
+
+    fn spell_flights(&self, sentence: &str) -> String {
+        RE_FLIGHT
+            .replace_all(sentence, |caps: &Captures| {
+                format!("{}{}", self.spell(&caps[1]), self.spell(&caps[2]))
+            })
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transport_normalize() {
+        let transport = Transport::new();
+
+        let cases = vec![
+            ("沪A·12345", "沪诶幺二三四五"),
+            ("京B12345D", "京比幺二三四五迪"),
+            ("乘坐G1234次列车", "乘坐吉幺二三四次列车"),
+            ("1461次列车", "幺四六幺次列车"),
+            ("航班MU5137", "航班艾姆优五幺三七"),
+            ("3U8888航班", "三优八八八八航班"),
+            ("K71路", "开七十一路"),
+            ("S1线", "艾斯一线"),
+            // 不带字母的线路交给 Num 按数值读
+            ("71路和3号线", "71路和3号线"),
+            ("iPhone15", "iPhone15"),
+            ("G7次高铁", "吉七次高铁"),
+            ("检票口在K21车厢旁", "检票口在开二幺车厢旁"),
+            // 没有“次”也没有列车、航班相关的词时不按编号读
+            ("G20峰会", "G20峰会"),
+            ("国产大飞机C919", "国产大飞机C919"),
+            ("编码GB2312", "编码GB2312"),
+            ("型号AB1234", "型号AB1234"),
+            // 纯数字后只有“次”时是次数
+            ("跑了2000次", "跑了2000次"),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(transport.normalize(input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_transport_code_spans() {
+        let text = "乘坐G1234次列车，车牌沪A·12345";
+        let spans: Vec<&str> = transport_code_spans(text)
+            .into_iter()
+            .map(|(s, e)| &text[s..e])
+            .collect();
+        assert_eq!(spans, vec!["G1234", "沪A·12345"]);
+
+        assert!(transport_code_spans("在G20峰会上").is_empty());
+        assert!(transport_code_spans("C919首飞").is_empty());
+        assert!(transport_code_spans("GB2312编码").is_empty());
+
+        // 单独成段的编号不再看上下文
+        let transport = Transport::new();
+        assert_eq!(transport.read_code("D301"), "迪三零幺");
+        assert_eq!(transport.read_code("MU5137"), "艾姆优五幺三七");
+        assert_eq!(transport.read_code("沪A·12345"), "沪诶幺二三四五");
+    }
+}
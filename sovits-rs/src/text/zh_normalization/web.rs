@@ -0,0 +1,156 @@
+use super::num::Num;
+use fancy_regex::{Captures, Regex};
+use lazy_static::lazy_static;
+
+lazy_static! {
+    // 邮箱：support@abc.cn
+    static ref RE_EMAIL: Regex = Regex::new(
+        r"(?<![A-Za-z0-9._%+-])([A-Za-z0-9._%+-]+)@((?:[A-Za-z0-9-]+\.)+[A-Za-z]{2,})(?![A-Za-z0-9-])"
+    )
+    .unwrap();
+
+    // 网址：www.example.com、https://abc.cn/help，不带协议时只认常见顶级域名
+    static ref RE_URL: Regex = Regex::new(
+        r"(?<![A-Za-z0-9.@/-])((?:https?|ftp)://)?((?:[A-Za-z0-9-]+\.)+([A-Za-z]{2,}))((?:/[A-Za-z0-9._~-]*)*)(?![A-Za-z0-9-])"
+    )
+    .unwrap();
+
+    // 版本号：v2.3.1、版本 10.0
+    static ref RE_VERSION: Regex = Regex::new(
+        r"(?<![A-Za-z0-9])(?:[vV](?:ersion)?\s?|版本\s?)(\d+(?:\.\d+){1,3})(?![\d.])"
+    )
+    .unwrap();
+
+    // IP 地址：192.168.1.1、10.0.0.1:8080
+    static ref RE_IP: Regex = Regex::new(
+        r"(?<![\d.])((?:25[0-5]|2[0-4]\d|1?\d?\d)(?:\.(?:25[0-5]|2[0-4]\d|1?\d?\d)){3})(?::(\d{1,5}))?(?![\d.])"
+    )
+    .unwrap();
+}
+
+/// 不带协议的网址需以这些域名结尾，避免把 a.b 之类的缩写当作网址
+const TOP_LEVEL_DOMAINS: [&str; 16] = [
+    "com", "cn", "net", "org", "edu", "gov", "io", "info", "top", "xyz", "app", "dev", "me", "hk",
+    "tw", "jp",
+];
+
+pub struct Web {
+    num: Num,
+}
+
+impl Web {
+    pub(crate) fn new() -> Self {
+        Self { num: Num }
+    }
+
+    /// 需在去空格、特殊符号替换之前调用，字母保留给英文读
+    pub fn normalize(&self, sentence: &str) -> String {
+        let funcs: Vec<fn(&Self, &str) -> String> = vec![
+            Self::replace_email,
+            Self::replace_url,
+            Self::replace_version,
+            Self::replace_ip,
+        ];
+
+        funcs
+            .iter()
+            .fold(sentence.to_string(), |result, func| func(self, &result))
+    }
+
+    /// 分隔符读作中文，数字逐位读，字母原样保留
+    fn speak(&self, text: &str) -> String {
+        text.chars()
+            .map(|c| match c {
+                '.' => "点".to_string(),
+                '/' => "斜杠".to_string(),
+                ':' => "冒号".to_string(),
+                '-' => "杠".to_string(),
+                '_' => "下划线".to_string(),
+                '~' => "波浪线".to_string(),
+                '+' => "加".to_string(),
+                '%' => "百分号".to_string(),
+                '0'..='9' => self.num.verbalize_digits(&c.to_string(), false),
+                _ => c.to_string(),
+            })
+            .collect()
+    }
+
+    fn replace_email(&self, sentence: &str) -> String {
+        RE_EMAIL
+            .replace_all(sentence, |caps: &Captures| {
+                format!("{} at {}", self.speak(&caps[1]), self.speak(&caps[2]))
+            })
+            .to_string()
+    }
+
+    fn replace_url(&self, sentence: &str) -> String {
+        RE_URL
+            .replace_all(sentence, |caps: &Captures| {
+                let tld = caps[3].to_lowercase();
+                if caps.get(1).is_none() && !TOP_LEVEL_DOMAINS.contains(&tld.as_str()) {
+                    return caps[0].to_string();
+                }
+                let scheme = caps.get(1).map_or("", |m| m.as_str());
+                format!(
+                    "{}{}{}",
+                    self.speak(scheme),
+                    self.speak(&caps[2]),
+                    self.speak(&caps[4])
+                )
+            })
+            .to_string()
+    }
+
+    fn replace_version(&self, sentence: &str) -> String {
+        RE_VERSION
+            .replace_all(sentence, |caps: &Captures| {
+                let parts: Vec<String> = caps[1]
+                    .split('.')
+                    .map(|part| self.num.verbalize_cardinal(part, false))
+                    .collect();
+                format!("版本{}", parts.join("点"))
+            })
+            .to_string()
+    }
+
+    fn replace_ip(&self, sentence: &str) -> String {
+        RE_IP
+            .replace_all(sentence, |caps: &Captures| match caps.get(2) {
+                Some(port) => format!("{}冒号{}", self.speak(&caps[1]), self.speak(port.as_str())),
+                None => self.speak(&caps[1]),
+            })
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_web_normalize() {
+        let web = Web::new();
+
+        let cases = vec![
+            ("访问www.example.com", "访问www点example点com"),
+            (
+                "https://abc.cn/help/faq",
+                "https冒号斜杠斜杠abc点cn斜杠help斜杠faq",
+            ),
+            ("官网163.com", "官网一六三点com"),
+            ("发邮件到support@abc.cn", "发邮件到support at abc点cn"),
+            ("地址192.168.1.1", "地址一九二点一六八点一点一"),
+            ("10.0.0.1:8080", "一零点零点零点一冒号八零八零"),
+            ("升级到v2.3.1", "升级到版本二点三点一"),
+            ("版本 10.0", "版本十点零"),
+            // 不是网址、IP 的保持不变
+            ("e.g.", "e.g."),
+            ("3.14", "3.14"),
+            ("1.2.3.456", "1.2.3.456"),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(web.normalize(input), expected, "{}", input);
+        }
+    }
+}
@@ -1,13 +1,135 @@
-use super::text::{self, symbols::SYMBOLS};
+use super::text::{
+    self,
+    symbols::{SymbolTable, SYMBOLS},
+    zh_normalization::transport::{transport_code_spans, Transport},
+};
 use lazy_static::lazy_static;
-use lingua::Language::{Chinese, English};
+use lingua::Language::{Chinese, English, Japanese};
 use lingua::{Language, LanguageDetector, LanguageDetectorBuilder};
 use regex::{Captures, Regex};
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
 
 pub(crate) const ENGLISH_LANG: &str = "English";
 pub(crate) const CHINESE_LANG: &str = "Chinese";
 pub(crate) const JAPANESE_LANG: &str = "Japanese";
+pub(crate) const CANTONESE_LANG: &str = "Cantonese";
+pub(crate) const KOREAN_LANG: &str = "Korean";
+
+/// 汉字多于英文单词（两个字母以上的字母串）时按中文为主
+fn is_chinese_dominant(sentence: &str) -> bool {
+    let han = sentence
+        .chars()
+        .filter(|c| script_of(*c) == Script::Han)
+        .count();
+    let words = PATTERN_AZ
+        .find_iter(sentence)
+        .filter(|m| m.as_str().len() > 1)
+        .count();
+    han > words
+}
+
+/// 车牌、车次、航班号等交通编号读成汉字（字母名和逐位数字）留在中文（或粤语）lang 里，
+/// 分段后编号单独成段、看不到“次”、航班等上下文，所以在这里按整句确认后直接读出；
+/// 其余部分交给 seg 分段。只处理以中文为主的句子，英文句子中的 G20 等照常按英文读
+fn keep_transport_codes<F>(sentence: &str, lang: &str, seg: F) -> Vec<(String, String)>
+where
+    F: Fn(&str) -> Vec<(String, String)>,
+{
+    if !is_chinese_dominant(sentence) {
+        return seg(sentence);
+    }
+    let transport = Transport::new();
+    let mut out = vec![];
+    let mut last = 0;
+    for (start, end) in transport_code_spans(sentence) {
+        if start > last {
+            out.extend(seg(&sentence[last..start]));
+        }
+        out.push((lang.to_string(), transport.read_code(&sentence[start..end])));
+        last = end;
+    }
+    if last < sentence.len() {
+        out.extend(seg(&sentence[last..]));
+    }
+    out
+}
+
+/// 请求或 <lang xx> 标签中的语言选项
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum LanguageOption {
+    /// 自动检测
+    Auto,
+    /// 不做检测，整段按指定语言处理，其中的英文字母仍按英文读
+    Forced(&'static str),
+    /// 只有中英文：字母连同相连的数字按英文读（iPhone15），其余按中文
+    ZhEnMixed,
+}
+
+impl LanguageOption {
+    /// auto 自动检测；粤语无法与普通话区分，只能指定
+    pub(crate) fn parse(language: &str) -> Result<Self, String> {
+        match language {
+            "auto" | "" => Ok(Self::Auto),
+            "zh" => Ok(Self::Forced(CHINESE_LANG)),
+            "en" => Ok(Self::Forced(ENGLISH_LANG)),
+            "ja" => Ok(Self::Forced(JAPANESE_LANG)),
+            "yue" => Ok(Self::Forced(CANTONESE_LANG)),
+            "ko" => Ok(Self::Forced(KOREAN_LANG)),
+            "zh-en-mixed" => Ok(Self::ZhEnMixed),
+            _ => Err(format!("unsupported language: {}", language)),
+        }
+    }
+}
+
+/// 拆出 <lang en>…</lang> 标签，标签内按标签语言处理，其余按请求选项；不支持嵌套
+///
+/// 无法识别的语言按请求选项处理，未闭合的标签到文本末尾为止
+pub(crate) fn split_lang_tags(text: &str, option: LanguageOption) -> Vec<(LanguageOption, String)> {
+    let mut out = vec![];
+    let mut current = option;
+    let mut last = 0;
+    for caps in PATTERN_LANG_TAG.captures_iter(text) {
+        let m = caps.get(0).unwrap();
+        if m.start() > last {
+            out.push((current, text[last..m.start()].to_string()));
+        }
+        current = match caps.get(1) {
+            Some(code) => LanguageOption::parse(code.as_str()).unwrap_or_else(|e| {
+                log::warn!("{} in tag {}", e, m.as_str());
+                option
+            }),
+            None => option,
+        };
+        last = m.end();
+    }
+    if last < text.len() {
+        out.push((current, text[last..].to_string()));
+    }
+    out
+}
+
+/// 切分后标签可能跨段：段尾补上 </lang>，下一段开头重新打开
+fn balance_lang_tags(texts: Vec<String>) -> Vec<String> {
+    let mut open: Option<String> = None;
+    texts
+        .into_iter()
+        .map(|text| {
+            let mut text = match open.take() {
+                Some(tag) => tag + &text,
+                None => text,
+            };
+            open = PATTERN_LANG_TAG
+                .find_iter(&text)
+                .last()
+                .filter(|m| m.as_str() != "</lang>")
+                .map(|m| m.as_str().to_string());
+            if open.is_some() {
+                text.push_str("</lang>");
+            }
+            text
+        })
+        .collect()
+}
 
 lazy_static! {
     static ref PATTERN_ALPHA_RANGE: Regex =
@@ -16,33 +138,90 @@ lazy_static! {
     static ref PATTERN_AZ: Regex = Regex::new(r"[a-zA-Z]+").unwrap();
     static ref PATTERN_2: Regex = Regex::new(r"[a-zA-Z0-9|.%]+").unwrap();
     static ref PATTERN_ZH: Regex = Regex::new(r"[\u4e00-\u9fa5]+").unwrap();
+    static ref PATTERN_LANG_TAG: Regex = Regex::new(r"<lang\s+([a-zA-Z-]+)\s*>|</lang>").unwrap();
+    static ref PATTERN_SAY_AS_TAG: Regex = Regex::new(r"</?(?:digits|cardinal)>").unwrap();
+    static ref PATTERN_KANA: Regex = Regex::new(r"[\u3040-\u30ff]").unwrap();
+    // 韩文按文字判断，连同其中的空格、数字和标点：3번 출구로 나가세요.
+    static ref PATTERN_HANGUL_SPAN: Regex = Regex::new(
+        r"[0-9]*[\uac00-\ud7a3](?:[\uac00-\ud7a3\s0-9,.?!~%:]*[\uac00-\ud7a3.,?!])?"
+    )
+    .unwrap();
+}
+
+/// 语种分段方式
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Segmenter {
+    /// lingua 检测
+    #[default]
+    Lingua,
+    /// 按字符所属文字分段，不加载 lingua 模型，适合中日韩与英文混合的文本
+    Script,
+}
+
+/// 按文字分段时字符的类别
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Script {
+    Han,
+    Kana,
+    Hangul,
+    Latin,
+    Digit,
+    Other,
+}
+
+fn script_of(c: char) -> Script {
+    match c {
+        '\u{4e00}'..='\u{9fa5}' | '\u{3400}'..='\u{4dbf}' | '々' => Script::Han,
+        '\u{3040}'..='\u{30ff}' | '\u{31f0}'..='\u{31ff}' => Script::Kana,
+        '\u{ac00}'..='\u{d7a3}' | '\u{1100}'..='\u{11ff}' | '\u{3130}'..='\u{318f}' => {
+            Script::Hangul
+        }
+        'a'..='z' | 'A'..='Z' | '\u{c0}'..='\u{24f}' => Script::Latin,
+        '0'..='9' | '０'..='９' => Script::Digit,
+        _ => Script::Other,
+    }
 }
 
 pub struct LangSegment {
     pub _splits: Vec<String>,
-    pub detector: LanguageDetector,
+    /// None 时按文字分段
+    pub detector: Option<LanguageDetector>,
 }
 
 pub struct TextUtils {
     pub lang_seg: LangSegment,
     pub lang_chinese: text::chinese::Chinese,
     pub lang_english: text::english::English,
-    pub symbol_to_id: HashMap<String, usize>,
+    pub lang_japanese: text::japanese::Japanese,
+    pub lang_cantonese: text::cantonese::Cantonese,
+    pub lang_korean: text::korean::Korean,
+    pub symbols: SymbolTable,
 }
 
+/// 单段清洗结果：音素、word2ph、规范化文本、英文词及其音素数
+type CleanedSegment = (Vec<String>, Vec<usize>, String, Vec<(String, usize)>);
+
 #[derive(Debug, Clone)]
 pub struct CleanedText {
     pub phones_list: Vec<Vec<usize>>,
     pub word2ph_list: Vec<Vec<usize>>,
     pub lang_list: Vec<String>,
     pub norm_text_list: Vec<String>,
+    /// 音素表中没有的音素，按 UNK 处理
+    pub unknown_phones: Vec<String>,
+    /// 英文分段按 English::g2p_words 切出的词及其音素数，供英文 bert 对齐；其他语言为空
+    pub en_words_list: Vec<Vec<(String, usize)>>,
 }
 
 /// 语言分割
 impl LangSegment {
-    pub fn new(languages: Vec<Language>) -> Self {
-        let detector: LanguageDetector =
-            LanguageDetectorBuilder::from_languages(&languages).build();
+    /// Script 方式不构建 lingua 检测器，languages 不起作用
+    pub fn new(segmenter: Segmenter, languages: Vec<Language>) -> Self {
+        let detector = match segmenter {
+            Segmenter::Lingua => Some(LanguageDetectorBuilder::from_languages(&languages).build()),
+            Segmenter::Script => None,
+        };
         let _splits: Vec<String> = vec![
             "，", "。", "？", "！", ",", ".", "?", "!", "~", ":", "：", "—", "…",
         ]
@@ -57,23 +236,41 @@ impl LangSegment {
     fn zh_en_seg(&self, sentence: &str, lang: &str) -> Vec<(String, String)> {
         // 一个字母都没有：直接返回原始
         // 一个中文都没有
-        if !PATTERN_AZ.is_match(sentence) || !PATTERN_ZH.is_match(sentence) {
+        if !PATTERN_AZ.is_match(sentence) || (!PATTERN_ZH.is_match(sentence) && lang != KOREAN_LANG)
+        {
             vec![(lang.to_string(), sentence.to_string())]
+        } else if lang == JAPANESE_LANG || lang == CANTONESE_LANG || lang == KOREAN_LANG {
+            // 日语、粤语、韩语只拆出字母，数字留给本语言读法
+            let mut out = vec![];
+            let mut last = 0;
+            for m in PATTERN_AZ.find_iter(sentence) {
+                if m.start() > last {
+                    out.push((lang.to_string(), sentence[last..m.start()].to_string()));
+                }
+                out.extend(self.lang_seg_texts(m.as_str()));
+                last = m.end();
+            }
+            if last < sentence.len() {
+                out.push((lang.to_string(), sentence[last..].to_string()));
+            }
+            out
         } else {
             // 包含中文、a-Z：z-Z的单独拆分
             let replacement = |caps: &Captures| format!("\n{}\n", caps.get(0).unwrap().as_str());
 
-            let caps = PATTERN_2.replace_all(sentence, replacement).to_string();
-            caps.split("\n")
-                .filter(|s| !s.is_empty())
-                .flat_map(|s| self.lang_seg_texts(s))
-                .collect()
+            keep_transport_codes(sentence, CHINESE_LANG, |s| {
+                let caps = PATTERN_2.replace_all(s, replacement).to_string();
+                caps.split("\n")
+                    .filter(|s| !s.is_empty())
+                    .flat_map(|s| self.lang_seg_texts(s))
+                    .collect()
+            })
         }
     }
 
     // "包含a-b"："包含a至b"
     fn replae_az_range(&self, sentence: &str, lang: &str) -> String {
-        let (zhi, gan) = if lang == CHINESE_LANG {
+        let (zhi, gan) = if lang == CHINESE_LANG || lang == CANTONESE_LANG {
             ("至", "杠")
         } else {
             (" to ", " ")
@@ -106,6 +303,56 @@ impl LangSegment {
         self.zh_en_seg(&self.replae_az_range(sentence, lang), lang)
     }
 
+    /// 指定语言时不做检测，只把 pattern 匹配到的含字母片段拆出按英文处理
+    fn split_letters(&self, sentence: &str, lang: &str, pattern: &Regex) -> Vec<(String, String)> {
+        let sentence = self.replae_az_range(sentence, lang);
+        if lang == ENGLISH_LANG {
+            return vec![(lang.to_string(), sentence)];
+        }
+        let split = |sentence: &str| {
+            let mut out = vec![];
+            let mut last = 0;
+            for m in pattern
+                .find_iter(sentence)
+                .filter(|m| PATTERN_AZ.is_match(m.as_str()))
+            {
+                if m.start() > last {
+                    out.push((lang.to_string(), sentence[last..m.start()].to_string()));
+                }
+                out.push((ENGLISH_LANG.to_string(), m.as_str().to_string()));
+                last = m.end();
+            }
+            if last < sentence.len() {
+                out.push((lang.to_string(), sentence[last..].to_string()));
+            }
+            out
+        };
+        if lang == CHINESE_LANG || lang == CANTONESE_LANG {
+            keep_transport_codes(&sentence, lang, split)
+        } else {
+            split(&sentence)
+        }
+    }
+
+    /// 按语言选项分段，返回的每一组对应一个语言片段，组内第一段添加标题
+    pub(crate) fn option_seg_texts(
+        &self,
+        sentence: &str,
+        option: LanguageOption,
+    ) -> Vec<Vec<(String, String)>> {
+        match option {
+            LanguageOption::Auto => self
+                .lang_seg_texts(sentence)
+                .into_iter()
+                .map(|(lang, text)| self.lang_seg_texts2(&text, &lang))
+                .collect(),
+            LanguageOption::Forced(lang) => vec![self.split_letters(sentence, lang, &PATTERN_AZ)],
+            LanguageOption::ZhEnMixed => {
+                vec![self.split_letters(sentence, CHINESE_LANG, &PATTERN_2)]
+            }
+        }
+    }
+
     /// 获取文本中的多语言
     ///
     /// "hello，Google.。我们中出了一个叛徒"
@@ -114,20 +361,37 @@ impl LangSegment {
     ///
     /// Chinese: 我们中出了一个叛徒
     pub fn lang_seg_texts(&self, sentence: &str) -> Vec<(String, String)> {
-        let results = self.detector.detect_multiple_languages_of(sentence);
-        let mut out = results
-            .iter()
-            .map(|res| {
-                (
-                    res.language().to_string(),
-                    sentence[res.start_index()..res.end_index()].to_string(),
-                )
-            })
-            .collect::<Vec<_>>();
-        // 123344 -> 纯数字、数字+标点，无法识别
-        if out.is_empty() {
-            // 默认中文
-            out.push((CHINESE_LANG.to_string(), sentence.to_string()));
+        let Some(detector) = &self.detector else {
+            return script_seg_texts(sentence);
+        };
+        if !PATTERN_HANGUL_SPAN.is_match(sentence) {
+            return lingua_seg_texts(detector, sentence);
+        }
+        // 韩文片段直接标为韩语，其余部分交给 lingua，只有空格和标点的部分并入前一段
+        let mut out: Vec<(String, String)> = vec![];
+        let push = |out: &mut Vec<(String, String)>, lang: String, text: &str| match out.last_mut()
+        {
+            Some((last_lang, last_text))
+                if *last_lang == lang || text.chars().all(|c| !c.is_alphanumeric()) =>
+            {
+                last_text.push_str(text)
+            }
+            _ => out.push((lang, text.to_string())),
+        };
+        let mut last = 0;
+        for m in PATTERN_HANGUL_SPAN.find_iter(sentence) {
+            if m.start() > last {
+                for (lang, text) in lingua_seg_texts(detector, &sentence[last..m.start()]) {
+                    push(&mut out, lang, &text);
+                }
+            }
+            push(&mut out, KOREAN_LANG.to_string(), m.as_str());
+            last = m.end();
+        }
+        if last < sentence.len() {
+            for (lang, text) in lingua_seg_texts(detector, &sentence[last..]) {
+                push(&mut out, lang, &text);
+            }
         }
         out
     }
@@ -233,38 +497,127 @@ impl LangSegment {
         // let text = self.cut2(&text, max_num);
         let texts: Vec<String> = text.split("\n").map(|s| s.to_string()).collect();
 
-        self.merge_short_text_in_array(texts, 5)
+        balance_lang_tags(self.merge_short_text_in_array(texts, 5))
+    }
+}
+
+fn lingua_seg_texts(detector: &LanguageDetector, sentence: &str) -> Vec<(String, String)> {
+    let results = detector.detect_multiple_languages_of(sentence);
+    let mut out = results
+        .iter()
+        .map(|res| {
+            (
+                res.language().to_string(),
+                sentence[res.start_index()..res.end_index()].to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+    // 含假名的句子中，汉字词按日语处理：東京駅に -> Japanese
+    if PATTERN_KANA.is_match(sentence) {
+        out = out.into_iter().fold(vec![], |mut merged, (lang, text)| {
+            let lang = if lang == CHINESE_LANG {
+                JAPANESE_LANG.to_string()
+            } else {
+                lang
+            };
+            match merged.last_mut() {
+                Some((last_lang, last_text)) if *last_lang == lang => last_text.push_str(&text),
+                _ => merged.push((lang, text)),
+            }
+            merged
+        });
+    }
+    // 123344 -> 纯数字、数字+标点，无法识别
+    if out.is_empty() {
+        // 默认中文
+        out.push((CHINESE_LANG.to_string(), sentence.to_string()));
+    }
+    out
+}
+
+/// 按文字分段：汉字为中文（句中有假名时为日语），假名为日语，谚文为韩语，拉丁字母为英文
+///
+/// 紧跟字母的数字并入英文（iPhone15），紧挨着文字的其它数字并入后面的文字（3号线、3번），
+/// 其余数字、空格和标点并入前一段，开头的并入第一段
+fn script_seg_texts(sentence: &str) -> Vec<(String, String)> {
+    let mut runs: Vec<(Script, String)> = vec![];
+    for c in sentence.chars() {
+        let script = script_of(c);
+        match runs.last_mut() {
+            Some((last, text)) if *last == script => text.push(c),
+            _ => runs.push((script, c.to_string())),
+        }
+    }
+    let han_lang = if runs.iter().any(|(script, _)| *script == Script::Kana) {
+        JAPANESE_LANG
+    } else {
+        CHINESE_LANG
+    };
+    let lang_of = |script: Script| match script {
+        Script::Han => Some(han_lang),
+        Script::Kana => Some(JAPANESE_LANG),
+        Script::Hangul => Some(KOREAN_LANG),
+        Script::Latin => Some(ENGLISH_LANG),
+        Script::Digit | Script::Other => None,
+    };
+
+    let mut out: Vec<(String, String)> = vec![];
+    let mut pending = String::new();
+    for (i, (script, text)) in runs.iter().enumerate() {
+        let lang = match script {
+            Script::Digit if i > 0 && runs[i - 1].0 == Script::Latin => Some(ENGLISH_LANG),
+            Script::Digit => runs.get(i + 1).and_then(|(next, _)| lang_of(*next)),
+            _ => lang_of(*script),
+        };
+        match (lang, out.last_mut()) {
+            (Some(lang), Some((last_lang, last_text))) if last_lang == lang => {
+                last_text.push_str(text)
+            }
+            (Some(lang), _) => out.push((lang.to_string(), std::mem::take(&mut pending) + text)),
+            (None, Some((_, last_text))) => last_text.push_str(text),
+            (None, None) => pending.push_str(text),
+        }
+    }
+    if out.is_empty() {
+        // 纯数字、标点，与 lingua 一致默认中文
+        out.push((CHINESE_LANG.to_string(), pending));
     }
+    out
 }
 
 impl TextUtils {
     /// 英语处理需要的文本
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         eng_dict_json_path: &str,
         rep_map_json_path: &str,
         ph_model_path: &str,
         phrases_dict_path: &str,
         pinyin_dict_path: &str,
+        ja_lexicon_path: &str,
+        yue_dict_path: &str,
+        zh_dict_path: &str,
+        symbols: SymbolTable,
+        segmenter: Segmenter,
     ) -> Result<Self, String> {
-        // let languages = vec![English, Chinese, Japanese];
-        let languages = vec![English, Chinese];
-        let lang_seg: LangSegment = LangSegment::new(languages);
+        let languages = vec![English, Chinese, Japanese];
+        let lang_seg: LangSegment = LangSegment::new(segmenter, languages);
         let lang_chinese =
             text::chinese::Chinese::new(rep_map_json_path, phrases_dict_path, pinyin_dict_path)
                 .unwrap();
         let lang_english = text::english::English::new(eng_dict_json_path, ph_model_path).unwrap();
-
-        let symbol_to_id = SYMBOLS
-            .iter()
-            .enumerate()
-            .map(|(i, s)| (s.to_string(), i))
-            .collect();
+        let lang_japanese = text::japanese::Japanese::new(ja_lexicon_path)?;
+        let lang_cantonese = text::cantonese::Cantonese::new(yue_dict_path, zh_dict_path)?;
+        let lang_korean = text::korean::Korean;
 
         Ok(TextUtils {
             lang_seg,
             lang_chinese,
             lang_english,
-            symbol_to_id,
+            lang_japanese,
+            lang_cantonese,
+            lang_korean,
+            symbols,
         })
     }
 
@@ -275,7 +628,7 @@ impl TextUtils {
         language: &String,
         special_s: &str,
         target_symbol: &str,
-    ) -> (Vec<String>, Vec<usize>, String) {
+    ) -> CleanedSegment {
         let text = text.replace(special_s, ",");
         let (phones, word2ph) = if language == CHINESE_LANG {
             let norm_text = self.lang_chinese.text_normalize(&text);
@@ -298,13 +651,18 @@ impl TextUtils {
                 }
             })
             .collect();
-        (new_ph, word2ph, text.to_string())
+        (new_ph, word2ph, text.to_string(), vec![])
     }
 
-    /// 单一语言的处理
-    fn clean_text_inf(&self, text: &str, language: &String) -> (Vec<String>, Vec<usize>, String) {
+    /// 单一语言的处理，英文另外返回每个词的音素数
+    fn clean_text_inf(&self, text: &str, language: &String) -> CleanedSegment {
         let (mut text, language) = {
-            if language != ENGLISH_LANG && language != CHINESE_LANG && language != JAPANESE_LANG {
+            if language != ENGLISH_LANG
+                && language != CHINESE_LANG
+                && language != JAPANESE_LANG
+                && language != CANTONESE_LANG
+                && language != KOREAN_LANG
+            {
                 (" ".to_string(), ENGLISH_LANG.to_string())
             } else {
                 (text.to_string(), language.clone())
@@ -313,15 +671,14 @@ impl TextUtils {
         let special = vec![("￥", CHINESE_LANG, "SP2"), ("^", CHINESE_LANG, "SP3")];
         for (special_s, special_l, target_symbol) in special {
             if text.contains(special_s) && language == special_l {
-                let (phones, word2ph, norm_text) =
-                    self.clean_special(&text, &language, special_s, target_symbol);
-                return (phones, word2ph, norm_text);
+                return self.clean_special(&text, &language, special_s, target_symbol);
             }
         }
 
         let mut norm_text = "".to_string();
         let mut phones: Vec<String> = vec![];
         let mut word2ph: Vec<usize> = vec![];
+        let mut en_words: Vec<(String, usize)> = vec![];
 
         if language == CHINESE_LANG {
             norm_text = self.lang_chinese.text_normalize(&text);
@@ -330,33 +687,84 @@ impl TextUtils {
             // 英文中可能多余符号
             text = self.lang_english.text_normalize(&text);
             norm_text = self.lang_chinese.replace_symbol(&text);
-            phones = self.lang_english.g2p(&norm_text);
+            let words = self.lang_english.g2p_words(&norm_text);
+            en_words = words.iter().map(|(w, p)| (w.clone(), p.len())).collect();
+            phones = words.into_iter().flat_map(|(_, p)| p).collect();
         } else if language == JAPANESE_LANG {
-            // todo
+            text = self.lang_japanese.text_normalize(&text);
+            norm_text = self.lang_chinese.replace_symbol(&text);
+            phones = self.lang_japanese.g2p(&norm_text);
+        } else if language == CANTONESE_LANG {
+            text = self.lang_cantonese.text_normalize(&text);
+            norm_text = self.lang_chinese.replace_symbol(&text);
+            phones = self.lang_cantonese.g2p(&norm_text);
+        } else if language == KOREAN_LANG {
+            text = self.lang_korean.text_normalize(&text);
+            norm_text = self.lang_chinese.replace_symbol(&text);
+            phones = self.lang_korean.g2p(&norm_text);
         }
 
-        (phones, word2ph, norm_text)
+        (phones, word2ph, norm_text, en_words)
     }
 
     /// Converts a string of text to a sequence of IDs corresponding to the symbols in the text
-    fn cleaned_text_to_sequence(&self, cleaned_texts: &[String]) -> Vec<usize> {
+    /// 音素表中没有的音素记入 unknown 并映射为 UNK
+    fn cleaned_text_to_sequence(
+        &self,
+        cleaned_texts: &[String],
+        unknown: &mut Vec<String>,
+    ) -> Vec<usize> {
         cleaned_texts
             .iter()
-            .map(|symbol| self.symbol_to_id.get(symbol).cloned().unwrap_or(0))
+            .map(|symbol| {
+                self.symbols.id(symbol).unwrap_or_else(|| {
+                    unknown.push(symbol.to_string());
+                    self.symbols.id("UNK").expect("symbol table has no UNK")
+                })
+            })
             .collect()
     }
 
     /// 可以是混合中英文的原始文本
     pub fn get_cleaned_text_final(&self, short_text: &str) -> CleanedText {
-        let seg_texts = self.lang_seg.lang_seg_texts(short_text);
+        self.get_cleaned_text_lang(short_text, LanguageOption::Auto)
+    }
+
+    /// 中文语境下先把读法标签、网址、邮箱、带字母的时间写法读作中文，否则分段时会被拆散；
+    /// 其他语言不支持读法标签，只去掉标签
+    fn normalize_latin(&self, text: &str, option: LanguageOption) -> String {
+        let chinese = match option {
+            LanguageOption::Forced(lang) => lang == CHINESE_LANG || lang == CANTONESE_LANG,
+            // 英文为主的句子里夹了几个汉字时，8pm、网址等照常按英文读
+            _ => is_chinese_dominant(text) && !PATTERN_KANA.is_match(text),
+        };
+        if chinese {
+            self.lang_chinese.normalize_latin(text)
+        } else {
+            PATTERN_SAY_AS_TAG.replace_all(text, "").to_string()
+        }
+    }
+
+    /// option 为请求中的语言选项，文本中的 <lang xx> 标签优先
+    pub(crate) fn get_cleaned_text_lang(
+        &self,
+        short_text: &str,
+        option: LanguageOption,
+    ) -> CleanedText {
+        let seg_texts: Vec<Vec<(String, String)>> = split_lang_tags(short_text, option)
+            .into_iter()
+            .flat_map(|(option, text)| {
+                let text = self.normalize_latin(&text, option);
+                self.lang_seg.option_seg_texts(&text, option)
+            })
+            .collect();
         let mut phones_list: Vec<Vec<usize>> = vec![];
         let mut lang_list: Vec<String> = vec![];
         let mut word2ph_list: Vec<Vec<usize>> = vec![];
         let mut norm_text_list: Vec<String> = vec![];
-        for seg in seg_texts {
-            let (lang, text) = &seg;
-
-            let seg_texts2 = self.lang_seg.lang_seg_texts2(text, lang);
+        let mut unknown_phones: Vec<String> = vec![];
+        let mut en_words_list: Vec<Vec<(String, usize)>> = vec![];
+        for seg_texts2 in seg_texts {
             for (ei, (lang2, text2)) in seg_texts2.iter().enumerate() {
                 if text2.is_empty() {
                     continue;
@@ -364,14 +772,15 @@ impl TextUtils {
                 let mut text2 = text2.clone();
                 // 添加标题
                 if ei == 0 && !text2.chars().nth(0).unwrap().is_numeric() {
-                    if lang2 == CHINESE_LANG {
+                    if lang2 == CHINESE_LANG || lang2 == CANTONESE_LANG {
                         text2 = "。".to_string() + &text2;
                     } else if lang2 == ENGLISH_LANG {
                         text2 = ". ".to_string() + &text2;
                     }
                 }
-                let (phones, mut word2ph, norm_text) = self.clean_text_inf(&text2, lang2);
-                let mut phones = self.cleaned_text_to_sequence(&phones);
+                let (phones, mut word2ph, norm_text, mut en_words) =
+                    self.clean_text_inf(&text2, lang2);
+                let mut phones = self.cleaned_text_to_sequence(&phones, &mut unknown_phones);
                 // todo : 合并同语言
                 let p_len = phones_list.len();
                 let lang_len = lang_list.len();
@@ -381,6 +790,7 @@ impl TextUtils {
                     if &lang_list[lang_len - 1] == lang2 {
                         phones_list[p_len - 1].append(&mut phones);
                         word2ph_list[norm_lang_len - 1].append(&mut word2ph);
+                        en_words_list[norm_lang_len - 1].append(&mut en_words);
                         // lang_list[lang_len - 1] = lang_list[lang_len - 1].to_string() + text2;
                         norm_text_list[lang_len - 1] =
                             norm_text_list[norm_lang_len - 1].to_string() + &norm_text;
@@ -392,17 +802,28 @@ impl TextUtils {
                     lang_list.push(lang2.to_string());
 
                     word2ph_list.push(word2ph);
+                    en_words_list.push(en_words);
 
                     norm_text_list.push(norm_text);
                 }
             }
         }
 
+        if !unknown_phones.is_empty() {
+            log::warn!(
+                "unknown phones {:?} in text: {}, replaced with UNK",
+                unknown_phones,
+                short_text
+            );
+        }
+
         CleanedText {
             phones_list,
             word2ph_list,
             lang_list,
             norm_text_list,
+            unknown_phones,
+            en_words_list,
         }
     }
 }
@@ -418,11 +839,17 @@ mod tests {
             "../assets/model.npz",
             "../assets/PHRASES_DICT.json",
             "../assets/PINYIN_DICT.json",
+            "../assets/ja_lexicon.json",
+            "../assets/yue_dict.json",
+            "../assets/zh_dict.json",
+            SymbolTable::v1(),
+            Segmenter::Lingua,
         )
         .expect("Failed to create TextUtils")
     }
 
     #[test]
+    #[cfg(feature = "g2p-model")]
     pub fn chinese_test0() {
         // let a="a一个";
         let text_util = create_text_utils();
@@ -437,6 +864,7 @@ mod tests {
                 word2ph_list,
                 lang_list,
                 norm_text_list,
+                ..
             } = text_util.get_cleaned_text_final(text);
 
             assert_eq!(
@@ -472,6 +900,7 @@ mod tests {
                 word2ph_list,
                 lang_list,
                 norm_text_list,
+                ..
             } = text_util.get_cleaned_text_final(text);
 
             assert_eq!(
@@ -497,6 +926,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "g2p-model")]
     pub fn chinese_test2() {
         // let a="a一个";
         let text_util = create_text_utils();
@@ -511,6 +941,7 @@ mod tests {
                 word2ph_list,
                 lang_list,
                 norm_text_list,
+                ..
             } = text_util.get_cleaned_text_final(text);
 
             assert_eq!(
@@ -545,6 +976,7 @@ mod tests {
                 word2ph_list,
                 lang_list,
                 norm_text_list,
+                ..
             } = text_util.get_cleaned_text_final(text);
 
             assert_eq!(
@@ -565,6 +997,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "g2p-model")]
     pub fn chinese_test4() {
         // let a="a一个";
         let text_util = create_text_utils();
@@ -579,6 +1012,7 @@ mod tests {
                 word2ph_list,
                 lang_list,
                 norm_text_list,
+                ..
             } = text_util.get_cleaned_text_final(text);
 
             assert_eq!(
@@ -612,6 +1046,7 @@ mod tests {
                 word2ph_list,
                 lang_list,
                 norm_text_list,
+                ..
             } = text_util.get_cleaned_text_final(text);
 
             assert_eq!(
@@ -631,6 +1066,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "g2p-model")]
     pub fn chinese_test6() {
         // let a="a一个";
         let text_util = create_text_utils();
@@ -645,6 +1081,7 @@ mod tests {
                 word2ph_list,
                 lang_list,
                 norm_text_list,
+                ..
             } = text_util.get_cleaned_text_final(text);
 
             assert_eq!(
@@ -690,6 +1127,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "g2p-model")]
     pub fn chinese_test7() {
         // let a="a一个";
         let text_util = create_text_utils();
@@ -807,6 +1245,7 @@ mod tests {
                 word2ph_list,
                 lang_list,
                 norm_text_list,
+                ..
             } = text_util.get_cleaned_text_final(text);
 
             assert_eq!(
@@ -833,4 +1272,297 @@ mod tests {
             );
         });
     }
+
+    #[test]
+    fn test_japanese_seg() {
+        let lang_seg = LangSegment::new(Segmenter::Lingua, vec![English, Chinese, Japanese]);
+        assert_eq!(
+            lang_seg.lang_seg_texts("東京駅に到着します。"),
+            vec![(
+                JAPANESE_LANG.to_string(),
+                "東京駅に到着します。".to_string()
+            )]
+        );
+        assert_eq!(
+            lang_seg.lang_seg_texts("我们中出了一个叛徒"),
+            vec![(CHINESE_LANG.to_string(), "我们中出了一个叛徒".to_string())]
+        );
+        let segs = lang_seg.lang_seg_texts2("JR山手線は3番線です", JAPANESE_LANG);
+        assert_eq!(segs[0], (ENGLISH_LANG.to_string(), "JR".to_string()));
+        assert_eq!(
+            segs[1],
+            (JAPANESE_LANG.to_string(), "山手線は3番線です".to_string())
+        );
+    }
+
+    #[test]
+    fn test_korean_seg() {
+        let lang_seg = LangSegment::new(Segmenter::Lingua, vec![English, Chinese, Japanese]);
+        assert_eq!(
+            lang_seg.lang_seg_texts("3번 출구로 나가세요."),
+            vec![(KOREAN_LANG.to_string(), "3번 출구로 나가세요.".to_string())]
+        );
+        let segs = lang_seg.lang_seg_texts("下一站是首尔站，서울역입니다。");
+        assert_eq!(
+            segs.last().unwrap(),
+            &(KOREAN_LANG.to_string(), "서울역입니다。".to_string())
+        );
+        assert_eq!(segs[0].0, CHINESE_LANG);
+        let segs = lang_seg.lang_seg_texts2("KTX 열차입니다", KOREAN_LANG);
+        assert_eq!(segs[0], (ENGLISH_LANG.to_string(), "KTX".to_string()));
+        assert_eq!(
+            segs[1],
+            (KOREAN_LANG.to_string(), " 열차입니다".to_string())
+        );
+    }
+
+    #[test]
+    fn test_language_option() {
+        let lang_seg = LangSegment::new(Segmenter::Lingua, vec![English, Chinese, Japanese]);
+        assert_eq!(
+            LanguageOption::parse("en"),
+            Ok(LanguageOption::Forced(ENGLISH_LANG))
+        );
+        assert!(LanguageOption::parse("fr").is_err());
+        let segs =
+            lang_seg.option_seg_texts("乘坐robot taxi前往", LanguageOption::Forced(CHINESE_LANG));
+        assert_eq!(
+            segs,
+            vec![vec![
+                (CHINESE_LANG.to_string(), "乘坐".to_string()),
+                (ENGLISH_LANG.to_string(), "robot".to_string()),
+                (CHINESE_LANG.to_string(), " ".to_string()),
+                (ENGLISH_LANG.to_string(), "taxi".to_string()),
+                (CHINESE_LANG.to_string(), "前往".to_string()),
+            ]]
+        );
+        // zh-en-mixed 中字母连同数字按英文读，zh 中数字按中文读
+        let segs = lang_seg.option_seg_texts("新款iPhone15", LanguageOption::ZhEnMixed);
+        assert_eq!(
+            segs[0][1],
+            (ENGLISH_LANG.to_string(), "iPhone15".to_string())
+        );
+        let segs = lang_seg.option_seg_texts("新款iPhone15", LanguageOption::Forced(CHINESE_LANG));
+        assert_eq!(segs[0][2], (CHINESE_LANG.to_string(), "15".to_string()));
+    }
+
+    #[test]
+    fn test_transport_codes() {
+        let lang_seg = LangSegment::new(Segmenter::Lingua, vec![English, Chinese, Japanese]);
+        let segs = lang_seg.lang_seg_texts2("乘坐G1234次列车", CHINESE_LANG);
+        assert!(segs.iter().all(|(lang, _)| lang == CHINESE_LANG));
+        assert_eq!(
+            segs[1],
+            (CHINESE_LANG.to_string(), "吉幺二三四".to_string())
+        );
+
+        let segs = lang_seg.option_seg_texts(
+            "车牌沪A·12345的robot taxi",
+            LanguageOption::Forced(CHINESE_LANG),
+        );
+        assert_eq!(
+            segs[0][..3],
+            [
+                (CHINESE_LANG.to_string(), "车牌".to_string()),
+                (CHINESE_LANG.to_string(), "沪诶幺二三四五".to_string()),
+                (CHINESE_LANG.to_string(), "的".to_string()),
+            ]
+        );
+        assert_eq!(segs[0][3], (ENGLISH_LANG.to_string(), "robot".to_string()));
+
+        // 英文为主的句子、没有列车航班上下文的编号不留在中文里
+        let segs = lang_seg.lang_seg_texts2("Please take train G1234次 to 北京", CHINESE_LANG);
+        assert!(
+            segs.iter()
+                .any(|(lang, text)| lang == ENGLISH_LANG && text.contains("G1234")),
+            "{:?}",
+            segs
+        );
+        let segs = lang_seg.lang_seg_texts2("Leaders met at the G20 summit in 大阪", CHINESE_LANG);
+        assert!(
+            segs.iter()
+                .any(|(lang, text)| lang == ENGLISH_LANG && text.contains("G20")),
+            "{:?}",
+            segs
+        );
+        for text in ["各国领导人出席G20峰会", "国产大飞机C919", "字符集GB2312"] {
+            let segs = lang_seg.lang_seg_texts2(text, CHINESE_LANG);
+            assert!(
+                segs.iter().any(|(lang, _)| lang == ENGLISH_LANG),
+                "{}: {:?}",
+                text,
+                segs
+            );
+        }
+    }
+
+    #[test]
+    fn test_lang_tags() {
+        assert_eq!(
+            split_lang_tags("欢迎乘坐<lang en>Metro</lang>一号线", LanguageOption::Auto),
+            vec![
+                (LanguageOption::Auto, "欢迎乘坐".to_string()),
+                (LanguageOption::Forced(ENGLISH_LANG), "Metro".to_string()),
+                (LanguageOption::Auto, "一号线".to_string()),
+            ]
+        );
+        // 无法识别的语言按请求选项处理
+        assert_eq!(
+            split_lang_tags("<lang fr>Bonjour", LanguageOption::ZhEnMixed),
+            vec![(LanguageOption::ZhEnMixed, "Bonjour".to_string())]
+        );
+        // 切分后跨段的标签补全
+        assert_eq!(
+            balance_lang_tags(vec![
+                "前方到站<lang en>Central Station, ".to_string(),
+                "please get off</lang>。".to_string(),
+            ]),
+            vec![
+                "前方到站<lang en>Central Station, </lang>",
+                "<lang en>please get off</lang>。",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_script_seg() {
+        let lang_seg = LangSegment::new(Segmenter::Script, vec![]);
+        assert!(lang_seg.detector.is_none());
+        let segs = |text: &str| {
+            lang_seg
+                .lang_seg_texts(text)
+                .into_iter()
+                .map(|(lang, text)| format!("{}:{}", lang, text))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            segs("前往Central Station的乘客，请在3号线换乘。"),
+            vec![
+                "Chinese:前往",
+                "English:Central Station",
+                "Chinese:的乘客，请在3号线换乘。"
+            ]
+        );
+        assert_eq!(
+            segs("新款iPhone15 Pro发布了"),
+            vec!["Chinese:新款", "English:iPhone15 Pro", "Chinese:发布了"]
+        );
+        assert_eq!(
+            segs("“Hello”，你好"),
+            vec!["English:“Hello”，", "Chinese:你好"]
+        );
+        assert_eq!(
+            segs("東京駅に到着します"),
+            vec!["Japanese:東京駅に到着します"]
+        );
+        assert_eq!(
+            segs("下一站是首尔站，3번 출구"),
+            vec!["Chinese:下一站是首尔站，", "Korean:3번 출구"]
+        );
+        assert_eq!(segs("12345。"), vec!["Chinese:12345。"]);
+    }
+
+    /// 与 lingua 分段对比：中日韩与英文混合的常见句子两者结果一致
+    #[test]
+    fn test_script_seg_matches_lingua() {
+        let lingua = LangSegment::new(Segmenter::Lingua, vec![English, Chinese, Japanese]);
+        let script = LangSegment::new(Segmenter::Script, vec![]);
+        let texts = [
+            "hello，Google.。我们中出了一个叛徒",
+            "今天天气不错，我准备去打篮球。I am going to play basketball today.",
+            "我的房间号是 404，希望一切顺利。",
+            "Welcome to Shanghai Hongqiao Railway Station.",
+            "欢迎乘坐本次列车。",
+            "次は東京、東京です。",
+            "서울역입니다.",
+            "下一站是首尔站，서울역입니다。",
+            "2024年3月15日",
+        ];
+        for text in texts {
+            assert_eq!(
+                script.lang_seg_texts(text),
+                lingua.lang_seg_texts(text),
+                "{}",
+                text
+            );
+        }
+        // lingua 把英文后面的日语并入英文，按文字分段能拆开
+        let text = "The next station is Shinjuku. 次は新宿です。";
+        assert_eq!(lingua.lang_seg_texts(text).len(), 1);
+        assert_eq!(
+            script.lang_seg_texts(text),
+            vec![
+                (
+                    ENGLISH_LANG.to_string(),
+                    "The next station is Shinjuku. ".to_string()
+                ),
+                (JAPANESE_LANG.to_string(), "次は新宿です。".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_latin() {
+        let text_util = create_text_utils();
+        let text = text_util.normalize_latin("我们8pm在example.com见", LanguageOption::Auto);
+        assert!(!text.contains("8pm") && !text.contains(".com"), "{}", text);
+
+        // 英文为主的句子不按中文改写
+        for text in [
+            "Meet me at 8pm at 北京 station",
+            "Visit https://example.com for 北京 tours",
+        ] {
+            assert_eq!(text_util.normalize_latin(text, LanguageOption::Auto), text);
+        }
+        let cleaned =
+            text_util.get_cleaned_text_lang("Meet me at 8pm at 北京 station", LanguageOption::Auto);
+        assert!(
+            !cleaned.norm_text_list.concat().contains("晚上八点"),
+            "{:?}",
+            cleaned.norm_text_list
+        );
+    }
+
+    #[test]
+    fn test_yue_normalizers() {
+        let mut text_util = create_text_utils();
+        text_util.symbols = SymbolTable::load("../assets/symbols_v2.json").unwrap();
+        // 车牌、车次、航班号、网址和英镑金额转写出的字，粤语字表都要有读音
+        let cases = [
+            ("车牌京Z·WJY12", "车牌京贼德达不溜杰歪一二"),
+            ("乘坐D301次列车", "乘坐迪三零一次列车"),
+            ("航班MU5137", "航班艾姆优五一三七"),
+            ("访问https://a-b.cn", "访问https冒号斜杠斜杠a杠b点cn"),
+            ("售价£20", "售价二十英镑"),
+        ];
+        for (text, expected) in cases {
+            let cleaned =
+                text_util.get_cleaned_text_lang(text, LanguageOption::Forced(CANTONESE_LANG));
+            // 第一段前有添加的句点
+            assert_eq!(
+                cleaned.norm_text_list.concat().trim_start_matches('.'),
+                expected,
+                "{}",
+                text
+            );
+            assert!(
+                cleaned.unknown_phones.is_empty(),
+                "{}: {:?}",
+                text,
+                cleaned.unknown_phones
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_phones() {
+        let text_util = create_text_utils();
+        let mut unknown = vec![];
+        let phones = text_util.cleaned_text_to_sequence(
+            &["n".to_string(), "ㄱ".to_string(), "i3".to_string()],
+            &mut unknown,
+        );
+        assert_eq!(phones, vec![227, 86, 168]);
+        assert_eq!(unknown, vec!["ㄱ"]);
+    }
 }
@@ -0,0 +1,253 @@
+use crate::model_manifest::ModelManifest;
+use anyhow::{bail, Context};
+use fnv::FnvHasher;
+use ndarray::{Array2, ArrayView2};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::hash::Hasher;
+use std::io::{Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// 语音包格式版本，格式不兼容时递增
+pub const VOICE_BUNDLE_VERSION: u32 = 2;
+
+const MANIFEST: &str = "manifest.json";
+const WAV16K: &str = "wav16k.f32";
+const WAV32K: &str = "wav32k.f32";
+const PHONES: &str = "phones.i64";
+const BERT_FEATURES: &str = "bert_features.f32";
+const PROMPT: &str = "prompt.i64";
+
+#[derive(Serialize, Deserialize, Debug)]
+struct Manifest {
+    version: u32,
+    model_version: String,
+    symbols: String,
+    sample_rate: u32,
+    ref_words: String,
+    source_checksum: String,
+    wav16k_shape: [usize; 2],
+    wav32k_shape: [usize; 2],
+    bert_features_shape: [usize; 2],
+    prompt_shape: [usize; 2],
+}
+
+/// 参考音色预处理后的结果，加载时不需要文本前端和模型
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoiceBundle {
+    /// 生成语音包时模型 manifest 的 GPT-SoVITS 版本
+    pub model_version: String,
+    /// 生成语音包时的音素表版本，phones 为该音素表的 id
+    pub symbols: String,
+    /// wav32k 的采样率，即生成语音包时模型的采样率
+    pub sample_rate: u32,
+    pub ref_words: String,
+    /// 参考音频和参考文本的校验值
+    pub source_checksum: String,
+    /// [1, n]，16k 参考音频，末尾补 0.3s 静音
+    pub wav16k: Array2<f32>,
+    /// [1, n]，按 sample_rate 重采样的参考音频
+    pub wav32k: Array2<f32>,
+    /// 参考文本的音素 id
+    pub phones: Vec<usize>,
+    /// [1024, phones]，参考文本的 BERT 特征
+    pub bert_features: Array2<f32>,
+    /// [1, T]，参考音频经 ssl 和 vq_model_latent 得到的 prompt codes
+    pub prompt: Array2<i64>,
+}
+
+/// 参考音频文件内容和参考文本的 FNV-1a 64 校验值
+pub fn source_checksum(ref_wav_path: &str, ref_words: &str) -> std::io::Result<String> {
+    let mut hasher = FnvHasher::default();
+    let mut wav = vec![];
+    File::open(ref_wav_path)?.read_to_end(&mut wav)?;
+    hasher.write(&wav);
+    hasher.write(ref_words.as_bytes());
+    Ok(format!("{:016x}", hasher.finish()))
+}
+
+fn write_entry(zip: &mut ZipWriter<File>, name: &str, data: &[u8]) -> anyhow::Result<()> {
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    zip.start_file(name, options)?;
+    zip.write_all(data)?;
+    Ok(())
+}
+
+fn read_entry(zip: &mut ZipArchive<File>, name: &str) -> anyhow::Result<Vec<u8>> {
+    let mut data = vec![];
+    zip.by_name(name)
+        .with_context(|| format!("voice bundle missing {}", name))?
+        .read_to_end(&mut data)?;
+    Ok(data)
+}
+
+fn f32_bytes(arr: ArrayView2<f32>) -> Vec<u8> {
+    arr.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+fn i64_bytes<'a>(values: impl Iterator<Item = &'a i64>) -> Vec<u8> {
+    values.flat_map(|x| x.to_le_bytes()).collect()
+}
+
+fn f32_array(data: &[u8], shape: [usize; 2]) -> anyhow::Result<Array2<f32>> {
+    let values: Vec<f32> = data
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect();
+    Ok(Array2::from_shape_vec(shape, values)?)
+}
+
+fn i64_values(data: &[u8]) -> Vec<i64> {
+    data.chunks_exact(8)
+        .map(|b| i64::from_le_bytes(b.try_into().unwrap()))
+        .collect()
+}
+
+impl VoiceBundle {
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let manifest = Manifest {
+            version: VOICE_BUNDLE_VERSION,
+            model_version: self.model_version.clone(),
+            symbols: self.symbols.clone(),
+            sample_rate: self.sample_rate,
+            ref_words: self.ref_words.clone(),
+            source_checksum: self.source_checksum.clone(),
+            wav16k_shape: self.wav16k.dim().into(),
+            wav32k_shape: self.wav32k.dim().into(),
+            bert_features_shape: self.bert_features.dim().into(),
+            prompt_shape: self.prompt.dim().into(),
+        };
+        let phones: Vec<i64> = self.phones.iter().map(|&p| p as i64).collect();
+
+        let mut zip = ZipWriter::new(File::create(path)?);
+        write_entry(&mut zip, MANIFEST, &serde_json::to_vec_pretty(&manifest)?)?;
+        write_entry(&mut zip, WAV16K, &f32_bytes(self.wav16k.view()))?;
+        write_entry(&mut zip, WAV32K, &f32_bytes(self.wav32k.view()))?;
+        write_entry(&mut zip, PHONES, &i64_bytes(phones.iter()))?;
+        write_entry(
+            &mut zip,
+            BERT_FEATURES,
+            &f32_bytes(self.bert_features.view()),
+        )?;
+        write_entry(&mut zip, PROMPT, &i64_bytes(self.prompt.iter()))?;
+        zip.finish()?;
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let file =
+            File::open(path).with_context(|| format!("open voice bundle {}", path.display()))?;
+        let mut zip = ZipArchive::new(file)?;
+        let manifest: Manifest = serde_json::from_slice(&read_entry(&mut zip, MANIFEST)?)?;
+        if manifest.version != VOICE_BUNDLE_VERSION {
+            bail!(
+                "voice bundle {} version {} is not supported, expected {}",
+                path.display(),
+                manifest.version,
+                VOICE_BUNDLE_VERSION
+            );
+        }
+
+        let phones = i64_values(&read_entry(&mut zip, PHONES)?);
+        let prompt = Array2::from_shape_vec(
+            manifest.prompt_shape,
+            i64_values(&read_entry(&mut zip, PROMPT)?),
+        )?;
+        Ok(VoiceBundle {
+            wav16k: f32_array(&read_entry(&mut zip, WAV16K)?, manifest.wav16k_shape)?,
+            wav32k: f32_array(&read_entry(&mut zip, WAV32K)?, manifest.wav32k_shape)?,
+            bert_features: f32_array(
+                &read_entry(&mut zip, BERT_FEATURES)?,
+                manifest.bert_features_shape,
+            )?,
+            phones: phones.into_iter().map(|p| p as usize).collect(),
+            prompt,
+            model_version: manifest.model_version,
+            symbols: manifest.symbols,
+            sample_rate: manifest.sample_rate,
+            ref_words: manifest.ref_words,
+            source_checksum: manifest.source_checksum,
+        })
+    }
+
+    /// 语音包的模型版本、音素表版本和采样率须与当前加载的模型一致，否则 prompt、音素 id 和参考音频不可用
+    pub fn check_manifest(&self, manifest: &ModelManifest) -> anyhow::Result<()> {
+        if self.model_version != manifest.version {
+            bail!(
+                "voice bundle was built for model {}, current model is {}",
+                self.model_version,
+                manifest.version
+            );
+        }
+        if self.symbols != manifest.symbols {
+            bail!(
+                "voice bundle was built with symbols {}, current model uses {}",
+                self.symbols,
+                manifest.symbols
+            );
+        }
+        if self.sample_rate != manifest.sample_rate {
+            bail!(
+                "voice bundle reference audio is {}Hz, current model expects {}Hz",
+                self.sample_rate,
+                manifest.sample_rate
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_load() {
+        let bundle = VoiceBundle {
+            model_version: "v2".to_string(),
+            symbols: "v2".to_string(),
+            sample_rate: 32000,
+            ref_words: "今天天气不错".to_string(),
+            source_checksum: "0123456789abcdef".to_string(),
+            wav16k: Array2::from_shape_fn((1, 16), |(_, i)| i as f32 / 16.0),
+            wav32k: Array2::from_shape_fn((1, 32), |(_, i)| -(i as f32) / 32.0),
+            phones: vec![1, 2, 3],
+            bert_features: Array2::from_shape_fn((1024, 3), |(i, j)| (i * 3 + j) as f32),
+            prompt: Array2::from_shape_vec((1, 4), vec![5, 6, 7, 1023]).unwrap(),
+        };
+        let path = std::env::temp_dir().join("sovits_test_voice.bundle");
+        bundle.save(&path).unwrap();
+        let loaded = VoiceBundle::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, bundle);
+    }
+
+    #[test]
+    fn test_check_manifest() {
+        let bundle = VoiceBundle {
+            model_version: "v1".to_string(),
+            symbols: "v1".to_string(),
+            sample_rate: 32000,
+            ref_words: String::new(),
+            source_checksum: String::new(),
+            wav16k: Array2::zeros((1, 0)),
+            wav32k: Array2::zeros((1, 0)),
+            phones: vec![],
+            bert_features: Array2::zeros((1024, 0)),
+            prompt: Array2::zeros((1, 0)),
+        };
+        let mut manifest = ModelManifest::default();
+        assert!(bundle.check_manifest(&manifest).is_ok());
+        manifest.symbols = "v2".to_string();
+        assert!(bundle.check_manifest(&manifest).is_err());
+        manifest.symbols = "v1".to_string();
+        manifest.version = "v2".to_string();
+        assert!(bundle.check_manifest(&manifest).is_err());
+        manifest.version = "v1".to_string();
+        manifest.sample_rate = 48000;
+        assert!(bundle.check_manifest(&manifest).is_err());
+    }
+}
@@ -2,6 +2,10 @@ use super::super::error::AppError;
 use super::trace::*;
 use serde::{Deserialize, Serialize};
 use serde_yaml;
+use sovits::bert_utils::EngineConfig;
+use sovits::decode_utils::BatchConfig;
+use sovits::session_config::ModelSessionsConfig;
+use sovits::{ChineseLexiconConfig, EnglishLexiconConfig, Segmenter};
 use std::path::PathBuf;
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
@@ -9,6 +13,40 @@ pub struct AppConfigItem {
     pub log_path: Option<String>,
     pub ip: String,
     pub port: u16,
+    #[serde(default)]
+    pub sessions: ModelSessionsConfig,
+    #[serde(default)]
+    pub t2s_batch: BatchConfig,
+    /// 同一请求内并行合成的分段数
+    #[serde(default = "default_parallelism")]
+    pub parallelism: usize,
+    /// 语种分段方式：lingua 或 script
+    #[serde(default)]
+    pub segmenter: Segmenter,
+    /// 中文用户词典和 jieba 自定义词典
+    #[serde(default)]
+    pub chinese_lexicon: ChineseLexiconConfig,
+    /// 英文 CMUdict、用户词典及查词顺序
+    #[serde(default)]
+    pub english_lexicon: EnglishLexiconConfig,
+}
+
+impl AppConfigItem {
+    /// 合成引擎的配置
+    pub fn engine_config(&self) -> EngineConfig {
+        EngineConfig {
+            sessions: self.sessions.clone(),
+            batch: self.t2s_batch.clone(),
+            session_pool_size: self.parallelism,
+            segmenter: self.segmenter,
+            chinese_lexicon: self.chinese_lexicon.clone(),
+            english_lexicon: self.english_lexicon.clone(),
+        }
+    }
+}
+
+fn default_parallelism() -> usize {
+    1
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug)]
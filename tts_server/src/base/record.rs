@@ -69,7 +69,7 @@ impl QueryTracker {
             self.cost_records.push_back(record.clone());
             self.cost_records
                 .make_contiguous()
-                .sort_by(|a, b| b.duration.cmp(&a.duration));
+                .sort_by_key(|r| std::cmp::Reverse(r.duration));
         }
 
         self.records.push_front(record);
@@ -4,10 +4,11 @@ pub mod tts;
 
 use tts::engine::tts_engine::TTSEngine;
 use base::record::QueryTracker;
+use std::sync::Mutex;
 
-// 定义全局状态
+// 定义全局状态，engine 不加锁，并发请求的 t2s 解码可以合批
 pub struct AppState {
     pub engine: TTSEngine,
-    pub track: QueryTracker,
+    pub track: Mutex<QueryTracker>,
 }
 
@@ -1,4 +1,5 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use sovits::bert_utils::ChBertUtils;
 use std::{path::PathBuf, process::exit};
 use tracing::{self, error, info};
 use tts_server::base::*;
@@ -6,17 +7,55 @@ use tts_server::base::*;
 #[derive(Parser, Debug)]
 struct Args {
     #[arg(short, long, value_name = "CONFIG_FILE_PATH")]
-    config: PathBuf,
+    config: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// 由参考音频和参考文本生成语音包，配置文件中的 sessions 参数可选
+    BuildVoice {
+        #[arg(long, value_name = "WAV_PATH")]
+        wav: String,
+        #[arg(long)]
+        words: String,
+        #[arg(short, long, value_name = "OUTPUT_PATH")]
+        output: PathBuf,
+    },
+}
+
+fn build_voice(
+    config: Option<&PathBuf>,
+    wav: &str,
+    words: &str,
+    output: &PathBuf,
+) -> anyhow::Result<()> {
+    let sessions = match config {
+        Some(config) => configuration::decode_config(config)?.sessions,
+        None => Default::default(),
+    };
+    ChBertUtils::build_voice_bundle(&sessions, wav, words)?.save(output)?;
+    println!("voice bundle saved to {}", output.display());
+    anyhow::Ok(())
 }
 
 pub fn main() -> anyhow::Result<()> {
     let args = Args::parse();
-    if !args.config.is_file() {
-        error!("config file is not existed: {}", args.config.display());
+    if let Some(Command::BuildVoice { wav, words, output }) = &args.command {
+        return build_voice(args.config.as_ref(), wav, words, output);
+    }
+
+    let Some(config) = args.config else {
+        error!("config file is required");
+        exit(1);
+    };
+    if !config.is_file() {
+        error!("config file is not existed: {}", config.display());
         exit(1);
     }
 
-    let config_data = configuration::decode_config(&args.config)?;
+    let config_data = configuration::decode_config(&config)?;
     let _guard = trace::init(&config_data);
 
     info!("tts_server start with config: {:#?}", config_data);
@@ -1,12 +1,11 @@
 use actix_web::{web, HttpRequest, HttpResponse};
-use std::sync::{Arc, Mutex};
 use super::super::super::AppState;
 
 #[actix_web::get("/")]
-pub async fn index(data: web::Data<Arc<Mutex<AppState>>>, _req: HttpRequest) -> HttpResponse {
-    let app_state = data.lock().unwrap();
+pub async fn index(data: web::Data<AppState>, _req: HttpRequest) -> HttpResponse {
+    let track = data.track.lock().unwrap();
 
     HttpResponse::Ok()
     .content_type("text/plain; charset=utf-8")
-    .body(app_state.track.to_table_string())
+    .body(track.to_table_string())
 }
\ No newline at end of file
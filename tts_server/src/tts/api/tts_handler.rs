@@ -1,29 +1,54 @@
 use super::super::super::AppState;
-use actix_web::{web, HttpResponse};
+use actix_web::{web, web::Bytes, HttpResponse};
 use chrono::Local;
+use futures::channel::mpsc;
+use sovits::bert_utils::ChBertUtils;
 use std::io::Cursor;
-use std::sync::{Arc, Mutex};
-use tracing::{self, info};
+use tracing::{self, error, info};
 
 #[derive(serde::Deserialize)]
 pub struct TTSQuery {
+    /// 中文数字可用 <digits>404</digits>、<cardinal>404</cardinal> 指定逐位读或按数值读
     text: String,
+    /// auto 自动检测语言；zh、en、ja、yue、ko、zh-en-mixed 指定语言，文本中的 <lang en>…</lang> 优先
+    #[serde(default = "default_language")]
+    language: String,
+}
+
+fn default_language() -> String {
+    "auto".to_string()
 }
 
 #[actix_web::get("/api/tts")]
-pub async fn api_tts(
-    data: web::Data<Arc<Mutex<AppState>>>,
-    query: web::Query<TTSQuery>,
-) -> HttpResponse {
+pub async fn api_tts(data: web::Data<AppState>, query: web::Query<TTSQuery>) -> HttpResponse {
+    if let Err(e) = ChBertUtils::check_language(&query.language) {
+        return HttpResponse::BadRequest().body(e);
+    }
     let start_time = Local::now();
     let text = &query.text;
     let mut cursor = Cursor::new(Vec::new());
-    let wav = data.get_ref().lock().unwrap().engine.synthesis(text);
+    let result = {
+        let data = data.clone();
+        let text = text.to_owned();
+        let language = query.language.to_owned();
+        web::block(move || data.engine.synthesis(&text, &language)).await
+    };
+    let wav = match result {
+        Ok(Ok(wav)) => wav,
+        Ok(Err(e)) => {
+            error!("synthesis failed: {:?} {:#}", text, e);
+            return HttpResponse::InternalServerError().body(format!("{:#}", e));
+        }
+        Err(e) => {
+            error!("synthesis failed: {:?} {}", text, e);
+            return HttpResponse::InternalServerError().body(e.to_string());
+        }
+    };
     let mut writer = hound::WavWriter::new(
         &mut cursor,
         hound::WavSpec {
             channels: 1,
-            sample_rate: 24000,
+            sample_rate: data.engine.sample_rate(),
             bits_per_sample: 16,
             sample_format: hound::SampleFormat::Int,
         },
@@ -37,7 +62,7 @@ pub async fn api_tts(
     writer.finalize().unwrap();
 
     let duration = Local::now().signed_duration_since(start_time);
-    data.get_ref().lock().unwrap().track.record_query(
+    data.track.lock().unwrap().record_query(
         text.to_owned(),
         start_time.format("%Y-%m-%d %H:%M:%S").to_string(),
         std::time::Duration::from_millis(duration.num_milliseconds() as u64),
@@ -52,3 +77,116 @@ pub async fn api_tts(
         .content_type("audio/wav")
         .body(cursor.into_inner())
 }
+
+/// 修改 zh_user_lexicon.json、zh_jieba_dict.txt 或英文词典后重新加载，无需重启服务
+#[actix_web::post("/api/lexicon/reload")]
+pub async fn api_reload_lexicon(data: web::Data<AppState>) -> HttpResponse {
+    let result = web::block(move || data.engine.reload_user_lexicon())
+        .await
+        .expect("Failed to reload user lexicon");
+    match result {
+        Ok(len) => {
+            info!("user lexicon reloaded, {} words", len);
+            HttpResponse::Ok().body(format!("{} words", len))
+        }
+        Err(e) => {
+            error!("reload user lexicon failed: {}", e);
+            HttpResponse::BadRequest().body(e)
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+pub struct ExplainQuery {
+    text: String,
+}
+
+/// 调试用：查看中文文本的分词、词性和变调结果
+#[actix_web::get("/api/debug/segment")]
+pub async fn api_explain(
+    data: web::Data<AppState>,
+    query: web::Query<ExplainQuery>,
+) -> HttpResponse {
+    let text = query.into_inner().text;
+    let traces = web::block(move || data.engine.explain_chinese(&text))
+        .await
+        .expect("Failed to explain text");
+    HttpResponse::Ok().json(traces)
+}
+
+/// 流式返回的 wav 头，长度未知时按最大值填写
+fn wav_stream_header(sample_rate: u32) -> Vec<u8> {
+    let mut header = Vec::with_capacity(44);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&u32::MAX.to_le_bytes());
+    header.extend_from_slice(b"WAVEfmt ");
+    header.extend_from_slice(&16u32.to_le_bytes());
+    header.extend_from_slice(&1u16.to_le_bytes());
+    header.extend_from_slice(&1u16.to_le_bytes());
+    header.extend_from_slice(&sample_rate.to_le_bytes());
+    header.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+    header.extend_from_slice(&2u16.to_le_bytes());
+    header.extend_from_slice(&16u16.to_le_bytes());
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&(u32::MAX - 36).to_le_bytes());
+    header
+}
+
+#[actix_web::get("/api/tts/stream")]
+pub async fn api_tts_stream(
+    data: web::Data<AppState>,
+    query: web::Query<TTSQuery>,
+) -> HttpResponse {
+    if let Err(e) = ChBertUtils::check_language(&query.language) {
+        return HttpResponse::BadRequest().body(e);
+    }
+    let start_time = Local::now();
+    let TTSQuery { text, language } = query.into_inner();
+    let (sender, receiver) = mpsc::unbounded::<Result<Bytes, std::io::Error>>();
+    let _ = sender.unbounded_send(Ok(Bytes::from(wav_stream_header(
+        data.engine.sample_rate(),
+    ))));
+
+    actix_web::rt::spawn(async move {
+        let engine_data = data.clone();
+        let engine_text = text.clone();
+        // 分段按顺序写出，第一段合成完就开始发送
+        let segment_sender = sender.clone();
+        let result = web::block(move || {
+            engine_data
+                .engine
+                .synthesis_stream(&engine_text, &language, |segment| {
+                    let bytes: Vec<u8> = segment.iter().flat_map(|s| s.to_le_bytes()).collect();
+                    let _ = segment_sender.unbounded_send(Ok(Bytes::from(bytes)));
+                })
+        })
+        .await;
+        let error = match result {
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => Some(format!("{:#}", e)),
+            Err(e) => Some(e.to_string()),
+        };
+        if let Some(e) = error {
+            error!("stream synthesis failed: {:?} {}", text, e);
+            // 已发出 wav 头，以错误中断响应，避免客户端把残缺的音频当作完整结果
+            let _ = sender.unbounded_send(Err(std::io::Error::other(e)));
+            return;
+        }
+
+        let duration = Local::now().signed_duration_since(start_time);
+        data.track.lock().unwrap().record_query(
+            text.clone(),
+            start_time.format("%Y-%m-%d %H:%M:%S").to_string(),
+            std::time::Duration::from_millis(duration.num_milliseconds() as u64),
+        );
+        info!(
+            "stream req: {:?} cost: {:.2}s",
+            text,
+            duration.num_milliseconds() as f64 / 1000.0
+        );
+    });
+
+    HttpResponse::Ok()
+        .content_type("audio/wav")
+        .streaming(receiver)
+}
@@ -1,34 +1,204 @@
+use rayon::{ThreadPool, ThreadPoolBuilder};
 use rubato::{
     Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction,
 };
-use sovits::bert_utils::ChBertUtils;
+use sovits::bert_utils::{ChBertUtils, EngineConfig};
+use sovits::WordTrace;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+
+/// 输出音频的采样率，模型输出按此重采样
+pub const OUTPUT_SAMPLE_RATE: u32 = 24000;
+/// 重采样每次处理的输入帧数
+const RESAMPLE_CHUNK: usize = 1024;
 
-#[derive(Default)]
 pub struct TTSEngine {
     engine: ChBertUtils,
+    pool: ThreadPool,
+}
+
+impl Default for TTSEngine {
+    fn default() -> Self {
+        Self::new(&EngineConfig::default())
+    }
 }
 
 impl TTSEngine {
-    pub fn synthesis(&self, text: &str) -> Vec<i16> {
-        // 32K 16bit 1channel
-        let audio = self.engine.infer(text);
-        let params = SincInterpolationParameters {
-            sinc_len: 256,
-            f_cutoff: 0.95,
-            interpolation: SincInterpolationType::Linear,
-            oversampling_factor: 256,
-            window: WindowFunction::BlackmanHarris2,
+    /// config.session_pool_size 即同一请求内并行合成的分段数，每个并发各持有一组模型 Session
+    pub fn new(config: &EngineConfig) -> Self {
+        let parallelism = config.session_pool_size.max(1);
+        Self {
+            engine: ChBertUtils::with_config(config),
+            pool: ThreadPoolBuilder::new()
+                .num_threads(parallelism)
+                .thread_name(|i| format!("tts-segment-{}", i))
+                .build()
+                .expect("Failed to build synthesis thread pool"),
+        }
+    }
+
+    pub fn reload_user_lexicon(&self) -> Result<usize, String> {
+        self.engine.reload_user_lexicon()
+    }
+
+    pub fn explain_chinese(&self, text: &str) -> Vec<WordTrace> {
+        self.engine.explain_chinese(text)
+    }
+
+    /// 合成结果的采样率
+    pub fn sample_rate(&self) -> u32 {
+        OUTPUT_SAMPLE_RATE
+    }
+
+    pub fn synthesis(&self, text: &str, language: &str) -> anyhow::Result<Vec<i16>> {
+        let mut wav = vec![];
+        self.synthesis_stream(text, language, |segment| wav.extend(segment))?;
+        Ok(wav)
+    }
+
+    /// 分段并行合成，按分段顺序回调重采样后的音频，第一段合成完即可先发出；任一分段失败时返回错误
+    pub fn synthesis_stream<F: FnMut(Vec<i16>)>(
+        &self,
+        text: &str,
+        language: &str,
+        mut on_segment: F,
+    ) -> anyhow::Result<()> {
+        let texts = self.engine.cut_texts(text);
+        let workers = self.engine.session_pool_size().min(texts.len());
+        // 按顺序领取分段，保证第一段最先开始合成
+        let next = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel();
+
+        self.pool.in_place_scope(|scope| {
+            for worker in 0..workers {
+                let sender = sender.clone();
+                let (next, texts) = (&next, &texts);
+                scope.spawn(move |_| loop {
+                    let idx = next.fetch_add(1, Ordering::SeqCst);
+                    if idx >= texts.len() {
+                        break;
+                    }
+                    let audio = self.engine.infer_segment(&texts[idx], language, worker);
+                    if audio.is_err() {
+                        // 其余分段不再开始合成
+                        next.store(texts.len(), Ordering::SeqCst);
+                    }
+                    if sender.send((idx, audio)).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(sender);
+
+            // 按分段顺序送入同一个重采样器，分段边界处不会重新开始
+            let mut resampler = StreamResampler::new(self.engine.sample_rate(), self.sample_rate());
+            let mut pending = BTreeMap::new();
+            let mut expected = 0;
+            for (idx, audio) in receiver {
+                pending.insert(idx, audio?);
+                while let Some(audio) = pending.remove(&expected) {
+                    on_segment(resampler.process(&audio));
+                    expected += 1;
+                }
+            }
+            let tail = resampler.finish();
+            if !tail.is_empty() {
+                on_segment(tail);
+            }
+            Ok(())
+        })
+    }
+}
+
+/// 按顺序送入各分段的 16bit 1channel 音频，分段之间保留滤波器状态，输出与整段一次重采样一致
+struct StreamResampler {
+    resampler: Option<SincFixedIn<f32>>,
+    ratio: f64,
+    /// 不足一块的输入
+    pending: Vec<f32>,
+    input_len: usize,
+    /// 已生成的输出帧数，含开头的滤波器延迟
+    produced: usize,
+}
+
+impl StreamResampler {
+    fn new(from: u32, to: u32) -> Self {
+        let ratio = to as f64 / from as f64;
+        let resampler = (from != to).then(|| {
+            let params = SincInterpolationParameters {
+                sinc_len: 256,
+                f_cutoff: 0.95,
+                interpolation: SincInterpolationType::Linear,
+                oversampling_factor: 256,
+                window: WindowFunction::BlackmanHarris2,
+            };
+            SincFixedIn::<f32>::new(ratio, 1.0, params, RESAMPLE_CHUNK, 1)
+                .expect("Failed to create resampler")
+        });
+        StreamResampler {
+            resampler,
+            ratio,
+            pending: vec![],
+            input_len: 0,
+            produced: 0,
+        }
+    }
+
+    /// 送入一段音频，返回目前可以输出的部分
+    fn process(&mut self, audio: &[i16]) -> Vec<i16> {
+        let Some(resampler) = &mut self.resampler else {
+            return audio.to_vec();
+        };
+        self.input_len += audio.len();
+        self.pending
+            .extend(audio.iter().map(|&x| x as f32 / i16::MAX as f32));
+        let mut output = vec![];
+        let mut start = 0;
+        while self.pending.len() - start >= RESAMPLE_CHUNK {
+            let chunk = &self.pending[start..start + RESAMPLE_CHUNK];
+            output.extend(
+                resampler
+                    .process(&[chunk], None)
+                    .expect("Failed to resample")
+                    .remove(0),
+            );
+            start += RESAMPLE_CHUNK;
+        }
+        self.pending.drain(..start);
+        self.emit(output)
+    }
+
+    /// 送入剩余的输入并冲出滤波器延迟，返回最后的输出
+    fn finish(&mut self) -> Vec<i16> {
+        let Some(resampler) = &mut self.resampler else {
+            return vec![];
         };
-        let mut resampler =
-            SincFixedIn::<f32>::new(24000_f64 / 32000_f64, 2.0, params, audio.len(), 1).unwrap();
-        let converted_data: Vec<Vec<f32>> = vec![audio
+        let delay = resampler.output_delay();
+        let total = (self.input_len as f64 * self.ratio).round() as usize;
+        let mut output = vec![];
+        let mut input = Some(std::mem::take(&mut self.pending));
+        while self.produced + output.len() < delay + total {
+            let wave_in = input.take().filter(|pending| !pending.is_empty());
+            output.extend(
+                resampler
+                    .process_partial(wave_in.as_ref().map(std::slice::from_ref), None)
+                    .expect("Failed to resample")
+                    .remove(0),
+            );
+        }
+        output.truncate(delay + total - self.produced);
+        self.emit(output)
+    }
+
+    /// 丢掉开头的滤波器延迟并转为 16bit
+    fn emit(&mut self, output: Vec<f32>) -> Vec<i16> {
+        let delay = self.resampler.as_ref().map_or(0, |r| r.output_delay());
+        let skip = delay.saturating_sub(self.produced).min(output.len());
+        self.produced += output.len();
+        output[skip..]
             .iter()
-            .map(|&x| (x as f32 / i16::MAX as f32))
-            .collect::<Vec<f32>>()];
-        let res_audio = resampler.process(&converted_data, None).unwrap();
-        res_audio
-            .into_iter()
-            .flat_map(|x| x.into_iter().map(|a| (a * i16::MAX as f32) as i16))
+            .map(|&a| (a * i16::MAX as f32) as i16)
             .collect()
     }
 }
@@ -38,16 +208,42 @@ mod tests {
     use super::*;
     use hound::{SampleFormat, WavSpec, WavWriter};
 
+    #[test]
+    fn test_stream_resampler() {
+        let audio: Vec<i16> = (0..5000)
+            .map(|i| ((i as f32 * 0.05).sin() * 10000.0) as i16)
+            .collect();
+        let mut whole = StreamResampler::new(32000, 24000);
+        let mut expected = whole.process(&audio);
+        expected.extend(whole.finish());
+        assert_eq!(expected.len(), 3750);
+
+        // 分段送入与整段一次重采样结果相同
+        let mut stream = StreamResampler::new(32000, 24000);
+        let mut output = vec![];
+        for segment in audio.chunks(1700) {
+            output.extend(stream.process(segment));
+        }
+        output.extend(stream.finish());
+        assert_eq!(output, expected);
+
+        let mut same = StreamResampler::new(24000, 24000);
+        assert_eq!(same.process(&audio[..10]), &audio[..10]);
+        assert!(same.finish().is_empty());
+    }
+
     #[test]
     fn test_synthesis() {
         println!("test_synthesis");
         let engine = TTSEngine::default();
-        let wav = engine.synthesis("今天天气不错,有50%的概率会下雨！");
+        let wav = engine
+            .synthesis("今天天气不错,有50%的概率会下雨！", "auto")
+            .expect("Failed to run synthesis");
         let mut writer = WavWriter::create(
             "tts.wav",
             WavSpec {
                 channels: 1,
-                sample_rate: 24000,
+                sample_rate: engine.sample_rate(),
                 bits_per_sample: 16,
                 sample_format: SampleFormat::Int,
             },
@@ -64,12 +260,14 @@ mod tests {
     fn test_synthesis1() {
         println!("test_synthesis1");
         let engine = TTSEngine::default();
-        let wav = engine.synthesis("乘客朋友，您好，您现在即将体验和参观的是无人之境项目，无人之境示范体验区是国家智能网联汽车上海试点示范区的重要组成部分，可支撑无人化高级别自动驾驶技术测试验证。目前已实现无人驾驶小巴，robot taxi，无人清扫等多业态无人驾驶应用场景。同时也欢迎您乘坐体验酷哇科技无人驾驶小巴，我们具备完善的功能配置，可完成十余项自动驾驶场景展示。包括路径规划，智能避障，站点停泊，临时起停，自动返场，自主泊车等，360度全景智能交互。在感知，控制，底盘，供电等各个环节，执行冗余式安全策略，切实保障乘客安全，后续将以预约形式逐步开放给社会公众。本车由上海汽车博物馆站，开往一维诶爱智行港终点站，下一站，房车中国上海基地站，车辆离站，请系好安全带。");
+        let wav = engine
+            .synthesis("乘客朋友，您好，您现在即将体验和参观的是无人之境项目，无人之境示范体验区是国家智能网联汽车上海试点示范区的重要组成部分，可支撑无人化高级别自动驾驶技术测试验证。目前已实现无人驾驶小巴，robot taxi，无人清扫等多业态无人驾驶应用场景。同时也欢迎您乘坐体验酷哇科技无人驾驶小巴，我们具备完善的功能配置，可完成十余项自动驾驶场景展示。包括路径规划，智能避障，站点停泊，临时起停，自动返场，自主泊车等，360度全景智能交互。在感知，控制，底盘，供电等各个环节，执行冗余式安全策略，切实保障乘客安全，后续将以预约形式逐步开放给社会公众。本车由上海汽车博物馆站，开往一维诶爱智行港终点站，下一站，房车中国上海基地站，车辆离站，请系好安全带。", "auto")
+            .expect("Failed to run synthesis");
         let mut writer = WavWriter::create(
             "tts1.wav",
             WavSpec {
                 channels: 1,
-                sample_rate: 24000,
+                sample_rate: engine.sample_rate(),
                 bits_per_sample: 16,
                 sample_format: SampleFormat::Int,
             },
@@ -86,12 +284,14 @@ mod tests {
     fn test_synthesis2() {
         println!("test_synthesis2");
         let engine = TTSEngine::default();
-        let wav = engine.synthesis("robot taxi, 一维诶爱.");
+        let wav = engine
+            .synthesis("robot taxi, 一维诶爱.", "auto")
+            .expect("Failed to run synthesis");
         let mut writer = WavWriter::create(
             "tts2.wav",
             WavSpec {
                 channels: 1,
-                sample_rate: 24000,
+                sample_rate: engine.sample_rate(),
                 bits_per_sample: 16,
                 sample_format: SampleFormat::Int,
             },
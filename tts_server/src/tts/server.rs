@@ -5,7 +5,7 @@ use super::engine::tts_engine::TTSEngine;
 use actix_files as fs;
 use actix_web::*;
 use chrono::{Datelike, Local, Timelike};
-use std::sync::{Arc, Mutex};
+use std::sync::Mutex;
 use tracing::{self, info};
 
 #[actix_web::main]
@@ -22,15 +22,18 @@ pub async fn start(config: &AppConfigItem) -> anyhow::Result<()> {
     );
     info!("tts_server start at {}.", nowtime);
 
-    let app_state = web::Data::new(Arc::new(Mutex::new(AppState {
-        engine: TTSEngine::default(),
-        track: QueryTracker::new(nowtime),
-    })));
+    let app_state = web::Data::new(AppState {
+        engine: TTSEngine::new(&config.engine_config()),
+        track: Mutex::new(QueryTracker::new(nowtime)),
+    });
 
     HttpServer::new(move || {
         App::new()
             .app_data(app_state.clone())
             .service(tts_handler::api_tts)
+            .service(tts_handler::api_tts_stream)
+            .service(tts_handler::api_reload_lexicon)
+            .service(tts_handler::api_explain)
             .service(index::index)
             .service(fs::Files::new("/demo", "../demo"))
             .configure(init)